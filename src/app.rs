@@ -1,18 +1,79 @@
-use std::{io, sync::mpsc};
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    io,
+    sync::mpsc::{self, RecvTimeoutError},
+    time::{Duration, Instant, SystemTime},
+};
 
-use crate::event::{self, Event};
+use crate::{
+    config::{Action, Config, Graphics, KeyBindings, Theme},
+    details::DetailsView,
+    diagnostics::DiagnosticsView,
+    diff::DiffView,
+    event::{self, Event, TopicRate},
+    export::{self, ExportPrompt, PromptKey, ReceivedMessage},
+    filter::{highlight, FilterMatch, TableFilter},
+    graph::{self, GraphSnapshot},
+    graph_view::GraphView,
+    info,
+    latency::LatencyStats,
+    preview::{self, Preview},
+    ros::{Backend, RosCommand},
+    sort::TableSort,
+    tree::{NamespaceTree, PaneRow},
+};
 
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
-    layout::{Constraint, Layout, Margin, Offset},
+    layout::{Constraint, Layout, Margin, Offset, Position},
     prelude::Rect,
-    style::{Color, Style, Stylize},
-    text::Line,
+    style::{Style, Stylize},
+    text::{Line, Span},
     widgets::{Block, BorderType, Padding, Paragraph, Row, ScrollbarState, TableState, Widget},
     DefaultTerminal, Frame,
 };
 
 const ITEM_HEIGHT: usize = 1;
 
+/// Events handled at most before a due frame is drawn, so that a flood of
+/// messages cannot hold back the screen.
+const MAX_EVENTS_PER_FRAME: usize = 1000;
+
+/// How long a notification stays in the status bar.
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Messages kept for copying and saving by echoes with a preview, whose
+/// pictures take a lot of memory.
+const PREVIEW_MESSAGES: usize = 10;
+
+/// Lines the details pane scrolls per step of the mouse wheel.
+const WHEEL_LINES: isize = 3;
+
+/// Lines the instructions popup scrolls by a page.
+const INSTRUCTIONS_PAGE: u16 = 10;
+
+/// Narrowest share of the width either column can be dragged to.
+const MIN_COLUMN_PERCENT: u16 = 10;
+
+const NODE_COLUMNS: [&str; 2] = ["Node Name", "Publishes"];
+const TOPIC_COLUMNS: [&str; 5] = ["Topic Name", "Message Type", "Pubs", "Subs", "Hz"];
+
+/// Terminals narrower than this show one pane at a time, under tabs.
+const SINGLE_PANE_WIDTH: u16 = 60;
+
+/// Below this size nothing is drawn but a message asking for more room.
+const MIN_WIDTH: u16 = 20;
+const MIN_HEIGHT: u16 = 6;
+
+/// The tabs of the single pane layout, separated by a line.
+const TABS: [(&str, PaneType); 3] = [
+    (" Nodes ", PaneType::NodePane),
+    (" Topics ", PaneType::TopicsPane),
+    (" Details ", PaneType::DetailsPane),
+];
+
 enum Movement {
     Up,
     Down,
@@ -20,13 +81,227 @@ enum Movement {
     Right,
 }
 
-#[derive(Default)]
+impl Movement {
+    fn from_key_event(
+        key_event: &crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> Option<Movement> {
+        [
+            (Action::Up, Movement::Up),
+            (Action::Down, Movement::Down),
+            (Action::Left, Movement::Left),
+            (Action::Right, Movement::Right),
+        ]
+        .into_iter()
+        .find(|(action, _)| keys.matches(*action, key_event))
+        .map(|(_, movement)| movement)
+    }
+}
+
 pub struct App {
     app_state: AppState,
 
-    details: String,
     instructions: Vec<Instruction>,
+    /// First line of the instructions popup shown, kept in range when drawn.
+    instructions_scroll: Cell<u16>,
     pane_manager: PaneManager,
+    graph: GraphSnapshot,
+    graph_view: GraphView,
+    diagnostics: DiagnosticsView,
+    config: Config,
+    /// List hidden and infrastructure nodes and topics in the panes.
+    show_hidden: bool,
+    /// Commands to the ROS backend, `None` while it is stopped.
+    ros: Option<mpsc::Sender<RosCommand>>,
+    /// Started again to restart the backend, with the sender of the UI events.
+    backend: Option<(Box<dyn Backend>, mpsc::Sender<Event>)>,
+    /// Topics whose rates the backend was last asked to measure.
+    measured_topics: Vec<String>,
+    notification: Option<Notification>,
+    /// What the details pane describes while not echoing. It keeps describing
+    /// the last selection while the details pane itself is focused.
+    info_target: Option<InfoTarget>,
+    /// Width of the left column, starts as configured and is dragged with the mouse.
+    left_column_percent: u16,
+    /// Where the panes were drawn last, to find what the mouse points at.
+    areas: Cell<PaneAreas>,
+    /// What the left mouse button holds since it was pressed.
+    drag: Option<Drag>,
+    /// Text to put on the clipboard, written to the terminal with the next frame.
+    clipboard: Option<String>,
+    /// The file name prompt for saving the messages of the echo.
+    export: Option<ExportPrompt>,
+    /// The pictures left to the terminal to draw in the last frame.
+    placed: RefCell<Vec<Placement>>,
+}
+
+/// A picture of a preview the terminal draws with kitty or sixel graphics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Placement {
+    echo: usize,
+    area: Rect,
+    frame: u64,
+}
+
+/// The screen areas of the main view at the last render, including the borders.
+#[derive(Debug, Default, Clone, Copy)]
+struct PaneAreas {
+    main: Rect,
+    /// The tab row of the single pane layout, empty otherwise.
+    tabs: Rect,
+    /// The panes not shown are empty.
+    nodes: Rect,
+    topics: Rect,
+    details: Rect,
+}
+
+impl PaneAreas {
+    fn pane_at(&self, position: Position) -> Option<PaneType> {
+        [
+            (self.nodes, PaneType::NodePane),
+            (self.topics, PaneType::TopicsPane),
+            (self.details, PaneType::DetailsPane),
+        ]
+        .into_iter()
+        .find(|(area, _)| area.contains(position))
+        .map(|(_, pane)| pane)
+    }
+
+    fn area(&self, pane: PaneType) -> Rect {
+        match pane {
+            PaneType::NodePane => self.nodes,
+            PaneType::TopicsPane => self.topics,
+            PaneType::DetailsPane => self.details,
+        }
+    }
+
+    /// The pane of the tab at `position`.
+    fn tab_at(&self, position: Position) -> Option<PaneType> {
+        if !self.tabs.contains(position) {
+            return None;
+        }
+        let mut right = self.tabs.x;
+        for (title, pane) in TABS {
+            // Each title is followed by a separator
+            right += title.chars().count() as u16 + 1;
+            if position.x < right {
+                return Some(pane);
+            }
+        }
+        None
+    }
+
+    /// Whether `position` is on either side of the border between the columns.
+    fn is_column_border(&self, position: Position) -> bool {
+        let left_edge = self.nodes.right().saturating_sub(1);
+        // The single pane layout has no columns
+        self.tabs.is_empty()
+            && self.main.contains(position)
+            && [left_edge, self.details.x].contains(&position.x)
+    }
+}
+
+/// Something dragged with the left mouse button.
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    ColumnBorder,
+    Scrollbar(PaneType),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InfoTarget {
+    Node(String),
+    Topic(String),
+}
+
+/// A topic whose messages the details pane shows, as asked for with `Action::Echo`.
+#[derive(Debug)]
+struct Echo {
+    topic: String,
+    msg_type: String,
+    view: DetailsView,
+    /// The newest messages, as many as the view keeps lines or `PREVIEW_MESSAGES`,
+    /// for copying and saving.
+    messages: VecDeque<ReceivedMessage>,
+    /// The field tree of the newest message, shown instead of the lines while on.
+    diff: Option<DiffView>,
+    latency: LatencyStats,
+    /// The picture of the newest message, for the message types that have one.
+    preview: Option<Preview>,
+    show_preview: bool,
+    /// The pinned topic whose newest pose a map shows.
+    pose_topic: Option<String>,
+}
+
+impl Echo {
+    /// The view on screen, the lines or the field tree.
+    fn shown(&self) -> &DetailsView {
+        match &self.diff {
+            Some(diff) => &diff.view,
+            None => &self.view,
+        }
+    }
+
+    fn shown_mut(&mut self) -> &mut DetailsView {
+        match &mut self.diff {
+            Some(diff) => &mut diff.view,
+            None => &mut self.view,
+        }
+    }
+
+    /// The topic, the mode of the field tree if it is on and the pose on a map.
+    fn title(&self) -> String {
+        let title = match &self.diff {
+            Some(diff) if diff.only_changes => format!("{} (changes)", self.topic),
+            Some(_) => format!("{} (diff)", self.topic),
+            None => self.topic.clone(),
+        };
+        match &self.pose_topic {
+            Some(pose_topic) => format!("{} (pose {})", title, pose_topic),
+            None => title,
+        }
+    }
+
+    fn shown_preview(&self) -> Option<&Preview> {
+        self.preview.as_ref().filter(|_| self.show_preview)
+    }
+
+    /// The preview on screen if it plots points or a map, for zooming and panning.
+    fn shown_plot_mut(&mut self) -> Option<&mut Preview> {
+        match self.show_preview {
+            true => self.preview.as_mut().filter(|preview| preview.is_plot()),
+            false => None,
+        }
+    }
+
+    /// The latency and age of the messages at the bottom of `block`, the
+    /// statistics last as they are cut first in narrow panes.
+    fn with_latency<'a>(&self, block: Block<'a>) -> Block<'a> {
+        match self.latency.summary(SystemTime::now()) {
+            Some(summary) => block.title_bottom(format!(" {} ", summary)),
+            None => block,
+        }
+    }
+
+    /// Compare the newest message with the one before in the field tree, if
+    /// it changed since the tree was built.
+    fn refresh_diff(&mut self) {
+        let Some(diff) = self.diff.as_mut().filter(|diff| diff.outdated) else {
+            return;
+        };
+        let mut newest = self.messages.iter().rev();
+        if let Some(message) = newest.next() {
+            diff.update(&message.message, newest.next().map(|m| &m.message));
+        }
+    }
+}
+
+/// A message shown in the status bar instead of the key hints for a while.
+#[derive(Debug)]
+struct Notification {
+    message: String,
+    is_error: bool,
+    shown_at: Instant,
 }
 
 #[derive(Debug, Default, Eq, PartialEq)]
@@ -34,6 +309,8 @@ enum AppState {
     #[default]
     Navigation,
     ShowingInstructions,
+    ShowingGraph,
+    ShowingDiagnostics,
     ActivePane,
     Exit,
 }
@@ -42,11 +319,18 @@ enum AppState {
 struct PaneManager {
     node_pane: NodePane,
     topics_pane: TopicPane,
-    details_pane: String,
+    /// Info about the selected node or topic.
+    details_pane: DetailsView,
+    /// Topics pinned with `Action::Echo`, in the order they were pinned.
+    echoes: Vec<Echo>,
+    /// Index into `echoes` of the one the details keys go to.
+    current_echo: usize,
+    /// Show every echo in a grid instead of one at a time under tabs.
+    echo_grid: bool,
     focused_pane: PaneType,
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 enum PaneType {
     #[default]
     NodePane,
@@ -55,25 +339,82 @@ enum PaneType {
 }
 
 impl PaneManager {
-    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> io::Result<()> {
-        use crossterm::event::{KeyCode, KeyEventKind};
+    fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> io::Result<()> {
+        use crossterm::event::KeyEventKind;
 
         if key_event.kind != KeyEventKind::Press {
             return Ok(());
         }
 
-        match key_event.code {
-            KeyCode::Left | KeyCode::Char('h') => self.previous_pane(),
-            KeyCode::Right | KeyCode::Char('l') => self.next_pane(),
-            KeyCode::Char('n') => self.focused_pane = PaneType::NodePane,
-            KeyCode::Char('t') => self.focused_pane = PaneType::TopicsPane,
-            KeyCode::Char('d') => self.focused_pane = PaneType::DetailsPane,
-            _ => {}
+        if keys.matches(Action::PreviousPane, &key_event) {
+            self.previous_pane();
+        } else if keys.matches(Action::NextPane, &key_event) {
+            self.next_pane();
+        } else if keys.matches(Action::FocusNodes, &key_event) {
+            self.focused_pane = PaneType::NodePane;
+        } else if keys.matches(Action::FocusTopics, &key_event) {
+            self.focused_pane = PaneType::TopicsPane;
+        } else if keys.matches(Action::FocusDetails, &key_event) {
+            self.focused_pane = PaneType::DetailsPane;
         }
 
         Ok(())
     }
 
+    /// Whether the filter prompt of the focused pane takes every key.
+    fn is_filter_editing(&self) -> bool {
+        match self.focused_pane {
+            PaneType::NodePane => self.node_pane.filter.is_editing(),
+            PaneType::TopicsPane => self.topics_pane.filter.is_editing(),
+            PaneType::DetailsPane => self.details().is_searching(),
+        }
+    }
+
+    /// Open the filter prompt of the focused pane, the search prompt for the details pane.
+    fn start_filter(&mut self) {
+        match self.focused_pane {
+            PaneType::NodePane => self.node_pane.filter.start_editing(),
+            PaneType::TopicsPane => self.topics_pane.filter.start_editing(),
+            PaneType::DetailsPane => self.details_mut().start_search(),
+        }
+    }
+
+    /// The view the details keys go to: the current echo while there is one,
+    /// the info otherwise.
+    fn details(&self) -> &DetailsView {
+        match self.echo() {
+            Some(echo) => echo.shown(),
+            None => &self.details_pane,
+        }
+    }
+
+    fn details_mut(&mut self) -> &mut DetailsView {
+        match self.echoes.get_mut(self.current_echo) {
+            Some(echo) => echo.shown_mut(),
+            None => &mut self.details_pane,
+        }
+    }
+
+    fn echo(&self) -> Option<&Echo> {
+        self.echoes.get(self.current_echo)
+    }
+
+    fn echo_mut(&mut self) -> Option<&mut Echo> {
+        self.echoes.get_mut(self.current_echo)
+    }
+
+    /// Make the echo `offset` places after the current one current, wrapping around.
+    fn switch_echo(&mut self, offset: isize) {
+        if !self.echoes.is_empty() {
+            let count = self.echoes.len() as isize;
+            self.current_echo = (self.current_echo as isize + offset).rem_euclid(count) as usize;
+        }
+    }
+
     fn previous_pane(&mut self) {
         self.focused_pane = match self.focused_pane {
             PaneType::NodePane => PaneType::DetailsPane,
@@ -92,73 +433,238 @@ impl PaneManager {
 }
 
 struct Instruction {
-    key_code: crossterm::event::KeyCode,
+    section: &'static str,
+    keys: String,
     description: String,
 }
 
 impl Instruction {
-    pub fn new(key_code: char, text: &str) -> Instruction {
+    pub fn new(action: Action, keys: &KeyBindings) -> Instruction {
         Instruction {
-            key_code: crossterm::event::KeyCode::Char(key_code),
-            description: text.to_string(),
+            section: action.section(),
+            keys: keys.keys(action),
+            description: action.description().to_string(),
         }
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, PartialEq)]
 struct TopicPane {
     state: TableState,
     scroll_state: ScrollbarState,
     topics: Vec<TopicData>,
+    filter: TableFilter,
+    /// The topics passing the filter, in table order.
+    visible: Vec<FilterMatch>,
+    sort: TableSort,
+    tree: NamespaceTree,
+    /// The lines of the table, one per visible topic or namespace.
+    rows: Vec<PaneRow>,
+    /// Index of the first row in view at the last render, to find clicked rows.
+    rendered_offset: Cell<usize>,
+    /// Last measured message rate of each topic, in Hz.
+    rates: HashMap<String, f64>,
+    /// Data time of the newest message of each topic, see `TopicRate::newest`.
+    newest: HashMap<String, SystemTime>,
+    /// Age of the newest message after which a topic counts as stale.
+    stale_after: Duration,
 }
 
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 struct TopicData {
     name: String,
     msg_type: String,
+    num_publishers: u32,
     num_subscribers: u32,
+    rate: Option<f64>,
+    /// Age of the newest message, once that is more than `stale_after`.
+    stale_for: Option<Duration>,
 }
 
 impl TopicData {
-    fn as_vec_string(&self) -> Vec<String> {
-        return vec![
-            self.name.clone(),
-            self.msg_type.clone(),
-            self.num_subscribers.to_string(),
-        ];
+    /// Order by one of the `TOPIC_COLUMNS`, then by name.
+    fn compare(column: usize, a: &TopicData, b: &TopicData) -> Ordering {
+        let ordering = match column {
+            1 => a.msg_type.cmp(&b.msg_type),
+            2 => a.num_publishers.cmp(&b.num_publishers),
+            3 => a.num_subscribers.cmp(&b.num_subscribers),
+            // Topics without a measured rate come first
+            4 => a.rate.partial_cmp(&b.rate).unwrap_or(Ordering::Equal),
+            _ => Ordering::Equal,
+        };
+        ordering.then_with(|| a.name.cmp(&b.name))
     }
 }
 
+/// Age of the newest message of `topic` at `now`, if it had one and it is
+/// older than `stale_after`.
+fn stale_for(
+    newest: &HashMap<String, SystemTime>,
+    stale_after: Duration,
+    topic: &str,
+    now: SystemTime,
+) -> Option<Duration> {
+    let age = now.duration_since(*newest.get(topic)?).ok()?;
+    (age > stale_after).then_some(age)
+}
+
 impl TopicPane {
-    pub fn get_rows(&self) -> Vec<Row> {
-        // Get first the data as a string
-        let topics_data_string: Vec<Vec<String>> = self.iter().map(|t| t.as_vec_string()).collect();
-
-        topics_data_string
-            .into_iter()
-            .map(|topic_data_string| {
-                Row::new(topic_data_string).style(Style::default().fg(Color::White))
+    pub fn get_rows(&self, theme: &Theme, columns: &[usize]) -> Vec<Row<'static>> {
+        let text = Style::default().fg(theme.text);
+        let matched = Style::default().fg(theme.search_match).bold();
+        let stale = Style::default().fg(theme.error);
+
+        self.rows
+            .iter()
+            .map(|row| match row {
+                PaneRow::Item {
+                    index,
+                    depth,
+                    label_start,
+                } => {
+                    let m = &self.visible[*index];
+                    let topic = &self.topics[m.index];
+                    let cells = vec![
+                        PaneRow::item_label(
+                            *depth,
+                            *label_start,
+                            &topic.name,
+                            &m.name_indices,
+                            text,
+                            matched,
+                        ),
+                        highlight(&topic.msg_type, &m.type_indices, text, matched),
+                        Line::from(topic.num_publishers.to_string()),
+                        Line::from(topic.num_subscribers.to_string()),
+                        match (topic.stale_for, topic.rate) {
+                            (Some(age), _) => {
+                                Line::styled(format!("⚠ {}", short_duration(age)), stale)
+                            }
+                            (None, Some(rate)) => Line::from(format!("{:.1}", rate)),
+                            (None, None) => Line::from("-"),
+                        },
+                    ];
+                    Row::new(pick_columns(cells, columns)).style(text)
+                }
+                PaneRow::Namespace {
+                    path,
+                    depth,
+                    count,
+                    collapsed,
+                } => Row::new(vec![PaneRow::namespace_label(
+                    path,
+                    *depth,
+                    *count,
+                    *collapsed,
+                    Style::default().fg(theme.header),
+                )]),
             })
             .collect()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &TopicData> {
-        self.topics.iter()
+    /// Replace the topics with the ones of the snapshot, keeping the selected topic selected.
+    pub fn update_from_graph(&mut self, graph: &GraphSnapshot) {
+        let selected_key = self.selected_key();
+
+        // Staleness is only known for the topics measured, so it is kept
+        let stale_for: HashMap<&str, Duration> = self
+            .topics
+            .iter()
+            .filter_map(|topic| Some((topic.name.as_str(), topic.stale_for?)))
+            .collect();
+        let topics = graph
+            .topics
+            .iter()
+            .map(|topic| TopicData {
+                name: topic.name.clone(),
+                msg_type: topic.msg_types.join(", "),
+                num_publishers: topic.publishers.len() as u32,
+                num_subscribers: topic.subscribers.len() as u32,
+                rate: self.rates.get(&topic.name).copied(),
+                stale_for: stale_for.get(topic.name.as_str()).copied(),
+            })
+            .collect();
+        self.topics = topics;
+        let listed = |topic: &String| graph.topics.iter().any(|t| &t.name == topic);
+        self.rates.retain(|topic, _| listed(topic));
+        self.newest.retain(|topic, _| listed(topic));
+        self.refresh_rows(selected_key);
+    }
+
+    pub fn selected(&self) -> Option<&TopicData> {
+        match self.rows.get(self.state.selected()?)? {
+            PaneRow::Item { index, .. } => Some(&self.topics[self.visible[*index].index]),
+            PaneRow::Namespace { .. } => None,
+        }
+    }
+
+    /// Take the rates measured up to `now`. The backend measures a few topics
+    /// at a time, the others keep their last rate and staleness.
+    pub fn update_rates(&mut self, rates: HashMap<String, TopicRate>, now: SystemTime) {
+        let selected_key = self.selected_key();
+
+        for (topic, rate) in &rates {
+            if let Some(newest) = rate.newest {
+                self.newest.insert(topic.clone(), newest);
+            }
+        }
+        for topic in &mut self.topics {
+            if let Some(rate) = rates.get(&topic.name) {
+                topic.rate = Some(rate.hz);
+                topic.stale_for = stale_for(&self.newest, self.stale_after, &topic.name, now);
+            }
+        }
+        self.rates
+            .extend(rates.into_iter().map(|(topic, rate)| (topic, rate.hz)));
+        self.refresh_rows(selected_key);
+    }
+
+    /// Sort, filter and group the topics again, selecting the row with
+    /// `selected_key` again if it is still shown.
+    fn refresh_rows(&mut self, selected_key: Option<String>) {
+        self.sort.sort(&mut self.topics, TopicData::compare);
+        self.visible = self.filter.apply(
+            self.topics
+                .iter()
+                .map(|t| (t.name.as_str(), Some(t.msg_type.as_str()))),
+        );
+        self.rows = self.tree.rows(
+            self.visible
+                .iter()
+                .map(|m| self.topics[m.index].name.as_str()),
+        );
+        self.scroll_state = self.scroll_state.content_length(self.rows.len());
+
+        let selected = selected_key.and_then(|key| {
+            self.rows
+                .iter()
+                .position(|row| row.key(|i| &self.topics[self.visible[i].index].name) == key)
+        });
+        self.select(selected);
     }
 
-    pub fn add_topic(&mut self, topic: TopicData) {
-        self.topics.push(topic);
-        self.scroll_state = ScrollbarState::new(self.topics.len());
+    fn selected_key(&self) -> Option<String> {
+        let row = self.rows.get(self.state.selected()?)?;
+        Some(row.key(|i| &self.topics[self.visible[i].index].name))
     }
 
-    pub fn remove_topic(&mut self, topic: TopicData) {
-        self.topics.retain(|t| t != &topic);
+    fn select(&mut self, index: Option<usize>) {
+        let index = match (index, self.state.selected()) {
+            (Some(i), _) => Some(i),
+            (None, Some(i)) if !self.rows.is_empty() => Some(i.min(self.rows.len() - 1)),
+            (None, _) => None,
+        };
+        self.state.select(index);
+        self.scroll_state = self.scroll_state.position(index.unwrap_or(0) * ITEM_HEIGHT);
     }
 
     pub fn next_row(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.topics.len() - 1 {
+                if i >= self.rows.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -171,10 +677,13 @@ impl TopicPane {
     }
 
     pub fn previous_row(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.topics.len() - 1
+                    self.rows.len() - 1
                 } else {
                     i - 1
                 }
@@ -185,27 +694,96 @@ impl TopicPane {
         self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
     }
 
+    /// Select the row drawn `line` lines below the header at the last render.
+    pub fn click_row(&mut self, line: usize) {
+        let index = self.rendered_offset.get() + line;
+        if index < self.rows.len() {
+            self.select(Some(index));
+        }
+    }
+
+    /// Move the selection by `delta` rows, stopping at the ends.
+    pub fn scroll_rows(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_add_signed(delta).min(self.rows.len() - 1),
+            None => 0,
+        };
+        self.select(Some(i));
+    }
+
+    /// Select the row at `fraction` of the way down the list, where the
+    /// scrollbar was dragged to.
+    pub fn select_fraction(&mut self, fraction: f64) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let last = self.rows.len() - 1;
+        self.select(Some((fraction * last as f64).round() as usize));
+    }
+
+    /// The columns that fit in `width`, dropping the publisher and subscriber
+    /// counts first, then the message type and last the rate.
+    fn columns(width: u16) -> &'static [usize] {
+        match width {
+            55.. => &[0, 1, 2, 3, 4],
+            41.. => &[0, 1, 4],
+            24.. => &[0, 4],
+            _ => &[0],
+        }
+    }
+
+    /// Sort by the next column.
     pub fn next_column(&mut self) {
-        self.state.select_next_column();
+        self.sort.next_column(TOPIC_COLUMNS.len());
+        self.refresh_rows(self.selected_key());
     }
 
+    /// Sort by the previous column.
     pub fn previous_column(&mut self) {
-        self.state.select_previous_column();
+        self.sort.previous_column();
+        self.refresh_rows(self.selected_key());
     }
 
-    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> io::Result<()> {
-        use crossterm::event::{KeyCode, KeyEventKind};
+    /// Fold or unfold the selected namespace.
+    fn toggle_fold(&mut self) {
+        if let Some(PaneRow::Namespace { path, .. }) =
+            self.state.selected().and_then(|i| self.rows.get(i))
+        {
+            self.tree.toggle_fold(&path.clone());
+            self.refresh_rows(self.selected_key());
+        }
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> io::Result<()> {
+        use crossterm::event::KeyEventKind;
 
         if key_event.kind != KeyEventKind::Press {
             return Ok(());
         }
 
-        match key_event.code {
-            KeyCode::Up | KeyCode::Char('k') => self.handle_arrow(Movement::Up)?,
-            KeyCode::Down | KeyCode::Char('j') => self.handle_arrow(Movement::Down)?,
-            KeyCode::Left | KeyCode::Char('h') => self.handle_arrow(Movement::Left)?,
-            KeyCode::Right | KeyCode::Char('l') => self.handle_arrow(Movement::Right)?,
-            _ => {}
+        if self.filter.is_editing() {
+            if self.filter.handle_key_event(key_event, keys) {
+                self.refresh_rows(self.selected_key());
+            }
+        } else if keys.matches(Action::Filter, &key_event) {
+            self.filter.start_editing();
+        } else if keys.matches(Action::ToggleSortOrder, &key_event) {
+            self.sort.toggle_order();
+            self.refresh_rows(self.selected_key());
+        } else if keys.matches(Action::ToggleTreeView, &key_event) {
+            self.tree.toggle_view();
+            self.refresh_rows(self.selected_key());
+        } else if keys.matches(Action::ToggleFold, &key_event) {
+            self.toggle_fold();
+        } else if let Some(movement) = Movement::from_key_event(&key_event, keys) {
+            self.handle_arrow(movement)?;
         }
 
         Ok(())
@@ -227,31 +805,145 @@ struct NodePane {
     state: TableState,
     scroll_state: ScrollbarState,
     nodes: Vec<NodeData>,
+    filter: TableFilter,
+    /// The nodes passing the filter, in table order.
+    visible: Vec<FilterMatch>,
+    sort: TableSort,
+    tree: NamespaceTree,
+    /// The lines of the table, one per visible node or namespace.
+    rows: Vec<PaneRow>,
+    /// Index of the first row in view at the last render, to find clicked rows.
+    rendered_offset: Cell<usize>,
 }
 
 #[derive(Debug, Default, Eq, PartialEq)]
 struct NodeData {
     name: String,
+    num_publications: usize,
+}
+
+impl NodeData {
+    /// Order by one of the `NODE_COLUMNS`, then by name.
+    fn compare(column: usize, a: &NodeData, b: &NodeData) -> Ordering {
+        let ordering = match column {
+            1 => a.num_publications.cmp(&b.num_publications),
+            _ => Ordering::Equal,
+        };
+        ordering.then_with(|| a.name.cmp(&b.name))
+    }
 }
 
 impl NodePane {
-    pub fn iter(&self) -> impl Iterator<Item = &NodeData> {
-        self.nodes.iter()
+    pub fn get_rows(&self, theme: &Theme, columns: &[usize]) -> Vec<Row<'static>> {
+        let text = Style::default().fg(theme.text);
+        let matched = Style::default().fg(theme.search_match).bold();
+
+        self.rows
+            .iter()
+            .map(|row| match row {
+                PaneRow::Item {
+                    index,
+                    depth,
+                    label_start,
+                } => {
+                    let m = &self.visible[*index];
+                    let node = &self.nodes[m.index];
+                    let cells = vec![
+                        PaneRow::item_label(
+                            *depth,
+                            *label_start,
+                            &node.name,
+                            &m.name_indices,
+                            text,
+                            matched,
+                        ),
+                        Line::from(node.num_publications.to_string()),
+                    ];
+                    Row::new(pick_columns(cells, columns)).style(text)
+                }
+                PaneRow::Namespace {
+                    path,
+                    depth,
+                    count,
+                    collapsed,
+                } => Row::new(vec![PaneRow::namespace_label(
+                    path,
+                    *depth,
+                    *count,
+                    *collapsed,
+                    Style::default().fg(theme.header),
+                )]),
+            })
+            .collect()
     }
 
-    pub fn add_node(&mut self, node: NodeData) {
-        self.nodes.push(node);
-        self.scroll_state = ScrollbarState::new(self.nodes.len());
+    /// Replace the nodes with the ones of the snapshot, keeping the selected node selected.
+    pub fn update_from_graph(&mut self, graph: &GraphSnapshot) {
+        let selected_key = self.selected_key();
+
+        self.nodes = graph
+            .nodes
+            .iter()
+            .map(|node| NodeData {
+                name: node.name.clone(),
+                num_publications: node.publishes.len(),
+            })
+            .collect();
+        self.refresh_rows(selected_key);
     }
 
-    pub fn remove_node(&mut self, node: NodeData) {
-        self.nodes.retain(|n| n != &node);
+    /// Sort, filter and group the nodes again, selecting the row with
+    /// `selected_key` again if it is still shown.
+    fn refresh_rows(&mut self, selected_key: Option<String>) {
+        self.sort.sort(&mut self.nodes, NodeData::compare);
+        self.visible = self
+            .filter
+            .apply(self.nodes.iter().map(|n| (n.name.as_str(), None)));
+        self.rows = self.tree.rows(
+            self.visible
+                .iter()
+                .map(|m| self.nodes[m.index].name.as_str()),
+        );
+        self.scroll_state = self.scroll_state.content_length(self.rows.len());
+
+        let selected = selected_key.and_then(|key| {
+            self.rows
+                .iter()
+                .position(|row| row.key(|i| &self.nodes[self.visible[i].index].name) == key)
+        });
+        self.select(selected);
+    }
+
+    fn selected_key(&self) -> Option<String> {
+        let row = self.rows.get(self.state.selected()?)?;
+        Some(row.key(|i| &self.nodes[self.visible[i].index].name))
+    }
+
+    /// The selected node, `None` when a namespace is selected.
+    pub fn selected(&self) -> Option<&NodeData> {
+        match self.rows.get(self.state.selected()?)? {
+            PaneRow::Item { index, .. } => Some(&self.nodes[self.visible[*index].index]),
+            PaneRow::Namespace { .. } => None,
+        }
+    }
+
+    fn select(&mut self, index: Option<usize>) {
+        let index = match (index, self.state.selected()) {
+            (Some(i), _) => Some(i),
+            (None, Some(i)) if !self.rows.is_empty() => Some(i.min(self.rows.len() - 1)),
+            (None, _) => None,
+        };
+        self.state.select(index);
+        self.scroll_state = self.scroll_state.position(index.unwrap_or(0) * ITEM_HEIGHT);
     }
 
     pub fn next_row(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.nodes.len() - 1 {
+                if i >= self.rows.len() - 1 {
                     0
                 } else {
                     i + 1
@@ -264,10 +956,13 @@ impl NodePane {
     }
 
     pub fn previous_row(&mut self) {
+        if self.rows.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.nodes.len() - 1
+                    self.rows.len() - 1
                 } else {
                     i - 1
                 }
@@ -278,27 +973,93 @@ impl NodePane {
         self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
     }
 
+    /// Select the row drawn `line` lines below the header at the last render.
+    pub fn click_row(&mut self, line: usize) {
+        let index = self.rendered_offset.get() + line;
+        if index < self.rows.len() {
+            self.select(Some(index));
+        }
+    }
+
+    /// Move the selection by `delta` rows, stopping at the ends.
+    pub fn scroll_rows(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => i.saturating_add_signed(delta).min(self.rows.len() - 1),
+            None => 0,
+        };
+        self.select(Some(i));
+    }
+
+    /// Select the row at `fraction` of the way down the list, where the
+    /// scrollbar was dragged to.
+    pub fn select_fraction(&mut self, fraction: f64) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let last = self.rows.len() - 1;
+        self.select(Some((fraction * last as f64).round() as usize));
+    }
+
+    /// The columns that fit in `width`, the publication count goes first.
+    fn columns(width: u16) -> &'static [usize] {
+        match width {
+            28.. => &[0, 1],
+            _ => &[0],
+        }
+    }
+
+    /// Sort by the next column.
     pub fn next_column(&mut self) {
-        self.state.select_next_column();
+        self.sort.next_column(NODE_COLUMNS.len());
+        self.refresh_rows(self.selected_key());
     }
 
+    /// Sort by the previous column.
     pub fn previous_column(&mut self) {
-        self.state.select_previous_column();
+        self.sort.previous_column();
+        self.refresh_rows(self.selected_key());
     }
 
-    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> io::Result<()> {
-        use crossterm::event::{KeyCode, KeyEventKind};
+    /// Fold or unfold the selected namespace.
+    fn toggle_fold(&mut self) {
+        if let Some(PaneRow::Namespace { path, .. }) =
+            self.state.selected().and_then(|i| self.rows.get(i))
+        {
+            self.tree.toggle_fold(&path.clone());
+            self.refresh_rows(self.selected_key());
+        }
+    }
+
+    fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> io::Result<()> {
+        use crossterm::event::KeyEventKind;
 
         if key_event.kind != KeyEventKind::Press {
             return Ok(());
         }
 
-        match key_event.code {
-            KeyCode::Up | KeyCode::Char('k') => self.handle_arrow(Movement::Up)?,
-            KeyCode::Down | KeyCode::Char('j') => self.handle_arrow(Movement::Down)?,
-            KeyCode::Left | KeyCode::Char('h') => self.handle_arrow(Movement::Left)?,
-            KeyCode::Right | KeyCode::Char('l') => self.handle_arrow(Movement::Right)?,
-            _ => {}
+        if self.filter.is_editing() {
+            if self.filter.handle_key_event(key_event, keys) {
+                self.refresh_rows(self.selected_key());
+            }
+        } else if keys.matches(Action::Filter, &key_event) {
+            self.filter.start_editing();
+        } else if keys.matches(Action::ToggleSortOrder, &key_event) {
+            self.sort.toggle_order();
+            self.refresh_rows(self.selected_key());
+        } else if keys.matches(Action::ToggleTreeView, &key_event) {
+            self.tree.toggle_view();
+            self.refresh_rows(self.selected_key());
+        } else if keys.matches(Action::ToggleFold, &key_event) {
+            self.toggle_fold();
+        } else if let Some(movement) = Movement::from_key_event(&key_event, keys) {
+            self.handle_arrow(movement)?;
         }
 
         Ok(())
@@ -316,34 +1077,44 @@ impl NodePane {
 }
 
 impl App {
-    pub fn new() -> App {
+    pub fn new(config: Config, backend: Box<dyn Backend>, events: mpsc::Sender<Event>) -> App {
         let mut app = App {
             app_state: AppState::default(),
-            details: "".to_string(),
-            instructions: vec![
-                Instruction::new('q', "Return to navigation"),
-                Instruction::new('i', "Toggle instructions"),
-                Instruction::new('j', "Down"),
-                Instruction::new('k', "Up"),
-                Instruction::new('h', "Left"),
-                Instruction::new('l', "Right"),
-                Instruction {
-                    key_code: crossterm::event::KeyCode::Enter,
-                    description: "Focus window".to_string(),
-                },
-                Instruction {
-                    key_code: crossterm::event::KeyCode::Esc,
-                    description: "Escape focused window".to_string(),
+            instructions: Action::SECTIONS
+                .iter()
+                .flat_map(|section| Action::ALL.iter().filter(|a| a.section() == *section))
+                .map(|action| Instruction::new(*action, &config.keys))
+                .collect(),
+            instructions_scroll: Cell::default(),
+            pane_manager: PaneManager {
+                details_pane: DetailsView::new(config.ui.details_max_lines),
+                topics_pane: TopicPane {
+                    stale_after: Duration::from_secs_f64(config.ui.stale_after),
+                    ..Default::default()
                 },
-            ],
-            pane_manager: PaneManager::default(),
+                ..Default::default()
+            },
+            graph: GraphSnapshot::default(),
+            graph_view: GraphView::default(),
+            diagnostics: DiagnosticsView::new(Duration::from_secs_f64(
+                config.ui.diagnostics_stale_after,
+            )),
+            show_hidden: config.filter.show_hidden,
+            left_column_percent: config.layout.left_column_percent,
+            config,
+            ros: None,
+            backend: Some((backend, events)),
+            measured_topics: vec![],
+            notification: None,
+            info_target: None,
+            areas: Cell::default(),
+            drag: None,
+            clipboard: None,
+            export: None,
+            placed: RefCell::default(),
         };
-        // Add more test nodes
-        for i in 1..15 {
-            app.pane_manager.node_pane.add_node(NodeData {
-                name: format!("/node{}", i),
-            });
-        }
+        app.start_backend();
+        app.refresh_info();
         app
     }
 
@@ -352,26 +1123,144 @@ impl App {
         terminal: &mut DefaultTerminal,
         rx: mpsc::Receiver<Event>,
     ) -> io::Result<()> {
+        let frame_interval = Duration::from_secs(1) / self.config.ui.max_fps;
+        let mut pictures = vec![];
+        self.draw_frame(terminal, &mut pictures)?;
+        let mut last_draw = Instant::now();
+        // Something changed since the last frame
+        let mut dirty = false;
+
         while self.app_state != AppState::Exit {
-            match rx.recv().unwrap() {
-                Event::Input(key_event) => self.handle_key_event(key_event)?,
-                Event::Resize(_, _) => terminal.clear()?,
-                // TODO: handle resize, for now only
-                // render the terminal again
-                Event::ROSEvent { event: ros_event } => self.handle_ros_events(ros_event)?,
+            // Sleep until the next event, or until the next frame is due
+            let first_event = match dirty {
+                true => match rx.recv_timeout(frame_interval.saturating_sub(last_draw.elapsed())) {
+                    Ok(event) => Some(event),
+                    Err(RecvTimeoutError::Timeout) => None,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(io::Error::other("event channel closed"))
+                    }
+                },
+                false => Some(rx.recv().map_err(io::Error::other)?),
+            };
+
+            // Handle everything that queued up meanwhile in one go
+            let mut redraw_now = false;
+            let events = first_event
+                .into_iter()
+                .chain(rx.try_iter().take(MAX_EVENTS_PER_FRAME));
+            for event in events {
+                if let Event::Resize(_, _) = event {
+                    terminal.clear()?;
+                    pictures.clear();
+                }
+                redraw_now |= self.handle_event(event)?;
+                dirty = true;
+            }
+
+            if let Some(text) = self.clipboard.take() {
+                crate::terminal::copy_to_clipboard(terminal.backend_mut(), &text)?;
+            }
+
+            // Input is drawn right away, messages at most once per frame
+            if redraw_now || (dirty && last_draw.elapsed() >= frame_interval) {
+                self.draw_frame(terminal, &mut pictures)?;
+                last_draw = Instant::now();
+                dirty = false;
             }
-            terminal.draw(|frame| self.draw(frame))?;
         }
         Ok(())
     }
 
+    /// Draw a frame, then the pictures of previews that the terminal draws
+    /// itself when they changed since they were drawn as `pictures`.
+    fn draw_frame(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        pictures: &mut Vec<Placement>,
+    ) -> io::Result<()> {
+        self.refresh_diffs();
+        terminal.draw(|frame| self.draw(frame))?;
+        let placed = self.placed.take();
+        if placed == *pictures {
+            return Ok(());
+        }
+        let graphics = self.config.ui.graphics;
+        crate::terminal::clear_pictures(terminal.backend_mut(), graphics)?;
+        for placement in &placed {
+            let echo = &self.pane_manager.echoes[placement.echo];
+            if let Some(picture) = echo.preview.as_ref().and_then(Preview::picture) {
+                crate::terminal::draw_picture(
+                    terminal.backend_mut(),
+                    graphics,
+                    placement.area,
+                    picture,
+                )?;
+            }
+        }
+        *pictures = placed;
+        Ok(())
+    }
+
+    /// Bring the field trees up to the newest messages, which arrive faster
+    /// than frames are drawn.
+    fn refresh_diffs(&mut self) {
+        for echo in &mut self.pane_manager.echoes {
+            echo.refresh_diff();
+        }
+    }
+
+    /// Update the state with one event, true when it should be drawn right away.
+    fn handle_event(&mut self, event: Event) -> io::Result<bool> {
+        match event {
+            Event::Input(key_event) => self.handle_key_event(key_event)?,
+            // The pointer moving over the screen changes nothing
+            Event::Mouse(mouse_event) if mouse_event.kind == MouseEventKind::Moved => {
+                return Ok(false)
+            }
+            Event::Mouse(mouse_event) => self.handle_mouse_event(mouse_event),
+            Event::Resize(_, _) => {}
+            Event::ROSEvent { event: ros_event } => {
+                self.handle_ros_events(ros_event)?;
+                return Ok(false);
+            }
+            Event::InputError(e) => return Err(e),
+        }
+        Ok(true)
+    }
+
     /// Render `self`, as we implemented the Widget trait for &App
     fn draw(&self, frame: &mut Frame) {
+        self.placed.borrow_mut().clear();
         // Split main layout into content and instructions
-        let main_layout = Layout::vertical([Constraint::Percentage(100), Constraint::Min(1)]);
-        let [main_area, instructions_area] = main_layout.areas(frame.area());
+        let area = frame.area();
+        if area.width < MIN_WIDTH || area.height < MIN_HEIGHT {
+            // Nothing to point at
+            self.areas.set(PaneAreas::default());
+            self.render_too_small(area, frame);
+            return;
+        }
 
-        self.render_main_content(main_area, frame);
+        let main_layout = Layout::vertical([Constraint::Percentage(100), Constraint::Min(1)]);
+        let [main_area, instructions_area] = main_layout.areas(area);
+
+        match self.app_state {
+            AppState::ShowingGraph => self.graph_view.render(
+                &self.graph,
+                self.pane_manager
+                    .node_pane
+                    .selected()
+                    .map(|n| n.name.as_str()),
+                main_area,
+                frame,
+                &self.config.theme,
+                &self.config.keys,
+            ),
+            AppState::ShowingDiagnostics => {
+                self.diagnostics
+                    .render(main_area, frame, &self.config.theme, &self.config.keys)
+            }
+            _ => self.render_main_content(main_area, frame),
+        }
         self.render_instructions_bar(instructions_area, frame);
 
         // Render instructions popup if needed
@@ -381,82 +1270,798 @@ impl App {
     }
 
     fn handle_ros_events(&mut self, ros_event: event::ROSEvent) -> io::Result<()> {
+        // Pictures held back by the frame-rate cap, in case their topic stopped
+        let now = Instant::now();
+        for echo in &mut self.pane_manager.echoes {
+            if let Some(preview) = &mut echo.preview {
+                preview.tick(now);
+            }
+        }
+
         match ros_event {
-            event::ROSEvent::SubscriptionMessage(msg) => {
-                self.details += &msg;
-                self.details += "\n";
+            event::ROSEvent::TopicMessage(topic, message) => {
+                // Messages still queued from an echo stopped before are dropped
+                let mut echoes = self.pane_manager.echoes.iter_mut();
+                if let Some(echo) = echoes.find(|echo| echo.topic == topic) {
+                    let received = SystemTime::now();
+                    let paused = echo.shown().is_paused();
+                    // Pictures are large, only a few of them are kept as messages
+                    let max_messages = match &mut echo.preview {
+                        Some(preview) => {
+                            echo.view.push(&preview::summary(&message));
+                            if !paused {
+                                preview.update(message.clone(), now);
+                            }
+                            PREVIEW_MESSAGES
+                        }
+                        None => {
+                            echo.view.push(&message.to_string());
+                            self.config.ui.details_max_lines
+                        }
+                    };
+                    echo.latency.record(received, &message);
+                    echo.messages
+                        .push_back(ReceivedMessage { received, message });
+                    if echo.messages.len() > max_messages {
+                        echo.messages.pop_front();
+                    }
+                    // Built when drawn, a busy topic would rebuild it for every message
+                    if let Some(diff) = &mut echo.diff {
+                        diff.outdated = true;
+                    }
+                }
             }
-            event::ROSEvent::NewNode(name) => {
-                self.pane_manager.node_pane.add_node(NodeData { name });
+            event::ROSEvent::ServiceResponse(service, response) => {
+                self.notify(format!("{}: {}", service, response));
             }
-            event::ROSEvent::NewTopic(name, msg_type) => {
-                self.pane_manager.topics_pane.add_topic(TopicData {
-                    name,
-                    num_subscribers: 0,
-                    msg_type,
-                });
+            event::ROSEvent::BackendError(error) => self.notify_error(error),
+            event::ROSEvent::BackendStopped(reason) => {
+                self.ros = None;
+                self.notify_error(format!("ROS backend stopped: {}", reason));
+            }
+            event::ROSEvent::GraphUpdate(graph) => {
+                if graph != self.graph {
+                    self.graph = graph;
+                    self.update_panes();
+                    self.refresh_info();
+                }
+            }
+            event::ROSEvent::Diagnostics(topic, statuses) => {
+                self.diagnostics.update(&topic, statuses);
+            }
+            event::ROSEvent::TopicRates(rates) => {
+                self.pane_manager
+                    .topics_pane
+                    .update_rates(rates, SystemTime::now());
+                self.refresh_info();
             }
         }
         return Ok(());
     }
 
+    /// Start a ROS backend, which replaces the one running before.
+    fn start_backend(&mut self) {
+        let Some((backend, events)) = &self.backend else {
+            return;
+        };
+        match backend.spawn(events.clone()) {
+            Ok(ros) => {
+                // The old backend exits once its sender is dropped here
+                self.ros = Some(ros);
+                self.measured_topics.clear();
+                for echo in &self.pane_manager.echoes {
+                    self.send_command(RosCommand::Subscribe {
+                        topic: echo.topic.clone(),
+                        msg_type: echo.msg_type.clone(),
+                    });
+                }
+                self.measure_rates();
+            }
+            Err(e) => {
+                self.ros = None;
+                self.notify_error(format!("cannot start the ROS backend: {}", e));
+            }
+        }
+    }
+
+    /// Pin an echo of the topic selected in the topics pane, or unpin it when
+    /// it is pinned already. In the details pane unpin the current echo.
+    fn toggle_echo(&mut self) {
+        let panes = &self.pane_manager;
+        let index = match panes.focused_pane {
+            PaneType::TopicsPane => {
+                let Some(topic) = panes.topics_pane.selected() else {
+                    return;
+                };
+                match panes
+                    .echoes
+                    .iter()
+                    .position(|echo| echo.topic == topic.name)
+                {
+                    Some(index) => index,
+                    None => return self.pin_echo(topic.name.clone(), topic.msg_type.clone()),
+                }
+            }
+            PaneType::DetailsPane if !panes.echoes.is_empty() => panes.current_echo,
+            _ => return,
+        };
+
+        let echo = self.pane_manager.echoes.remove(index);
+        let panes = &mut self.pane_manager;
+        for map in &mut panes.echoes {
+            if map.pose_topic.as_ref() == Some(&echo.topic) {
+                map.pose_topic = None;
+            }
+        }
+        if panes.current_echo > index || panes.current_echo == panes.echoes.len() {
+            panes.current_echo = panes.current_echo.saturating_sub(1);
+        }
+        self.send_command(RosCommand::Unsubscribe { topic: echo.topic });
+    }
+
+    /// Subscribe to `topic` and show it in a new echo, which becomes the current one.
+    fn pin_echo(&mut self, topic: String, msg_type: String) {
+        let preview = Preview::new(&msg_type, self.config.ui.preview_fps);
+        let echo = Echo {
+            topic,
+            msg_type,
+            view: DetailsView::new(self.config.ui.details_max_lines),
+            messages: VecDeque::new(),
+            diff: None,
+            latency: LatencyStats::default(),
+            preview,
+            show_preview: true,
+            pose_topic: None,
+        };
+        self.send_command(RosCommand::Subscribe {
+            topic: echo.topic.clone(),
+            msg_type: echo.msg_type.clone(),
+        });
+        self.pane_manager.echoes.push(echo);
+        self.pane_manager.current_echo = self.pane_manager.echoes.len() - 1;
+    }
+
+    /// Describe the selection of the focused pane in the details pane.
+    fn refresh_info(&mut self) {
+        let target = match self.pane_manager.focused_pane {
+            PaneType::NodePane => self
+                .pane_manager
+                .node_pane
+                .selected()
+                .map(|node| InfoTarget::Node(node.name.clone())),
+            PaneType::TopicsPane => self
+                .pane_manager
+                .topics_pane
+                .selected()
+                .map(|topic| InfoTarget::Topic(topic.name.clone())),
+            PaneType::DetailsPane => self.info_target.clone(),
+        };
+        let text = match &target {
+            Some(InfoTarget::Node(name)) => info::node_info(&self.shown_graph(), name),
+            Some(InfoTarget::Topic(name)) => {
+                let topics = &self.pane_manager.topics_pane;
+                let rate = topics.rates.get(name).copied();
+                let stale_for = topics
+                    .topics
+                    .iter()
+                    .find(|topic| &topic.name == name)
+                    .and_then(|topic| topic.stale_for);
+                info::topic_info(&self.shown_graph(), name, rate, stale_for)
+            }
+            None => format!(
+                "Select a node or topic to see its details.\nPress {} on a topic to echo its messages.",
+                self.config.keys.keys(Action::Echo)
+            ),
+        };
+
+        let details = &mut self.pane_manager.details_pane;
+        details.set_text(&text);
+        if target != self.info_target {
+            details.scroll_to_top();
+            self.info_target = target;
+        }
+        self.measure_rates();
+    }
+
+    /// Ask the backend for the rates of the listed and the echoed topics, when
+    /// they changed since the last time.
+    fn measure_rates(&mut self) {
+        let panes = &self.pane_manager;
+        let listed = panes.topics_pane.topics.iter().map(|topic| &topic.name);
+        let mut topics: Vec<String> = listed
+            .chain(panes.echoes.iter().map(|echo| &echo.topic))
+            .cloned()
+            .collect();
+        topics.sort();
+        topics.dedup();
+        if topics != self.measured_topics {
+            self.measured_topics = topics.clone();
+            self.send_command(RosCommand::MeasureRates { topics });
+        }
+    }
+
+    /// Copy the name selected in the focused pane, or what the details pane
+    /// shows: the newest message of an echo, the description otherwise. With
+    /// `msg_type` copy the type of the selected or echoed topic instead.
+    fn yank(&mut self, msg_type: bool) {
+        let panes = &self.pane_manager;
+        let text = match (&panes.focused_pane, panes.echo(), msg_type) {
+            (PaneType::NodePane, _, false) => panes.node_pane.selected_key(),
+            (PaneType::TopicsPane, _, false) => panes.topics_pane.selected_key(),
+            (PaneType::TopicsPane, _, true) => panes
+                .topics_pane
+                .selected()
+                .map(|topic| topic.msg_type.clone()),
+            (PaneType::DetailsPane, Some(echo), false) => echo
+                .messages
+                .back()
+                .and_then(|newest| serde_json::to_string_pretty(&newest.message).ok()),
+            (PaneType::DetailsPane, Some(echo), true) => Some(echo.msg_type.clone()),
+            (PaneType::DetailsPane, None, false) => {
+                self.info_target.as_ref().map(|_| panes.details_pane.text())
+            }
+            (PaneType::NodePane, _, true) | (PaneType::DetailsPane, None, true) => None,
+        };
+        let Some(text) = text.filter(|text| !text.is_empty()) else {
+            self.notify_error("Nothing to copy");
+            return;
+        };
+
+        self.notify(match text.lines().count() {
+            0 | 1 => format!("Copied {}", text),
+            lines => format!("Copied {} lines", lines),
+        });
+        self.clipboard = Some(text);
+    }
+
+    /// Switch the current echo between its lines and the field tree of the
+    /// newest message. With `only_changes` switch the tree between all fields
+    /// and the changed ones, turning it on first if needed.
+    fn toggle_diff(&mut self, only_changes: bool) {
+        let max_lines = self.config.ui.details_max_lines;
+        let Some(echo) = self.pane_manager.echo_mut() else {
+            return self.notify_error("Echo a topic to compare its messages");
+        };
+        if let Some(diff) = echo.diff.as_mut().filter(|_| only_changes) {
+            diff.only_changes = !diff.only_changes;
+            diff.outdated = true;
+        } else if echo.diff.is_some() {
+            echo.diff = None;
+        } else {
+            echo.diff = Some(DiffView::new(max_lines, only_changes));
+        }
+        echo.refresh_diff();
+    }
+
+    /// Switch the current echo between its preview and its lines.
+    fn toggle_preview(&mut self) {
+        match self.pane_manager.echo_mut() {
+            Some(echo) if echo.preview.is_some() => echo.show_preview = !echo.show_preview,
+            Some(echo) => {
+                let message = format!("No preview for {}", echo.msg_type);
+                self.notify_error(message)
+            }
+            None => {
+                self.notify_error("Echo an image, scan, point cloud or map topic to preview it")
+            }
+        }
+    }
+
+    /// Overlay the pose of the next pinned pose or odometry topic on the map of
+    /// the current echo, after the last one none.
+    fn cycle_pose_topic(&mut self) {
+        let panes = &mut self.pane_manager;
+        let poses: Vec<String> = panes
+            .echoes
+            .iter()
+            .filter(|echo| preview::has_pose(&echo.msg_type))
+            .map(|echo| echo.topic.clone())
+            .collect();
+        let Some(echo) = panes
+            .echo_mut()
+            .filter(|echo| echo.preview.as_ref().is_some_and(Preview::is_map))
+        else {
+            return self.notify_error("Echo an occupancy grid topic to overlay a pose on it");
+        };
+        if poses.is_empty() {
+            let message = format!(
+                "Echo a pose or odometry topic with {} to overlay it",
+                self.config.keys.keys(Action::Echo)
+            );
+            return self.notify_error(message);
+        }
+        let next = match &echo.pose_topic {
+            Some(topic) => poses
+                .iter()
+                .position(|pose| pose == topic)
+                .map_or(0, |i| i + 1),
+            None => 0,
+        };
+        echo.pose_topic = poses.get(next).cloned();
+    }
+
+    /// Ask where to save the messages of the echo.
+    fn start_export(&mut self) {
+        match self.pane_manager.echo() {
+            Some(echo) if !echo.messages.is_empty() => {
+                self.export = Some(ExportPrompt::new(&echo.topic, SystemTime::now()));
+            }
+            Some(echo) => self.notify_error(format!("No messages of {} yet", echo.topic)),
+            None => self.notify_error(format!(
+                "Echo a topic with {} to save its messages",
+                self.config.keys.keys(Action::Echo)
+            )),
+        }
+    }
+
+    fn handle_export_key_event(&mut self, key_event: crossterm::event::KeyEvent) {
+        let Some(prompt) = &mut self.export else {
+            return;
+        };
+        match prompt.handle_key_event(key_event) {
+            PromptKey::Edited => return,
+            PromptKey::Cancelled => {}
+            PromptKey::Confirmed => {
+                let Some(echo) = self.pane_manager.echo() else {
+                    return;
+                };
+                let path = prompt.path.clone();
+                match export::save(&path, &echo.messages, prompt.count()) {
+                    Ok(1) => self.notify(format!("Saved 1 message to {}", path)),
+                    Ok(count) => self.notify(format!("Saved {} messages to {}", count, path)),
+                    Err(e) => self.notify_error(format!("cannot save {}: {}", path, e)),
+                }
+            }
+        }
+        self.export = None;
+    }
+
+    fn notify(&mut self, message: impl Into<String>) {
+        self.notification = Some(Notification {
+            message: message.into(),
+            is_error: false,
+            shown_at: Instant::now(),
+        });
+    }
+
+    fn notify_error(&mut self, message: impl Into<String>) {
+        self.notification = Some(Notification {
+            message: message.into(),
+            is_error: true,
+            shown_at: Instant::now(),
+        });
+    }
+
+    fn send_command(&self, command: RosCommand) {
+        if let Some(ros) = &self.ros {
+            // A send only fails when the backend is gone, which it reports itself
+            let _ = ros.send(command);
+        }
+    }
+
+    /// The graph without the names the panes should not list.
+    fn shown_graph(&self) -> GraphSnapshot {
+        let filter = &self.config.filter;
+        self.graph.filtered(|name| {
+            (self.show_hidden || !graph::is_hidden(name)) && !filter.is_ignored(name)
+        })
+    }
+
+    /// Show the graph in the nodes and topics panes.
+    fn update_panes(&mut self) {
+        let shown = self.shown_graph();
+        self.pane_manager.node_pane.update_from_graph(&shown);
+        self.pane_manager.topics_pane.update_from_graph(&shown);
+    }
+
     fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> io::Result<()> {
-        use crossterm::event::{KeyCode, KeyEventKind};
+        use crossterm::event::KeyEventKind;
 
         if key_event.kind != KeyEventKind::Press {
             return Ok(());
         }
+        // Keys act on what is shown, such as copying the field tree
+        self.refresh_diffs();
+
+        let keys = &self.config.keys;
+        let pressed = |action| keys.matches(action, &key_event);
+
+        match &self.app_state {
+            // So does the export prompt
+            AppState::Navigation | AppState::ActivePane if self.export.is_some() => {
+                self.handle_export_key_event(key_event)
+            }
+            // The namespace prompt of the graph view takes every key, including quit
+            AppState::ShowingGraph if self.graph_view.is_editing() => {
+                self.graph_view.handle_key_event(key_event, keys)?
+            }
+
+            // So does the filter prompt of the nodes and topics panes
+            AppState::ActivePane if self.pane_manager.is_filter_editing() => {
+                self.handle_focused_pane_key_event(key_event)?
+            }
 
-        match (&self.app_state, key_event.code) {
-            (AppState::Navigation | AppState::ActivePane, KeyCode::Char('i')) => {
+            AppState::Navigation | AppState::ActivePane if pressed(Action::ToggleInstructions) => {
+                self.instructions_scroll.set(0);
                 self.app_state = AppState::ShowingInstructions
             }
 
-            (_, KeyCode::Char('q')) => self.app_state = AppState::Exit,
+            _ if pressed(Action::Quit) => self.app_state = AppState::Exit,
+
+            AppState::ShowingInstructions
+                if pressed(Action::Back) || pressed(Action::ToggleInstructions) =>
+            {
+                self.app_state = AppState::Navigation
+            }
 
-            (AppState::ShowingInstructions, KeyCode::Esc) => self.app_state = AppState::Navigation,
-            (AppState::ShowingInstructions, KeyCode::Char('i')) => {
+            AppState::Navigation if pressed(Action::ToggleGraph) => {
+                self.app_state = AppState::ShowingGraph
+            }
+            AppState::ShowingGraph if pressed(Action::Back) || pressed(Action::ToggleGraph) => {
                 self.app_state = AppState::Navigation
             }
+            // Panning wins over selecting when both are bound to the same key
+            AppState::ShowingGraph if pressed(Action::Down) && !pressed(Action::PanDown) => {
+                self.pane_manager.node_pane.next_row();
+                self.graph_view.reset_pan();
+            }
+            AppState::ShowingGraph if pressed(Action::Up) && !pressed(Action::PanUp) => {
+                self.pane_manager.node_pane.previous_row();
+                self.graph_view.reset_pan();
+            }
+            AppState::ShowingGraph => self.graph_view.handle_key_event(key_event, keys)?,
+
+            AppState::Navigation if pressed(Action::ToggleDiagnostics) => {
+                self.app_state = AppState::ShowingDiagnostics
+            }
+            AppState::ShowingDiagnostics
+                if pressed(Action::Back) || pressed(Action::ToggleDiagnostics) =>
+            {
+                self.app_state = AppState::Navigation
+            }
+            AppState::ShowingDiagnostics => self.diagnostics.handle_key_event(key_event, keys)?,
+
+            AppState::Navigation | AppState::ActivePane if pressed(Action::ToggleHidden) => {
+                self.show_hidden = !self.show_hidden;
+                self.update_panes();
+            }
+
+            AppState::Navigation | AppState::ActivePane if pressed(Action::RestartBackend) => {
+                self.start_backend();
+                if self.ros.is_some() {
+                    self.notify("ROS backend restarted");
+                }
+            }
+
+            AppState::Navigation | AppState::ActivePane if pressed(Action::Echo) => {
+                self.toggle_echo()
+            }
+
+            AppState::Navigation | AppState::ActivePane if pressed(Action::NextEcho) => {
+                self.pane_manager.switch_echo(1)
+            }
+            AppState::Navigation | AppState::ActivePane if pressed(Action::PreviousEcho) => {
+                self.pane_manager.switch_echo(-1)
+            }
+            AppState::Navigation | AppState::ActivePane if pressed(Action::ToggleEchoGrid) => {
+                self.pane_manager.echo_grid = !self.pane_manager.echo_grid
+            }
+
+            AppState::Navigation | AppState::ActivePane if pressed(Action::TogglePreview) => {
+                self.toggle_preview()
+            }
+            AppState::Navigation | AppState::ActivePane if pressed(Action::CyclePoseTopic) => {
+                self.cycle_pose_topic()
+            }
+            AppState::Navigation | AppState::ActivePane if pressed(Action::ToggleDiff) => {
+                self.toggle_diff(false)
+            }
+            AppState::Navigation | AppState::ActivePane if pressed(Action::ToggleOnlyChanges) => {
+                self.toggle_diff(true)
+            }
+
+            AppState::Navigation | AppState::ActivePane if pressed(Action::Export) => {
+                self.start_export()
+            }
+            AppState::Navigation | AppState::ActivePane if pressed(Action::Yank) => {
+                self.yank(false)
+            }
+            AppState::Navigation | AppState::ActivePane if pressed(Action::YankType) => {
+                self.yank(true)
+            }
 
-            (AppState::Navigation, KeyCode::Enter) => self.app_state = AppState::ActivePane,
-            (AppState::ActivePane, KeyCode::Esc) => {
+            AppState::Navigation if pressed(Action::Filter) => {
+                self.pane_manager.start_filter();
+                self.app_state = AppState::ActivePane
+            }
+            AppState::Navigation if pressed(Action::ActivatePane) => {
+                self.app_state = AppState::ActivePane
+            }
+            AppState::ActivePane if pressed(Action::Back) => {
                 self.app_state = AppState::Navigation;
             }
 
-            (AppState::ActivePane, _) => match self.pane_manager.focused_pane {
-                PaneType::NodePane => self.pane_manager.node_pane.handle_key_event(key_event)?,
-                PaneType::TopicsPane => {
-                    self.pane_manager.topics_pane.handle_key_event(key_event)?
+            AppState::ActivePane => self.handle_focused_pane_key_event(key_event)?,
+            AppState::Navigation => self.pane_manager.handle_key_event(key_event, keys)?,
+            AppState::ShowingInstructions => self.scroll_instructions(&key_event),
+            AppState::Exit => {}
+        }
+
+        // The selection or the focus may have changed
+        self.refresh_info();
+        Ok(())
+    }
+
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
+        // Like keys, clicks do not get past an open prompt
+        if !matches!(self.app_state, AppState::Navigation | AppState::ActivePane)
+            || self.pane_manager.is_filter_editing()
+        {
+            return;
+        }
+
+        let areas = self.areas.get();
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) if areas.is_column_border(position) => {
+                self.drag = Some(Drag::ColumnBorder);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(pane) = areas.tab_at(position) {
+                    self.pane_manager.focused_pane = pane;
+                } else if let Some(pane) = areas.pane_at(position) {
+                    self.click_pane(pane, position);
                 }
-                _ => {}
+            }
+            MouseEventKind::Drag(MouseButton::Left) => match self.drag {
+                Some(Drag::ColumnBorder) => {
+                    let main = areas.main;
+                    let left_width = position.x.saturating_sub(main.x) + 1;
+                    let percent =
+                        (u32::from(left_width) * 100 / u32::from(main.width.max(1))) as u16;
+                    self.left_column_percent =
+                        percent.clamp(MIN_COLUMN_PERCENT, 100 - MIN_COLUMN_PERCENT);
+                }
+                Some(Drag::Scrollbar(pane)) => self.drag_scrollbar(pane, position.y),
+                None => {}
             },
-            (AppState::Navigation, _) => self.pane_manager.handle_key_event(key_event)?,
-            (AppState::ShowingInstructions, _) => {}
-            (AppState::Exit, _) => {}
+            MouseEventKind::Up(_) => self.drag = None,
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                let Some(pane) = areas.pane_at(position) else {
+                    return;
+                };
+                let down = mouse_event.kind == MouseEventKind::ScrollDown;
+                self.pane_manager.focused_pane = pane;
+                if pane == PaneType::DetailsPane {
+                    self.select_echo_at(position);
+                }
+                match pane {
+                    PaneType::NodePane => {
+                        self.pane_manager
+                            .node_pane
+                            .scroll_rows(if down { 1 } else { -1 })
+                    }
+                    PaneType::TopicsPane => {
+                        self.pane_manager
+                            .topics_pane
+                            .scroll_rows(if down { 1 } else { -1 })
+                    }
+                    // The wheel zooms plots, like in map viewers
+                    PaneType::DetailsPane => {
+                        match self.pane_manager.echo_mut().and_then(Echo::shown_plot_mut) {
+                            Some(plot) => plot.zoom_by(if down { -1 } else { 1 }),
+                            None => self.pane_manager.details_mut().scroll_by(if down {
+                                WHEEL_LINES
+                            } else {
+                                -WHEEL_LINES
+                            }),
+                        }
+                    }
+                }
+            }
+            _ => {}
         }
 
-        Ok(())
+        // The selection or the focus may have changed
+        self.refresh_info();
+    }
+
+    /// Focus and activate `pane` and select the row clicked at `position`,
+    /// or grab its scrollbar.
+    fn click_pane(&mut self, pane: PaneType, position: Position) {
+        self.pane_manager.focused_pane = pane;
+        self.app_state = AppState::ActivePane;
+        if pane == PaneType::DetailsPane {
+            self.select_echo_at(position);
+        }
+
+        let area = self.view_area(pane);
+        if position.x == area.right().saturating_sub(2) {
+            self.drag = Some(Drag::Scrollbar(pane));
+            self.drag_scrollbar(pane, position.y);
+        } else if let Some(line) = position.y.checked_sub(area.y + 2) {
+            // Below the border and the header
+            match pane {
+                PaneType::NodePane => self.pane_manager.node_pane.click_row(line.into()),
+                PaneType::TopicsPane => self.pane_manager.topics_pane.click_row(line.into()),
+                PaneType::DetailsPane => {}
+            }
+        }
+    }
+
+    /// Scroll `pane` to where the scrollbar thumb is dragged to at line `y`.
+    fn drag_scrollbar(&mut self, pane: PaneType, y: u16) {
+        // The track runs along the inside of the border
+        let area = self.view_area(pane);
+        let track = area.height.saturating_sub(3).max(1);
+        let fraction = f64::from(y.saturating_sub(area.y + 1).min(track)) / f64::from(track);
+        match pane {
+            PaneType::NodePane => self.pane_manager.node_pane.select_fraction(fraction),
+            PaneType::TopicsPane => self.pane_manager.topics_pane.select_fraction(fraction),
+            PaneType::DetailsPane => self.pane_manager.details_mut().scroll_to_fraction(fraction),
+        }
+    }
+
+    /// Where the view the keys of `pane` go to is drawn. In the grid of echoes
+    /// it is the cell of the current echo.
+    fn view_area(&self, pane: PaneType) -> Rect {
+        let area = self.areas.get().area(pane);
+        let panes = &self.pane_manager;
+        match pane == PaneType::DetailsPane && panes.echo_grid {
+            true => echo_grid(area, panes.echoes.len())
+                .get(panes.current_echo)
+                .copied()
+                .unwrap_or(area),
+            false => area,
+        }
+    }
+
+    /// Make the echo at `position` current, its cell of the grid or its tab.
+    fn select_echo_at(&mut self, position: Position) {
+        let area = self.areas.get().details;
+        let index = match self.pane_manager.echo_grid {
+            true => echo_grid(area, self.pane_manager.echoes.len())
+                .iter()
+                .position(|cell| cell.contains(position)),
+            false if position.y == area.y => {
+                // The title starts right of the corner
+                let mut right = area.x + 1;
+                self.echo_tabs()
+                    .into_iter()
+                    .find_map(|(index, text)| {
+                        right += text.chars().count() as u16;
+                        (position.x < right).then_some(index)
+                    })
+                    .flatten()
+            }
+            false => None,
+        };
+        if let Some(index) = index {
+            self.pane_manager.current_echo = index;
+        }
+    }
+
+    fn handle_focused_pane_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+    ) -> io::Result<()> {
+        let keys = &self.config.keys;
+        match self.pane_manager.focused_pane {
+            PaneType::NodePane => self
+                .pane_manager
+                .node_pane
+                .handle_key_event(key_event, keys),
+            PaneType::TopicsPane => self
+                .pane_manager
+                .topics_pane
+                .handle_key_event(key_event, keys),
+            // A plot takes the keys to zoom and pan before the lines get them
+            PaneType::DetailsPane => {
+                let panes = &mut self.pane_manager;
+                if let Some(plot) = panes.echo_mut().and_then(Echo::shown_plot_mut) {
+                    if plot.handle_key_event(key_event, keys) {
+                        return Ok(());
+                    }
+                }
+                panes.details_mut().handle_key_event(key_event, keys)
+            }
+        }
     }
 }
 
 impl App {
     fn render_main_content(&self, area: Rect, frame: &mut Frame) {
-        let left_right_layout =
-            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]);
+        if area.width < SINGLE_PANE_WIDTH {
+            self.render_single_pane(area, frame);
+            return;
+        }
+
+        let left_percent = self.left_column_percent;
+        let left_right_layout = Layout::horizontal([
+            Constraint::Percentage(left_percent),
+            Constraint::Percentage(100 - left_percent),
+        ]);
         let [options_area, details_area] = left_right_layout.areas(area);
 
-        self.render_options_panes(options_area, frame);
+        let nodes_percent = self.config.layout.nodes_pane_percent;
+        let options_layout = Layout::vertical([
+            Constraint::Percentage(nodes_percent),
+            Constraint::Percentage(100 - nodes_percent),
+        ]);
+        let [nodes_area, topics_area] = options_layout.areas(options_area);
+
+        self.areas.set(PaneAreas {
+            main: area,
+            tabs: Rect::default(),
+            nodes: nodes_area,
+            topics: topics_area,
+            details: details_area,
+        });
+        self.render_nodes_pane(nodes_area, frame);
+        self.render_topics_pane(topics_area, frame);
         self.render_details_pane(details_area, frame);
     }
 
-    fn render_options_panes(&self, area: Rect, frame: &mut Frame) {
-        let options_layout =
-            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]);
-        let [nodes_area, topics_area] = options_layout.areas(area);
+    /// The focused pane alone under a row of tabs, for narrow terminals.
+    fn render_single_pane(&self, area: Rect, frame: &mut Frame) {
+        let [tabs_area, pane_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+        let theme = &self.config.theme;
+        let focused = self.pane_manager.focused_pane;
+
+        let mut tabs = vec![];
+        for (i, (title, pane)) in TABS.iter().enumerate() {
+            if i > 0 {
+                tabs.push(Span::styled("│", Style::default().fg(theme.border)));
+            }
+            let style = match *pane == focused {
+                true => Style::default().fg(theme.focused_border).bold(),
+                false => Style::default().fg(theme.text),
+            };
+            tabs.push(Span::styled(*title, style));
+        }
+        frame.render_widget(Line::from(tabs), tabs_area);
 
-        self.render_nodes_pane(nodes_area, frame);
-        self.render_topics_pane(topics_area, frame);
+        let mut areas = PaneAreas {
+            main: area,
+            tabs: tabs_area,
+            ..Default::default()
+        };
+        match focused {
+            PaneType::NodePane => {
+                areas.nodes = pane_area;
+                self.render_nodes_pane(pane_area, frame);
+            }
+            PaneType::TopicsPane => {
+                areas.topics = pane_area;
+                self.render_topics_pane(pane_area, frame);
+            }
+            PaneType::DetailsPane => {
+                areas.details = pane_area;
+                self.render_details_pane(pane_area, frame);
+            }
+        }
+        self.areas.set(areas);
+    }
+
+    /// Shown instead of everything else when the terminal is too small to be useful.
+    fn render_too_small(&self, area: Rect, frame: &mut Frame) {
+        let text = vec![
+            Line::from("Terminal too small"),
+            Line::from(format!(
+                "{}x{}, needs {}x{}",
+                area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+            )),
+        ];
+        let [text_area] = Layout::vertical([Constraint::Length(text.len() as u16)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        frame.render_widget(
+            Paragraph::new(text)
+                .style(Style::default().fg(self.config.theme.error))
+                .centered(),
+            text_area,
+        );
     }
 
     fn render_nodes_pane(&self, area: Rect, frame: &mut Frame) {
@@ -465,7 +2070,15 @@ impl App {
         // Render the border first
         let is_focused = self.pane_manager.focused_pane == PaneType::NodePane;
         let is_active = self.app_state == AppState::ActivePane;
-        let block = create_stylized_block(" Nodes ", is_focused, is_active);
+        let theme = &self.config.theme;
+        let node_pane = &self.pane_manager.node_pane;
+        let title = node_pane
+            .filter
+            .title("Nodes", node_pane.visible.len(), node_pane.nodes.len());
+        let mut block = create_stylized_block(title.as_str(), is_focused, is_active, theme);
+        if let Some(prompt) = node_pane.filter.prompt(false) {
+            block = block.title_bottom(prompt);
+        }
         frame.render_widget(block, area);
 
         // We split the area in scrollable area and scrollbar
@@ -477,27 +2090,20 @@ impl App {
             Layout::horizontal([Constraint::Percentage(100), Constraint::Min(1)]);
         let [scrollable_area, scrollbar_area] = left_right_layout.areas(inner_area);
 
-        let header = Row::new(vec!["Node Name"]).style(Style::default().fg(Color::Yellow));
-
-        let rows: Vec<Row> = self
-            .pane_manager
-            .node_pane
-            .iter()
-            .map(|node| Row::new(vec![node.name.as_str()]).style(Style::default().fg(Color::White)))
-            .collect();
+        let columns = NodePane::columns(scrollable_area.width);
+        let header = Row::new(pick_columns(node_pane.sort.header(&NODE_COLUMNS), columns))
+            .style(Style::default().fg(theme.header));
 
-        //let cols = vec!["Node Name"];
+        let rows = node_pane.get_rows(theme, columns);
 
-        let table = Table::default()
+        let widths = [Constraint::Fill(1), Constraint::Length(11)];
+        let table = Table::new(rows, pick_columns(widths.to_vec(), columns))
             .header(header)
-            .row_highlight_style(Style::default().fg(Color::Green).bold())
-            .rows(rows);
+            .row_highlight_style(Style::default().fg(theme.selection).bold());
 
-        frame.render_stateful_widget(
-            table,
-            scrollable_area,
-            &mut self.pane_manager.node_pane.state.clone(),
-        );
+        let mut state = node_pane.state.clone();
+        frame.render_stateful_widget(table, scrollable_area, &mut state);
+        node_pane.rendered_offset.set(state.offset());
 
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(None)
@@ -517,7 +2123,17 @@ impl App {
 
         let is_focused = self.pane_manager.focused_pane == PaneType::TopicsPane;
         let is_active = self.app_state == AppState::ActivePane;
-        let block = create_stylized_block(" Topics ", is_focused, is_active);
+        let theme = &self.config.theme;
+        let topics_pane = &self.pane_manager.topics_pane;
+        let title = topics_pane.filter.title(
+            "Topics",
+            topics_pane.visible.len(),
+            topics_pane.topics.len(),
+        );
+        let mut block = create_stylized_block(title.as_str(), is_focused, is_active, theme);
+        if let Some(prompt) = topics_pane.filter.prompt(true) {
+            block = block.title_bottom(prompt);
+        }
         frame.render_widget(block, area);
 
         // We split the area in scrollable area and scrollbar
@@ -529,22 +2145,30 @@ impl App {
             Layout::horizontal([Constraint::Percentage(100), Constraint::Min(1)]);
         let [scrollable_area, scrollbar_area] = left_right_layout.areas(inner_area);
 
-        let header = Row::new(vec!["Topic Name", "Message Type", "Publisher Count"])
-            .style(Style::default().fg(Color::Yellow));
-
-        let rows = self.pane_manager.topics_pane.get_rows();
-
-        let table = Table::default()
+        let columns = TopicPane::columns(scrollable_area.width);
+        let header = Row::new(pick_columns(
+            topics_pane.sort.header(&TOPIC_COLUMNS),
+            columns,
+        ))
+        .style(Style::default().fg(theme.header));
+
+        let rows = topics_pane.get_rows(theme, columns);
+
+        let widths = [
+            Constraint::Fill(3),
+            Constraint::Fill(2),
+            Constraint::Length(6),
+            Constraint::Length(6),
+            Constraint::Length(7),
+        ];
+        let table = Table::new(rows, pick_columns(widths.to_vec(), columns))
             .header(header)
-            .row_highlight_style(Style::default().fg(Color::Green).bold())
-            .rows(rows);
+            .row_highlight_style(Style::default().fg(theme.selection).bold());
 
         // Render table with state
-        frame.render_stateful_widget(
-            table,
-            scrollable_area,
-            &mut self.pane_manager.topics_pane.state.clone(),
-        );
+        let mut state = topics_pane.state.clone();
+        frame.render_stateful_widget(table, scrollable_area, &mut state);
+        topics_pane.rendered_offset.set(state.offset());
 
         // Render scrollbar
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
@@ -561,21 +2185,150 @@ impl App {
     }
 
     fn render_details_pane(&self, area: Rect, frame: &mut Frame) {
-        let details = Paragraph::new(self.details.clone()).block(create_stylized_block(
-            " Details area ",
-            self.pane_manager.focused_pane == PaneType::DetailsPane,
-            self.app_state == AppState::ActivePane,
-        ));
+        let panes = &self.pane_manager;
+        let is_focused = panes.focused_pane == PaneType::DetailsPane;
+        let is_active = self.app_state == AppState::ActivePane;
+        let theme = &self.config.theme;
+
+        if panes.echo_grid && !panes.echoes.is_empty() {
+            let cells = echo_grid(area, panes.echoes.len());
+            for (i, (echo, cell)) in panes.echoes.iter().zip(cells).enumerate() {
+                let title = format!(" Echo {} ", echo.title());
+                let is_current = i == panes.current_echo;
+                let block =
+                    create_stylized_block(title, is_focused && is_current, is_active, theme);
+                self.render_echo(i, block, cell, frame);
+            }
+            return;
+        }
+
+        let title = match (panes.echo(), &self.info_target) {
+            // The current one stands out once there is a choice
+            (Some(_), _) => Line::from(
+                self.echo_tabs()
+                    .into_iter()
+                    .map(|(index, text)| match index {
+                        Some(i) if i == panes.current_echo && panes.echoes.len() > 1 => {
+                            Span::from(text).reversed()
+                        }
+                        _ => Span::from(text),
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            (None, Some(InfoTarget::Node(name))) => Line::from(format!(" Node {} ", name)),
+            (None, Some(InfoTarget::Topic(name))) => Line::from(format!(" Topic {} ", name)),
+            (None, None) => Line::from(" Details "),
+        };
+        let block = create_stylized_block(title, is_focused, is_active, theme);
+        match panes.echo() {
+            Some(_) => self.render_echo(panes.current_echo, block, area, frame),
+            None => panes.details().render(block, area, frame, theme),
+        }
+    }
+
+    /// Draw an echo as its preview or its lines.
+    fn render_echo(&self, index: usize, block: Block, area: Rect, frame: &mut Frame) {
+        let echo = &self.pane_manager.echoes[index];
+        let theme = &self.config.theme;
+        let block = echo.with_latency(block);
+        let Some(preview) = echo.shown_preview() else {
+            return echo.shown().render(block, area, frame, theme);
+        };
+
+        let block = match echo.shown().is_paused() {
+            true => block.title(Line::from(" paused ").right_aligned()),
+            false => block,
+        };
+        // Popups would end up under the pictures of the terminal
+        let graphics = matches!(self.config.ui.graphics, Graphics::Kitty | Graphics::Sixel)
+            && matches!(self.app_state, AppState::Navigation | AppState::ActivePane);
+        let pose = echo.pose_topic.as_ref().and_then(|topic| {
+            let pose_echo = self
+                .pane_manager
+                .echoes
+                .iter()
+                .find(|e| &e.topic == topic)?;
+            preview::pose(&pose_echo.messages.back()?.message)
+        });
+        if let Some(area) = preview.render(block, area, frame, theme, graphics, pose) {
+            self.placed.borrow_mut().push(Placement {
+                echo: index,
+                area,
+                frame: preview.frame,
+            });
+        }
+    }
 
-        details.render(area, frame.buffer_mut());
+    /// The title of the echoes shown as tabs, e.g. ` Echo /cmd_vel │ /odom `,
+    /// in pieces with the index of the echo they name.
+    fn echo_tabs(&self) -> Vec<(Option<usize>, String)> {
+        let mut tabs = vec![(None, " Echo ".to_string())];
+        for (i, echo) in self.pane_manager.echoes.iter().enumerate() {
+            if i > 0 {
+                tabs.push((None, " │ ".to_string()));
+            }
+            tabs.push((Some(i), echo.title()));
+        }
+        tabs.push((None, " ".to_string()));
+        tabs
     }
 
     fn render_instructions_bar(&self, area: Rect, frame: &mut Frame) {
+        let keys = &self.config.keys;
+        let theme = &self.config.theme;
+        let key_hint = Style::default().fg(theme.key_hint).bold();
+
+        if let (Some(prompt), Some(echo)) = (&self.export, self.pane_manager.echo()) {
+            Line::styled(
+                prompt.prompt(echo.messages.len()),
+                Style::default().fg(theme.text),
+            )
+            .render(area, frame.buffer_mut());
+            return;
+        }
+
+        let notification = self
+            .notification
+            .as_ref()
+            .filter(|n| n.shown_at.elapsed() < NOTIFICATION_TIMEOUT);
+        if let Some(notification) = notification {
+            let color = match notification.is_error {
+                true => theme.error,
+                false => theme.key_hint,
+            };
+            Line::styled(
+                format!(" {} ", notification.message),
+                Style::default().fg(color),
+            )
+            .centered()
+            .bold()
+            .render(area, frame.buffer_mut());
+            return;
+        }
+        if self.ros.is_none() {
+            Line::from(vec![
+                Span::styled(
+                    " ROS backend stopped, restart ",
+                    Style::default().fg(theme.error),
+                ),
+                Span::styled(format!("<{}>", keys.keys(Action::RestartBackend)), key_hint),
+                " Quit ".into(),
+                Span::styled(format!("<{}>", keys.keys(Action::Quit)), key_hint),
+            ])
+            .centered()
+            .bold()
+            .render(area, frame.buffer_mut());
+            return;
+        }
+
         let instructions_line = Line::from(vec![
             " Quit ".into(),
-            "<q>".blue().bold(),
+            Span::styled(format!("<{}>", keys.keys(Action::Quit)), key_hint),
             " Instructions ".into(),
-            "<i>".blue().bold(),
+            Span::styled(
+                format!("<{}>", keys.keys(Action::ToggleInstructions)),
+                key_hint,
+            ),
         ])
         .centered()
         .bold();
@@ -584,27 +2337,115 @@ impl App {
     }
 
     fn render_instructions_popup(&self, area: Rect, frame: &mut Frame) {
-        let instructions: Vec<Line> = self
-            .instructions
-            .iter()
-            .map(|instruction| {
-                Line::from(vec![
-                    format!("{:?}", instruction.key_code).into(),
-                    " - ".into(),
-                    instruction.description.clone().into(),
-                ])
-            })
-            .collect();
+        let theme = &self.config.theme;
+        let mut lines: Vec<Line> = vec![];
+        let mut section = "";
+        for instruction in &self.instructions {
+            if instruction.section != section {
+                if !lines.is_empty() {
+                    lines.push(Line::default());
+                }
+                lines.push(Line::from(instruction.section).fg(theme.header).bold());
+                section = instruction.section;
+            }
+            lines.push(Line::from(vec![
+                "  ".into(),
+                instruction.keys.clone().fg(theme.key_hint).bold(),
+                " - ".into(),
+                instruction.description.clone().into(),
+            ]));
+        }
 
-        let instructions_paragraph =
-            Paragraph::new(instructions).block(Block::bordered().title("Instructions"));
+        let popup_area = popup_area(area, 60, 80);
+        let visible = popup_area.height.saturating_sub(2);
+        let max_scroll = (lines.len() as u16).saturating_sub(visible);
+        let scroll = self.instructions_scroll.get().min(max_scroll);
+        self.instructions_scroll.set(scroll);
+
+        let mut block = Block::bordered().title("Instructions");
+        if max_scroll > 0 {
+            let keys = &self.config.keys;
+            block = block.title_bottom(format!(
+                " {}-{} of {} | scroll <{}> <{}> ",
+                scroll + 1,
+                scroll + visible,
+                lines.len(),
+                keys.keys(Action::Down),
+                keys.keys(Action::Up),
+            ));
+        }
 
-        let popup_area = popup_area(area, 60, 20);
         ratatui::widgets::Clear.render(popup_area, frame.buffer_mut());
-        instructions_paragraph.render(popup_area, frame.buffer_mut());
+        Paragraph::new(lines)
+            .block(block)
+            .scroll((scroll, 0))
+            .render(popup_area, frame.buffer_mut());
+    }
+
+    /// Scroll the instructions popup by a line, a page or to either end.
+    fn scroll_instructions(&self, key_event: &crossterm::event::KeyEvent) {
+        let keys = &self.config.keys;
+        let scroll = self.instructions_scroll.get();
+        let scroll = if keys.matches(Action::Down, key_event) {
+            scroll.saturating_add(1)
+        } else if keys.matches(Action::Up, key_event) {
+            scroll.saturating_sub(1)
+        } else if keys.matches(Action::PageDown, key_event) {
+            scroll.saturating_add(INSTRUCTIONS_PAGE)
+        } else if keys.matches(Action::PageUp, key_event) {
+            scroll.saturating_sub(INSTRUCTIONS_PAGE)
+        } else if keys.matches(Action::ScrollTop, key_event) {
+            0
+        } else if keys.matches(Action::ScrollBottom, key_event) {
+            u16::MAX
+        } else {
+            return;
+        };
+        // Drawing brings it back in range
+        self.instructions_scroll.set(scroll);
+    }
+}
+
+/// Split `area` into cells for `count` echoes, as many columns as rows or one
+/// more, filled row by row.
+fn echo_grid(area: Rect, count: usize) -> Vec<Rect> {
+    if count == 0 {
+        return vec![];
+    }
+    let columns = (1..=count).find(|c| c * c >= count).unwrap_or(count);
+    let rows = count.div_ceil(columns);
+    Layout::vertical(vec![Constraint::Fill(1); rows])
+        .split(area)
+        .iter()
+        .enumerate()
+        .flat_map(|(row, row_area)| {
+            let cells = columns.min(count - row * columns);
+            Layout::horizontal(vec![Constraint::Fill(1); cells])
+                .split(*row_area)
+                .to_vec()
+        })
+        .collect()
+}
+
+/// The largest whole unit, e.g. `42s`, `12m` or `3h`, to fit the rate column.
+fn short_duration(duration: Duration) -> String {
+    match duration.as_secs() {
+        seconds @ 0..60 => format!("{}s", seconds),
+        seconds @ 60..3_600 => format!("{}m", seconds / 60),
+        seconds => format!("{}h", seconds / 3_600),
     }
 }
 
+/// The cells of the `columns` shown, out of the cells of every column.
+fn pick_columns<T>(cells: Vec<T>, columns: &[usize]) -> Vec<T> {
+    cells
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| columns.contains(i))
+        .map(|(_, cell)| cell)
+        .collect()
+}
+
 fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let vertical =
         Layout::vertical([Constraint::Percentage(percent_y)]).flex(ratatui::layout::Flex::Center);
@@ -615,23 +2456,27 @@ fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     area
 }
 
-fn create_stylized_block(
-    title: &str,
+fn create_stylized_block<'a>(
+    title: impl Into<Line<'a>>,
     is_focused: bool,
     is_active: bool,
-) -> ratatui::widgets::Block {
+    theme: &Theme,
+) -> ratatui::widgets::Block<'a> {
     let border_style = match { is_focused && is_active } {
         true => BorderType::Thick,
         false => BorderType::Rounded,
     };
     let color = match (is_focused, is_active) {
-        (true, true) => Color::Green,
-        (true, false) => Color::Blue,
-        (false, _) => Color::White,
+        (true, true) => theme.active_border,
+        (true, false) => theme.focused_border,
+        (false, _) => theme.border,
     };
 
     Block::bordered()
-        .title(Line::from(title))
+        .title(title)
         .style(color)
         .border_type(border_style)
 }
+
+#[cfg(test)]
+mod tests;
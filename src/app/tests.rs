@@ -0,0 +1,1287 @@
+use std::{cell::RefCell, io, rc::Rc, sync::mpsc, time::UNIX_EPOCH};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::{backend::TestBackend, buffer::Buffer, style::Color, Terminal};
+
+use super::*;
+use crate::{
+    demo::DemoSystem,
+    diagnostics::{DiagnosticLevel, DiagnosticStatus},
+    event::ROSEvent,
+    preview::{
+        image::tests::image,
+        map::tests::{grid, pose_with_covariance},
+        points::tests::scan,
+    },
+    ros::Backend,
+};
+
+/// A backend that only keeps the commands it receives, the tests send the ROS
+/// events themselves.
+struct RecordingBackend {
+    commands: Rc<RefCell<Option<mpsc::Receiver<RosCommand>>>>,
+}
+
+impl Backend for RecordingBackend {
+    fn spawn(&self, _events: mpsc::Sender<Event>) -> io::Result<mpsc::Sender<RosCommand>> {
+        let (command_tx, command_rx) = mpsc::channel();
+        *self.commands.borrow_mut() = Some(command_rx);
+        Ok(command_tx)
+    }
+}
+
+/// Drives an `App` with scripted events and renders it into a `TestBackend`.
+struct Harness {
+    app: App,
+    terminal: Terminal<TestBackend>,
+    /// Commands to the backend started last.
+    commands: Rc<RefCell<Option<mpsc::Receiver<RosCommand>>>>,
+}
+
+impl Harness {
+    fn new(width: u16, height: u16) -> Harness {
+        Harness::with_config(Config::default(), width, height)
+    }
+
+    fn with_config(config: Config, width: u16, height: u16) -> Harness {
+        let (events, _) = mpsc::channel();
+        let commands = Rc::new(RefCell::new(None));
+        let backend = RecordingBackend {
+            commands: commands.clone(),
+        };
+        Harness {
+            app: App::new(config, Box::new(backend), events),
+            terminal: Terminal::new(TestBackend::new(width, height)).unwrap(),
+            commands,
+        }
+    }
+
+    /// The commands sent since the last call.
+    fn commands(&self) -> Vec<RosCommand> {
+        match &*self.commands.borrow() {
+            Some(commands) => commands.try_iter().collect(),
+            None => vec![],
+        }
+    }
+
+    /// The commands sent since the last call, without the requests for rates.
+    fn subscriptions(&self) -> Vec<RosCommand> {
+        self.commands()
+            .into_iter()
+            .filter(|command| !matches!(command, RosCommand::MeasureRates { .. }))
+            .collect()
+    }
+
+    /// The topics of the last request for rates sent since the last call.
+    fn measured_topics(&self) -> Option<Vec<String>> {
+        self.commands()
+            .into_iter()
+            .filter_map(|command| match command {
+                RosCommand::MeasureRates { topics } => Some(topics),
+                _ => None,
+            })
+            .next_back()
+    }
+
+    /// A harness that already received the graph of the demo robot.
+    fn with_demo_graph(width: u16, height: u16) -> Harness {
+        let mut harness = Harness::new(width, height);
+        harness.ros(ROSEvent::GraphUpdate(DemoSystem::default().graph()));
+        harness
+    }
+
+    fn send(&mut self, event: Event) -> &mut Harness {
+        if let Event::Resize(width, height) = event {
+            self.terminal.backend_mut().resize(width, height);
+        }
+        self.app.handle_event(event).unwrap();
+        self
+    }
+
+    fn ros(&mut self, event: ROSEvent) -> &mut Harness {
+        self.send(Event::ROSEvent { event })
+    }
+
+    fn key(&mut self, code: KeyCode) -> &mut Harness {
+        self.send(Event::Input(KeyEvent::from(code)))
+    }
+
+    /// Type every character of `keys` as a key press of its own.
+    fn keys(&mut self, keys: &str) -> &mut Harness {
+        for c in keys.chars() {
+            self.key(KeyCode::Char(c));
+        }
+        self
+    }
+
+    fn mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) -> &mut Harness {
+        self.send(Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }))
+    }
+
+    /// Press and release the left button at one spot.
+    fn click(&mut self, column: u16, row: u16) -> &mut Harness {
+        self.mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+            .mouse(MouseEventKind::Up(MouseButton::Left), column, row)
+    }
+
+    /// Echo the first topic of the demo graph, `/amcl_pose`.
+    fn start_echo(&mut self) -> &mut Harness {
+        self.ros(ROSEvent::GraphUpdate(DemoSystem::default().graph()))
+            .keys("t")
+            .key(KeyCode::Enter)
+            .keys("je")
+            .key(KeyCode::Esc)
+    }
+
+    /// Add a line to the running echo, as a message would.
+    fn echo_line(&mut self, line: &str) -> &mut Harness {
+        let panes = &mut self.app.pane_manager;
+        let echo = panes
+            .echoes
+            .get_mut(panes.current_echo)
+            .expect("an echo runs");
+        echo.view.push(line);
+        self
+    }
+
+    fn buffer(&mut self) -> Buffer {
+        // As the frames of `App::run`
+        self.app.refresh_diffs();
+        self.terminal.draw(|frame| self.app.draw(frame)).unwrap();
+        self.terminal.backend().buffer().clone()
+    }
+
+    /// The rendered screen, one string per line.
+    fn screen(&mut self) -> Vec<String> {
+        let buffer = self.buffer();
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|line| line.iter().map(|cell| cell.symbol()).collect())
+            .collect()
+    }
+
+    fn screen_contains(&mut self, text: &str) -> bool {
+        self.screen().iter().any(|line| line.contains(text))
+    }
+}
+
+#[test]
+fn startup_renders_empty_panes() {
+    let mut harness = Harness::new(80, 10);
+    assert_eq!(
+        harness.screen(),
+        [
+            "╭ Nodes ───────────────────────╮╭ Details ─────────────────────────────────────╮",
+            "│Node Name ▲       Publishes   ││Select a node or topic to see its details.    │",
+            "│                              ││Press e on a topic to echo its messages.      │",
+            "│                              ││                                              │",
+            "╰──────────────────────────────╯│                                              │",
+            "╭ Topics ──────────────────────╮│                                              │",
+            "│Topic Name ▲          Hz      ││                                              │",
+            "│                              ││                                              │",
+            "╰──────────────────────────────╯╰──────────────────────────────────────────────╯",
+            "                            Quit <q> Instructions <i>                           ",
+        ]
+    );
+}
+
+#[test]
+fn moving_in_empty_lists_does_not_panic() {
+    let mut harness = Harness::new(60, 10);
+    for pane in ["n", "t"] {
+        harness
+            .keys(pane)
+            .key(KeyCode::Enter)
+            .keys("jjkkj")
+            .key(KeyCode::Up)
+            .key(KeyCode::Esc);
+    }
+    // A filter matching nothing empties a pane that had rows before
+    harness
+        .ros(ROSEvent::GraphUpdate(DemoSystem::default().graph()))
+        .keys("n/zzzz")
+        .key(KeyCode::Enter)
+        .keys("jk");
+    assert!(harness.screen_contains("Nodes (0 of"));
+}
+
+#[test]
+fn graph_update_fills_nodes_and_topics() {
+    let mut harness = Harness::with_demo_graph(160, 40);
+    assert!(harness.screen_contains("/robot/lidar_driver"));
+    assert!(harness.screen_contains("/cmd_vel"));
+    assert!(harness.screen_contains("/scan"));
+    // Infrastructure stays hidden until asked for
+    assert!(!harness.screen_contains("/rosout"));
+    assert!(!harness.screen_contains("_action"));
+    harness.keys(".");
+    assert!(harness.screen_contains("/rosout"));
+    assert!(harness.screen_contains("/navigate_to_pose"));
+}
+
+#[test]
+fn focus_moves_between_panes() {
+    let theme = Theme::default();
+    let mut harness = Harness::new(60, 10);
+    // Top left corners of the nodes, topics and details panes
+    let corners = [(0, 0), (0, 5), (24, 0)];
+    let border_colors = |buffer: &Buffer| -> Vec<Color> {
+        corners
+            .iter()
+            .map(|&(x, y)| buffer.cell((x, y)).unwrap().fg)
+            .collect()
+    };
+
+    let buffer = harness.buffer();
+    assert_eq!(
+        border_colors(&buffer),
+        [theme.focused_border, theme.border, theme.border]
+    );
+
+    let buffer = harness.keys("t").buffer();
+    assert_eq!(
+        border_colors(&buffer),
+        [theme.border, theme.focused_border, theme.border]
+    );
+
+    let buffer = harness.keys("d").buffer();
+    assert_eq!(
+        border_colors(&buffer),
+        [theme.border, theme.border, theme.focused_border]
+    );
+
+    // An active pane gets a thick border in its own colour
+    let buffer = harness.key(KeyCode::Enter).buffer();
+    assert_eq!(buffer.cell((24, 0)).unwrap().symbol(), "┏");
+    assert_eq!(buffer.cell((24, 0)).unwrap().fg, theme.active_border);
+    let buffer = harness.key(KeyCode::Esc).buffer();
+    assert_eq!(buffer.cell((24, 0)).unwrap().symbol(), "╭");
+}
+
+#[test]
+fn selected_row_is_highlighted() {
+    let theme = Theme::default();
+    let mut harness = Harness::with_demo_graph(80, 30);
+    harness.key(KeyCode::Enter).keys("jj");
+
+    let screen = harness.screen();
+    let buffer = harness.buffer();
+    let row = screen
+        .iter()
+        .position(|line| line.contains("/lazyros"))
+        .unwrap() as u16;
+    assert_eq!(buffer.cell((1, row)).unwrap().fg, theme.selection);
+    assert_ne!(buffer.cell((1, row + 1)).unwrap().fg, theme.selection);
+}
+
+#[test]
+fn filter_shows_match_count() {
+    let mut harness = Harness::with_demo_graph(80, 30);
+    harness.keys("/nav");
+    assert!(harness.screen_contains("Nodes (4 of 12)"));
+    assert!(harness.screen_contains("/nav█"));
+    assert!(!harness.screen_contains("/talker"));
+
+    harness.key(KeyCode::Esc);
+    assert!(harness.screen_contains("/talker"));
+    assert!(!harness.screen_contains("of 12"));
+}
+
+#[test]
+fn instructions_popup_opens_and_closes() {
+    let mut harness = Harness::new(100, 50);
+    harness.keys("i");
+    assert!(harness.screen_contains("Instructions"));
+    assert!(harness.screen_contains("R - Restart the ROS backend"));
+    harness.keys("i");
+    assert!(!harness.screen_contains("Restart the ROS backend"));
+}
+
+#[test]
+fn instructions_popup_scrolls_when_it_does_not_fit() {
+    let mut harness = Harness::new(80, 24);
+    harness.keys("i");
+    assert!(harness.screen_contains("General"));
+    assert!(harness.screen_contains("q - Quit"));
+    assert!(!harness.screen_contains("decrease stale timeout"));
+    assert!(harness.screen_contains("scroll <j/down> <k/up>"));
+
+    harness.keys("G");
+    assert!(harness.screen_contains("Diagnostics"));
+    assert!(harness.screen_contains("decrease stale timeout"));
+    assert!(!harness.screen_contains("q - Quit"));
+
+    // Scrolling past the end does not have to be scrolled back first
+    for key in "jjk".chars() {
+        harness.keys(&key.to_string()).buffer();
+    }
+    assert!(!harness.screen_contains("decrease stale timeout"));
+    harness.keys("g");
+    assert!(harness.screen_contains("q - Quit"));
+}
+
+#[test]
+fn resize_renders_at_the_new_size() {
+    let mut harness = Harness::with_demo_graph(100, 40);
+    harness.send(Event::Resize(50, 12));
+    let buffer = harness.buffer();
+    assert_eq!(buffer.area, Rect::new(0, 0, 50, 12));
+    assert!(harness.screen_contains("Nodes"));
+    assert!(harness.screen_contains("Quit <q>"));
+}
+
+#[test]
+fn details_describe_the_selected_node() {
+    let mut harness = Harness::with_demo_graph(100, 30);
+    harness.key(KeyCode::Enter).keys("jjjjjjjj");
+    assert!(harness.screen_contains("Node /robot/base_controller"));
+    assert!(harness.screen_contains("Publishes (3)"));
+    let screen = harness.screen();
+    assert!(screen
+        .iter()
+        .any(|line| line.contains("/odom ") && line.contains(" nav_msgs/msg/Odometry")));
+    assert!(harness.screen_contains("Subscribes (1)"));
+    assert!(harness.screen_contains("/cmd_vel  geometry_msgs/msg/Twist"));
+}
+
+#[test]
+fn details_describe_the_selected_topic() {
+    let mut harness = Harness::with_demo_graph(120, 30);
+    harness
+        .ros(ROSEvent::TopicRates(rates(&[("/scan", 9.87)])))
+        .keys("t")
+        .key(KeyCode::Enter)
+        .keys("/scan")
+        .key(KeyCode::Enter)
+        .keys("j");
+    assert!(harness.screen_contains("Topic /scan"));
+    assert!(harness.screen_contains("Type  sensor_msgs/msg/LaserScan"));
+    assert!(harness.screen_contains("Rate  9.9 Hz"));
+    assert!(harness.screen_contains("Publishers (1)"));
+    assert!(harness.screen_contains("/robot/lidar_driver  BestEffort, Volatile, KeepLast (10)"));
+    assert!(harness.screen_contains("/nav/localization  BestEffort, Volatile, KeepLast (10)"));
+
+    // Focusing the details pane keeps the topic described
+    harness.key(KeyCode::Esc).keys("d");
+    assert!(harness.screen_contains("Topic /scan"));
+}
+
+#[test]
+fn echo_subscribes_only_when_asked() {
+    let mut harness = Harness::with_demo_graph(80, 12);
+    assert_eq!(harness.subscriptions(), []);
+
+    harness
+        .keys("t")
+        .key(KeyCode::Enter)
+        .keys("/topic")
+        .key(KeyCode::Enter)
+        .keys("je");
+    assert_eq!(
+        harness.subscriptions(),
+        [RosCommand::Subscribe {
+            topic: "/topic".to_string(),
+            msg_type: "std_msgs/msg/String".to_string(),
+        }]
+    );
+
+    let mut system = DemoSystem::default();
+    system.execute(RosCommand::Subscribe {
+        topic: "/topic".to_string(),
+        msg_type: "std_msgs/msg/String".to_string(),
+    });
+    // The talker publishes once per second
+    for _ in 0..30 {
+        for event in system.step() {
+            harness.ros(event);
+        }
+    }
+    assert!(harness.screen_contains("Echo /topic"));
+    assert!(harness.screen_contains(r#"{"data":"Hello World: 0"}"#));
+    assert!(harness.screen_contains(r#"{"data":"Hello World: 2"}"#));
+
+    harness.keys("e");
+    assert_eq!(
+        harness.subscriptions(),
+        [RosCommand::Unsubscribe {
+            topic: "/topic".to_string()
+        }]
+    );
+    assert!(harness.screen_contains("Topic /topic"));
+    assert!(!harness.screen_contains("Hello World"));
+}
+
+/// Rates of topics without messages so far.
+fn rates(rates: &[(&str, f64)]) -> HashMap<String, TopicRate> {
+    rates
+        .iter()
+        .map(|(topic, hz)| {
+            (
+                topic.to_string(),
+                TopicRate {
+                    hz: *hz,
+                    newest: None,
+                },
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn topic_rates_fill_the_hz_column() {
+    let mut harness = Harness::with_demo_graph(120, 40);
+    harness.ros(ROSEvent::TopicRates(rates(&[("/scan", 9.87)])));
+    let screen = harness.screen();
+    let scan = screen.iter().find(|line| line.contains("/scan ")).unwrap();
+    assert!(scan.contains("9.9"));
+
+    // The topics measured later leave the others' rates alone
+    harness.ros(ROSEvent::TopicRates(rates(&[("/odom", 50.0)])));
+    let screen = harness.screen();
+    let scan = screen.iter().find(|line| line.contains("/scan ")).unwrap();
+    assert!(scan.contains("9.9"));
+    let odom = screen.iter().find(|line| line.contains("/odom ")).unwrap();
+    assert!(odom.contains("50.0"));
+}
+
+#[test]
+fn rates_are_measured_for_every_listed_topic() {
+    let mut harness = Harness::new(120, 40);
+    harness.ros(ROSEvent::GraphUpdate(DemoSystem::default().graph()));
+    let listed = harness.measured_topics().unwrap();
+    assert!(listed.contains(&"/scan".to_string()));
+    assert!(listed.contains(&"/cmd_vel".to_string()));
+    assert!(!listed.contains(&"/rosout".to_string()));
+    assert!(listed.is_sorted());
+
+    // Selecting and echoing a listed topic asks for nothing new
+    harness.keys("t").key(KeyCode::Enter).keys("je");
+    assert_eq!(harness.measured_topics(), None);
+}
+
+#[test]
+fn demo_sends_the_rates_of_the_measured_topics() {
+    let mut system = DemoSystem::default();
+    system.execute(RosCommand::MeasureRates {
+        topics: vec!["/scan".to_string()],
+    });
+    let rates = system
+        .step()
+        .into_iter()
+        .find_map(|event| match event {
+            ROSEvent::TopicRates(rates) => Some(rates),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(rates.keys().collect::<Vec<_>>(), ["/scan"]);
+    assert!(rates["/scan"].newest.is_some());
+}
+
+#[test]
+fn stopped_backend_is_reported_and_restarted() {
+    let theme = Theme::default();
+    let mut harness = Harness::new(80, 10);
+    harness.ros(ROSEvent::BackendStopped("rcl init failed".to_string()));
+    assert!(harness.screen_contains("ROS backend stopped: rcl init failed"));
+    assert_eq!(harness.buffer().cell((40, 9)).unwrap().fg, theme.error);
+    assert!(harness.app.ros.is_none());
+
+    harness.keys("R");
+    assert!(harness.app.ros.is_some());
+    assert!(harness.screen_contains("ROS backend restarted"));
+}
+
+#[test]
+fn diagnostics_view_lists_statuses() {
+    let mut harness = Harness::new(100, 20);
+    harness.ros(ROSEvent::Diagnostics(
+        "/diagnostics".to_string(),
+        vec![DiagnosticStatus {
+            level: DiagnosticLevel::Warn,
+            name: "base_controller: Motors".to_string(),
+            message: "Motor temperature high".to_string(),
+            hardware_id: "motor_driver".to_string(),
+            values: vec![],
+        }],
+    ));
+    harness.keys("D");
+    assert!(harness.screen_contains("Motor temperature high"));
+    harness.key(KeyCode::Esc);
+    assert!(!harness.screen_contains("Motor temperature high"));
+}
+
+#[test]
+fn views_hint_the_bound_keys_in_the_theme_colours() {
+    let config = Config::parse(
+        "[keys]\ncenter_view = [\"z\"]\nincrease_stale_timeout = [\"u\"]\n\
+         [theme]\nnode = \"#010203\"\nwarn = \"#040506\"",
+    )
+    .unwrap();
+    let mut harness = Harness::with_config(config, 120, 30);
+    harness
+        .ros(ROSEvent::GraphUpdate(DemoSystem::default().graph()))
+        .keys("g");
+    assert!(harness.screen_contains("center <z>"));
+    assert!(harness
+        .buffer()
+        .content
+        .iter()
+        .any(|cell| cell.fg == Color::Rgb(1, 2, 3)));
+
+    harness
+        .key(KeyCode::Esc)
+        .ros(ROSEvent::Diagnostics(
+            "/diagnostics".to_string(),
+            vec![DiagnosticStatus {
+                level: DiagnosticLevel::Warn,
+                name: "base_controller: Motors".to_string(),
+                ..Default::default()
+            }],
+        ))
+        .keys("D");
+    assert!(harness.screen_contains("stale after 5s <u/->"));
+    assert!(harness.screen_contains("Select a status with <j/down> <k/up>, fold"));
+    assert!(harness.screen_contains("with <enter/space>"));
+    assert!(harness
+        .buffer()
+        .content
+        .iter()
+        .any(|cell| cell.symbol() == "W" && cell.fg == Color::Rgb(4, 5, 6)));
+}
+
+/// A harness with the details pane active, echoing `count` numbered lines.
+fn with_details_lines(count: usize) -> Harness {
+    let mut harness = Harness::new(80, 10);
+    harness.start_echo();
+    for i in 0..count {
+        harness.echo_line(&format!("line {}", i));
+    }
+    harness.keys("d").key(KeyCode::Enter);
+    harness
+}
+
+#[test]
+fn details_keep_at_most_the_configured_lines() {
+    let mut config = Config::default();
+    config.ui.details_max_lines = 3;
+    let mut harness = Harness::with_config(config, 80, 10);
+    harness.start_echo();
+    for i in 0..10 {
+        harness.echo_line(&format!("line {}", i));
+    }
+    assert!(!harness.screen_contains("line 6"));
+    assert!(harness.screen_contains("line 7"));
+    assert!(harness.screen_contains("line 9"));
+}
+
+#[test]
+fn details_follow_the_newest_line_until_scrolled() {
+    let mut harness = with_details_lines(50);
+    // Seven lines fit between the borders
+    assert!(harness.screen_contains("line 49"));
+    assert!(!harness.screen_contains("line 42"));
+
+    harness.keys("k");
+    assert!(harness.screen_contains("line 42"));
+    assert!(!harness.screen_contains("line 49"));
+    assert!(!harness.screen_contains("following"));
+
+    // New lines do not move the view anymore
+    harness.echo_line("line 50");
+    assert!(harness.screen_contains("line 42"));
+
+    harness.keys("G");
+    assert!(harness.screen_contains("line 50"));
+    assert!(harness.screen_contains("following"));
+}
+
+#[test]
+fn details_scroll_by_page_and_to_the_ends() {
+    let mut harness = with_details_lines(50);
+    harness.keys("g");
+    assert!(harness.screen_contains("line 0 "));
+    // A page is the seven lines between the borders
+    harness.key(KeyCode::PageDown);
+    assert!(harness.screen_contains("line 7 "));
+    assert!(!harness.screen_contains("line 6 "));
+    harness.key(KeyCode::PageUp);
+    assert!(harness.screen_contains("line 0 "));
+    // Scrolling down stops at the newest line
+    harness.keys("jjjjjjjjjj").key(KeyCode::End);
+    for _ in 0..10 {
+        harness.key(KeyCode::PageDown);
+    }
+    assert!(harness.screen_contains("line 49"));
+}
+
+#[test]
+fn details_scroll_sideways_and_wrap() {
+    let mut harness = Harness::new(40, 10);
+    harness
+        .start_echo()
+        .echo_line(&format!("start{}end", "-".repeat(40)))
+        .keys("d")
+        .key(KeyCode::Enter);
+    assert!(harness.screen_contains("start"));
+    assert!(!harness.screen_contains("end"));
+
+    harness.keys("lllll");
+    assert!(harness.screen_contains("end"));
+    assert!(!harness.screen_contains("start"));
+
+    harness.keys("w");
+    assert!(harness.screen_contains("start"));
+    assert!(harness.screen_contains("end"));
+    assert!(harness.screen_contains("wrap"));
+}
+
+#[test]
+fn details_pause_holds_new_lines_back() {
+    let mut harness = with_details_lines(3);
+    harness.keys("p");
+    harness.echo_line("late line");
+    assert!(!harness.screen_contains("late line"));
+    assert!(harness.screen_contains("paused, 1 new"));
+    harness.keys("p");
+    assert!(harness.screen_contains("late line"));
+}
+
+#[test]
+fn details_search_jumps_to_and_highlights_matches() {
+    let theme = Theme::default();
+    let mut harness = with_details_lines(50);
+    harness.keys("g/LINE 1");
+    assert!(harness.screen_contains("/LINE 1█ (0 lines)"));
+    harness.key(KeyCode::Esc).keys("/line 2");
+    assert!(harness.screen_contains("/line 2█ (11 lines)"));
+    harness.key(KeyCode::Enter);
+    let screen = harness.screen();
+    assert!(screen[1].contains("line 2 "));
+
+    harness.keys("n");
+    let screen = harness.screen();
+    assert!(screen[1].contains("line 20"));
+    // The match is highlighted, the rest of the line is not
+    let buffer = harness.buffer();
+    let x = screen[1].find("line 20").unwrap() as u16;
+    let x = screen[1][..x as usize].chars().count() as u16;
+    assert_eq!(buffer.cell((x, 1)).unwrap().fg, theme.search_match);
+    assert_eq!(buffer.cell((x + 6, 1)).unwrap().fg, theme.text);
+
+    harness.keys("N");
+    let screen = harness.screen();
+    assert!(screen[1].contains("line 2 "));
+}
+
+#[test]
+fn clicking_a_row_focuses_its_pane_and_selects_it() {
+    let mut harness = Harness::with_demo_graph(100, 30);
+    // Clicks land where the panes were drawn last
+    let screen = harness.screen();
+    let row = screen
+        .iter()
+        .position(|line| line.contains("/cmd_vel "))
+        .unwrap() as u16;
+
+    harness.click(3, row);
+    assert_eq!(harness.app.pane_manager.focused_pane, PaneType::TopicsPane);
+    assert_eq!(harness.app.app_state, AppState::ActivePane);
+    assert!(harness.screen_contains("Topic /cmd_vel"));
+
+    // The first node is on the line below the header
+    harness.click(3, 2);
+    assert_eq!(harness.app.pane_manager.focused_pane, PaneType::NodePane);
+    assert!(harness.screen()[0].contains(" Node /"));
+}
+
+#[test]
+fn wheel_scrolls_the_pane_under_the_pointer() {
+    let mut harness = with_details_lines(50);
+    harness.buffer();
+    harness.mouse(MouseEventKind::ScrollUp, 50, 4);
+    assert!(!harness.screen_contains("following"));
+    assert!(harness.screen_contains("line 40"));
+    assert!(!harness.screen_contains("line 47"));
+
+    let selected = |harness: &Harness| harness.app.pane_manager.node_pane.state.selected();
+    harness.mouse(MouseEventKind::ScrollDown, 3, 2);
+    assert_eq!(harness.app.pane_manager.focused_pane, PaneType::NodePane);
+    // The wheel stops at the ends instead of wrapping around
+    harness.mouse(MouseEventKind::ScrollUp, 3, 2);
+    harness.mouse(MouseEventKind::ScrollUp, 3, 2);
+    assert_eq!(selected(&harness), Some(0));
+}
+
+#[test]
+fn dragging_the_column_border_resizes_the_columns() {
+    let mut harness = Harness::new(80, 10);
+    // The details pane starts at 40% of the width
+    assert!(harness.screen()[0].starts_with(&format!("╭ Nodes {}╮╭ Details", "─".repeat(23))));
+
+    harness
+        .mouse(MouseEventKind::Down(MouseButton::Left), 31, 3)
+        .mouse(MouseEventKind::Drag(MouseButton::Left), 47, 3)
+        .mouse(MouseEventKind::Up(MouseButton::Left), 47, 3);
+    assert!(harness.screen()[0].starts_with(&format!("╭ Nodes {}╮╭ Details", "─".repeat(39))));
+
+    // Neither column disappears, the details pane keeps a tenth of the width
+    harness
+        .mouse(MouseEventKind::Down(MouseButton::Left), 47, 3)
+        .mouse(MouseEventKind::Drag(MouseButton::Left), 79, 3);
+    assert_eq!(harness.screen()[0].chars().nth(72), Some('╭'));
+}
+
+#[test]
+fn dragging_the_details_scrollbar_scrolls() {
+    let mut harness = with_details_lines(50);
+    harness.buffer();
+    // The scrollbar is inside the right border, its track between the borders
+    harness.mouse(MouseEventKind::Down(MouseButton::Left), 78, 1);
+    assert!(harness.screen_contains("line 0 "));
+    // Halfway down the track is halfway through the lines
+    harness.mouse(MouseEventKind::Drag(MouseButton::Left), 78, 4);
+    assert!(harness.screen_contains("line 22 "));
+    assert!(!harness.screen_contains("line 21 "));
+    harness
+        .mouse(MouseEventKind::Drag(MouseButton::Left), 78, 7)
+        .mouse(MouseEventKind::Up(MouseButton::Left), 78, 7);
+    assert!(harness.screen_contains("line 49"));
+    assert!(!harness.screen_contains("following"));
+}
+
+#[test]
+fn narrow_terminals_show_one_pane_under_tabs() {
+    let mut harness = Harness::with_demo_graph(50, 12);
+    let screen = harness.screen();
+    assert!(screen[0].starts_with(" Nodes │ Topics │ Details "));
+    assert!(harness.screen_contains("/diagnostic_aggregator"));
+
+    harness.keys("t");
+    assert!(harness.screen_contains("/cmd_vel"));
+    assert!(!harness.screen_contains("/diagnostic_aggregator"));
+
+    // The tabs can be clicked
+    harness.click(20, 0);
+    assert_eq!(harness.app.pane_manager.focused_pane, PaneType::DetailsPane);
+    assert!(harness.screen_contains("Select a node or topic"));
+    harness.click(2, 0);
+    assert!(harness.screen_contains("/diagnostic_aggregator"));
+}
+
+#[test]
+fn tables_drop_columns_when_short_of_space() {
+    let mut harness = Harness::new(160, 20);
+    assert!(harness.screen_contains("Message Type"));
+    assert!(harness.screen_contains("Pubs"));
+    assert!(harness.screen_contains("Publishes"));
+
+    // Counts go first, then the type
+    harness.send(Event::Resize(120, 20));
+    assert!(harness.screen_contains("Message Type"));
+    assert!(!harness.screen_contains("Pubs"));
+    harness.send(Event::Resize(60, 20));
+    assert!(!harness.screen_contains("Message Type"));
+    assert!(!harness.screen_contains("Publishes"));
+}
+
+#[test]
+fn tiny_terminals_ask_for_more_room() {
+    let mut harness = Harness::new(30, 4);
+    assert!(harness.screen_contains("Terminal too small"));
+    assert!(harness.screen_contains("30x4, needs 20x6"));
+
+    harness.send(Event::Resize(30, 10));
+    assert!(!harness.screen_contains("Terminal too small"));
+    assert!(harness.screen_contains(" Nodes │ Topics │ Details "));
+}
+
+#[test]
+fn yank_copies_the_selection_with_a_toast() {
+    let mut harness = Harness::with_demo_graph(120, 30);
+    harness.keys("t").key(KeyCode::Enter).keys("jy");
+    assert_eq!(harness.app.clipboard.take().as_deref(), Some("/amcl_pose"));
+    assert!(harness.screen_contains("Copied /amcl_pose"));
+
+    harness.keys("Y");
+    assert_eq!(
+        harness.app.clipboard.take().as_deref(),
+        Some("geometry_msgs/msg/PoseWithCovarianceStamped")
+    );
+
+    // The details pane copies the newest message of an echo
+    harness.keys("e").ros(ROSEvent::TopicMessage(
+        "/amcl_pose".to_string(),
+        serde_json::json!({ "x": 1.0 }),
+    ));
+    harness.key(KeyCode::Esc).keys("dy");
+    assert_eq!(
+        harness.app.clipboard.take().as_deref(),
+        Some("{\n  \"x\": 1.0\n}")
+    );
+    assert!(harness.screen_contains("Copied 3 lines"));
+}
+
+#[test]
+fn yank_without_a_selection_copies_nothing() {
+    let mut harness = Harness::new(80, 10);
+    harness.keys("y");
+    assert_eq!(harness.app.clipboard, None);
+    assert!(harness.screen_contains("Nothing to copy"));
+}
+
+#[test]
+fn clipboard_text_is_sent_base64_encoded() {
+    for (text, encoded) in [
+        ("a", "YQ=="),
+        ("ab", "YWI="),
+        ("abc", "YWJj"),
+        ("/tf", "L3Rm"),
+    ] {
+        let mut out = vec![];
+        crate::terminal::copy_to_clipboard(&mut out, text).unwrap();
+        assert_eq!(out, format!("\x1b]52;c;{}\x07", encoded).as_bytes());
+    }
+}
+
+/// A message with a header, as most sensor messages have.
+fn stamped(sec: i64, x: f64, frame_id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "header": { "stamp": { "sec": sec, "nanosec": 7 }, "frame_id": frame_id },
+        "x": x,
+    })
+}
+
+#[test]
+fn export_saves_the_newest_messages_to_a_file() {
+    let path = std::env::temp_dir().join(format!("lazyros_export_{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+    let mut harness = Harness::new(120, 20);
+    harness.start_echo();
+    for sec in 1..=3 {
+        harness.ros(ROSEvent::TopicMessage(
+            "/amcl_pose".to_string(),
+            stamped(sec, sec as f64 / 2.0, "map, odom"),
+        ));
+    }
+
+    harness.keys("S");
+    let prompt = harness.screen().pop().unwrap();
+    assert!(prompt.starts_with(" Save the newest message to: amcl_pose_2"));
+    assert!(prompt.contains(".json█"));
+
+    // Replace the default name, and save up to ten messages
+    for _ in 0..harness.app.export.as_ref().unwrap().path.len() {
+        harness.key(KeyCode::Backspace);
+    }
+    harness.keys(path).key(KeyCode::Tab);
+    assert!(harness.screen_contains("Save the last 3 messages to:"));
+    harness.key(KeyCode::Enter);
+    assert!(harness.screen_contains(&format!("Saved 3 messages to {}", path)));
+
+    let csv = std::fs::read_to_string(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "receive_time,header_time,header.frame_id,header.stamp.nanosec,header.stamp.sec,x"
+    );
+    assert_eq!(lines.len(), 4);
+    assert!(lines[3].ends_with(r#",3.000000007,"map, odom",7,3,1.5"#));
+}
+
+#[test]
+fn export_needs_an_echo_with_messages() {
+    let mut harness = Harness::new(120, 20);
+    harness.keys("S");
+    assert!(harness.screen_contains("Echo a topic with e to save its messages"));
+    harness.start_echo().keys("S");
+    assert!(harness.screen_contains("No messages of /amcl_pose yet"));
+    assert!(harness.app.export.is_none());
+}
+
+/// Pin echoes of `/amcl_pose` and `/cmd_vel`, the first two topics of the demo graph.
+fn with_two_echoes() -> Harness {
+    let mut harness = Harness::with_demo_graph(120, 30);
+    harness.keys("t").key(KeyCode::Enter).keys("jeje");
+    harness
+}
+
+fn message(topic: &str, text: &str) -> ROSEvent {
+    ROSEvent::TopicMessage(topic.to_string(), serde_json::json!({ "data": text }))
+}
+
+#[test]
+fn pinned_echoes_keep_their_own_subscription_and_view() {
+    let mut harness = with_two_echoes();
+    let subscribed: Vec<String> = harness
+        .commands()
+        .into_iter()
+        .filter_map(|command| match command {
+            RosCommand::Subscribe { topic, .. } => Some(topic),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(subscribed, ["/amcl_pose", "/cmd_vel"]);
+    assert!(harness.screen_contains(" Echo /amcl_pose │ /cmd_vel "));
+
+    // The newest pin is current, the tabs switch between them
+    harness
+        .ros(message("/amcl_pose", "pose 1"))
+        .ros(message("/cmd_vel", "twist 1"));
+    assert!(harness.screen_contains("twist 1"));
+    assert!(!harness.screen_contains("pose 1"));
+    harness.keys("[");
+    assert!(harness.screen_contains("pose 1"));
+    assert!(!harness.screen_contains("twist 1"));
+
+    // Pausing one leaves the other running
+    harness
+        .key(KeyCode::Esc)
+        .keys("d")
+        .key(KeyCode::Enter)
+        .keys("p");
+    harness
+        .ros(message("/amcl_pose", "pose 2"))
+        .ros(message("/cmd_vel", "twist 2"));
+    assert!(!harness.screen_contains("pose 2"));
+    harness.keys("]");
+    assert!(harness.screen_contains("twist 2"));
+
+    // Echo in the details pane unpins the current one
+    harness.keys("e");
+    assert_eq!(
+        harness.subscriptions(),
+        [RosCommand::Unsubscribe {
+            topic: "/cmd_vel".to_string()
+        }]
+    );
+    assert!(harness.screen_contains(" Echo /amcl_pose "));
+    assert!(harness.screen_contains("paused, 1 new"));
+}
+
+#[test]
+fn pinned_echoes_show_side_by_side_in_a_grid() {
+    let mut harness = with_two_echoes();
+    harness
+        .ros(message("/amcl_pose", "pose 1"))
+        .ros(message("/cmd_vel", "twist 1"))
+        .keys("o");
+    let screen = harness.screen();
+    assert!(screen[0].contains("╭ Echo /amcl_pose "));
+    assert!(screen[0].contains("╭ Echo /cmd_vel "));
+    assert!(harness.screen_contains("pose 1"));
+    assert!(harness.screen_contains("twist 1"));
+
+    // Clicking a cell makes its echo current
+    assert_eq!(harness.app.pane_manager.current_echo, 1);
+    harness.click(60, 5);
+    assert_eq!(harness.app.pane_manager.current_echo, 0);
+
+    // Back to tabs, whose titles can be clicked too
+    harness.keys("o").buffer();
+    let title = harness.screen()[0].clone();
+    let tab = title.find("/cmd_vel").unwrap();
+    harness.click(title[..tab].chars().count() as u16 + 1, 0);
+    assert_eq!(harness.app.pane_manager.current_echo, 1);
+}
+
+#[test]
+fn diff_mode_marks_the_fields_that_changed() {
+    let theme = Theme::default();
+    let mut harness = Harness::new(120, 20);
+    harness
+        .start_echo()
+        .ros(ROSEvent::TopicMessage(
+            "/amcl_pose".to_string(),
+            stamped(1, 0.5, "map"),
+        ))
+        .ros(ROSEvent::TopicMessage(
+            "/amcl_pose".to_string(),
+            stamped(1, 1.5, "map"),
+        ))
+        .keys("c");
+    assert!(harness.screen_contains(" Echo /amcl_pose (diff) "));
+    let screen = harness.screen();
+    let buffer = harness.buffer();
+    let fg = |field: &str| {
+        let (y, line) = screen
+            .iter()
+            .enumerate()
+            .find(|(_, line)| line.contains(field))
+            .unwrap();
+        let x = line[..line.find(field).unwrap()].chars().count();
+        buffer.cell((x as u16, y as u16)).unwrap().fg
+    };
+    assert_eq!(fg("x: 1.5"), theme.changed);
+    assert_ne!(fg("frame_id: \"map\""), theme.changed);
+    assert!(harness.screen_contains("    sec: 1"));
+
+    // Only the changed leaf and its parents are left
+    harness.keys("C");
+    assert!(harness.screen_contains(" Echo /amcl_pose (changes) "));
+    assert!(harness.screen_contains("x: 1.5"));
+    assert!(!harness.screen_contains("frame_id"));
+
+    // The tree follows new messages, and goes away again with c
+    harness.ros(ROSEvent::TopicMessage(
+        "/amcl_pose".to_string(),
+        stamped(1, 1.5, "map"),
+    ));
+    assert!(harness.screen_contains("No field changed since the previous message"));
+    harness.keys("c");
+    assert!(harness.screen_contains(" Echo /amcl_pose "));
+    assert!(!harness.screen_contains("(changes)"));
+}
+
+#[test]
+fn echoes_show_latency_and_age_below() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let mut harness = Harness::new(120, 20);
+    harness.start_echo().ros(ROSEvent::TopicMessage(
+        "/amcl_pose".to_string(),
+        stamped(now.as_secs() as i64, 0.5, "map"),
+    ));
+    let bottom = harness.screen()[18].clone();
+    assert!(bottom.contains("╰ latency "));
+    assert!(bottom.contains(" ms, age "));
+    assert!(bottom.contains("(min "));
+}
+
+#[test]
+fn old_topics_are_flagged_stale() {
+    let theme = Theme::default();
+    let mut harness = Harness::with_demo_graph(120, 40);
+    let start = SystemTime::now();
+    let rates = |newest| {
+        let mut rates = rates(&[("/scan", 9.87), ("/odom", 0.0)]);
+        rates.get_mut("/scan").unwrap().newest = Some(newest);
+        rates
+    };
+    let topics = &mut harness.app.pane_manager.topics_pane;
+    topics.update_rates(rates(start), start);
+    topics.update_rates(rates(start), start + Duration::from_secs(3));
+    assert!(!harness.screen_contains("⚠"));
+
+    // Past `stale_after`, the rate gives way to the age of the newest message,
+    // even while messages with old stamps keep coming
+    let topics = &mut harness.app.pane_manager.topics_pane;
+    topics.update_rates(rates(start), start + Duration::from_secs(12));
+    let screen = harness.screen();
+    let (y, scan) = screen
+        .iter()
+        .enumerate()
+        .find(|(_, line)| line.contains("/scan "))
+        .unwrap();
+    assert!(scan.contains("⚠ 12s"));
+    let x = scan[..scan.find('⚠').unwrap()].chars().count();
+    assert_eq!(
+        harness.buffer().cell((x as u16, y as u16)).unwrap().fg,
+        theme.error
+    );
+    // Topics that never published are not stale, only quiet
+    let odom = screen.iter().find(|line| line.contains("/odom ")).unwrap();
+    assert!(!odom.contains("⚠"));
+
+    harness
+        .keys("t")
+        .key(KeyCode::Enter)
+        .keys("/scan")
+        .key(KeyCode::Enter)
+        .keys("j");
+    assert!(harness.screen_contains("Rate  stale, newest message 12 s old"));
+
+    // A recent message makes it fresh again
+    let now = start + Duration::from_secs(13);
+    let topics = &mut harness.app.pane_manager.topics_pane;
+    topics.update_rates(rates(now), now);
+    assert!(!harness.screen_contains("⚠"));
+}
+
+/// The pixels of the picture a preview decodes from `message`.
+fn decoded(msg_type: &str, message: serde_json::Value) -> Result<Vec<[u8; 3]>, String> {
+    let mut preview = Preview::new(msg_type, 10).unwrap();
+    preview.update(message, Instant::now());
+    match preview.picture() {
+        Some(picture) => Ok(picture.pixels().map(|pixel| pixel.0).collect()),
+        None => Err(format!("{:?}", preview)),
+    }
+}
+
+#[test]
+fn previews_report_what_they_cannot_draw() {
+    let mut harness = Harness::new(120, 20);
+    harness.start_echo();
+    let echo = &mut harness.app.pane_manager.echoes[0];
+    echo.preview = Preview::new("sensor_msgs/msg/Image", 10);
+    harness.ros(ROSEvent::TopicMessage(
+        "/amcl_pose".to_string(),
+        image(1, 1, "yuv422", vec![0, 0]),
+    ));
+    assert!(harness.screen_contains("Cannot draw the message: unsupported encoding 'yuv422'"));
+
+    let short = image(4, 4, "rgb8", vec![0; 3]);
+    let error = decoded("sensor_msgs/msg/Image", short).unwrap_err();
+    assert!(error.contains("3 bytes are too few for 4x4 rgb8"));
+}
+
+#[test]
+fn previews_decode_at_most_preview_fps_pictures() {
+    let mut preview = Preview::new("sensor_msgs/msg/Image", 10).unwrap();
+    let start = Instant::now();
+    let pixel = |preview: &Preview| preview.picture().unwrap().get_pixel(0, 0).0;
+    preview.update(image(1, 1, "mono8", vec![1]), start);
+    preview.update(
+        image(1, 1, "mono8", vec![2]),
+        start + Duration::from_millis(40),
+    );
+    preview.update(
+        image(1, 1, "mono8", vec![3]),
+        start + Duration::from_millis(80),
+    );
+    assert_eq!(pixel(&preview), [1; 3]);
+    assert_eq!(preview.frame, 1);
+
+    // The newest message held back is drawn once the frame is due
+    assert!(!preview.tick(start + Duration::from_millis(90)));
+    assert!(preview.tick(start + Duration::from_millis(100)));
+    assert_eq!(pixel(&preview), [3; 3]);
+    assert!(!preview.tick(start + Duration::from_millis(300)));
+}
+
+#[test]
+fn image_echoes_show_a_preview_in_half_blocks() {
+    let mut harness = Harness::with_demo_graph(120, 30);
+    harness
+        .keys("t")
+        .key(KeyCode::Enter)
+        .keys("/image_raw")
+        .key(KeyCode::Enter)
+        .keys("je")
+        .ros(ROSEvent::TopicMessage(
+            "/robot/camera/image_raw".to_string(),
+            image(2, 2, "rgb8", vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 9, 9, 9]),
+        ));
+    assert!(harness.screen_contains(" 2x2 rgb8 "));
+
+    let buffer = harness.buffer();
+    let blocks: Vec<_> = buffer
+        .content
+        .iter()
+        .filter(|cell| cell.symbol() == "▀")
+        .collect();
+    assert!(blocks.len() > 100);
+    let (first, last) = (blocks[0], blocks[blocks.len() - 1]);
+    assert_eq!(
+        (first.fg, first.bg),
+        (Color::Rgb(255, 0, 0), Color::Rgb(255, 0, 0))
+    );
+    assert_eq!(
+        (last.fg, last.bg),
+        (Color::Rgb(9, 9, 9), Color::Rgb(9, 9, 9))
+    );
+
+    // The messages are listed with their bytes counted
+    harness.keys("P");
+    assert!(!harness.screen_contains("▀"));
+    assert!(harness.screen_contains("\"data\":\"<12 bytes>\""));
+    harness.keys("P");
+    assert!(harness.screen_contains("▀"));
+}
+
+#[test]
+fn scan_echoes_are_plotted_from_above_with_zoom() {
+    let mut harness = Harness::with_demo_graph(120, 30);
+    harness
+        .keys("t")
+        .key(KeyCode::Enter)
+        .keys("/scan")
+        .key(KeyCode::Enter)
+        .keys("je")
+        .ros(ROSEvent::TopicMessage(
+            "/scan".to_string(),
+            scan(serde_json::json!([1.0, 2.0, 3.0])),
+        ));
+    assert!(harness.screen_contains(" 3 points, rings 5 m, by intensity "));
+    assert!(harness.screen_contains("5 m"));
+    let braille = harness
+        .buffer()
+        .content
+        .iter()
+        .filter(|cell| ('\u{2801}'..='\u{28ff}').contains(&cell.symbol().chars().next().unwrap()))
+        .count();
+    assert!(braille > 20);
+
+    // The plot takes the keys of the focused details pane
+    harness
+        .key(KeyCode::Esc)
+        .keys("d")
+        .key(KeyCode::Enter)
+        .keys("++");
+    assert!(harness.screen_contains(" 3 points, rings 2 m, by intensity "));
+    harness.keys("m");
+    assert!(harness.screen_contains(" 3 points, rings 2 m, by height "));
+    harness.keys("0");
+    assert!(harness.screen_contains(" 3 points, rings 5 m, by height "));
+
+    // So does the mouse wheel
+    harness.mouse(MouseEventKind::ScrollUp, 100, 15);
+    harness.mouse(MouseEventKind::ScrollUp, 100, 15);
+    assert!(harness.screen_contains(" 3 points, rings 2 m, by height "));
+}
+
+#[test]
+fn maps_are_drawn_with_a_pose_on_top() {
+    let mut harness = Harness::with_demo_graph(120, 30);
+    harness.keys("t").key(KeyCode::Enter).keys("je");
+    harness.keys("O");
+    assert!(harness.screen_contains("Echo an occupancy grid topic to overlay a pose on it"));
+
+    // Cells go from the origin up, free, occupied, unknown and a cost
+    let map = grid(4, 2, &[0, 100, -1, 50, 0, 0, 0, 0]);
+    harness
+        .keys("/map")
+        .key(KeyCode::Enter)
+        .keys("je")
+        .ros(ROSEvent::TopicMessage("/map".to_string(), map))
+        .ros(ROSEvent::TopicMessage(
+            "/amcl_pose".to_string(),
+            pose_with_covariance(0.5, 1.5, std::f64::consts::FRAC_PI_2),
+        ));
+    assert!(harness.screen_contains(" 4x2 at 1 m, zoom 1.0x "));
+    let colours: Vec<_> = harness
+        .buffer()
+        .content
+        .iter()
+        .filter(|cell| cell.symbol() == "▀")
+        .flat_map(|cell| [cell.fg, cell.bg])
+        .collect();
+    for colour in [
+        Color::Rgb(230, 230, 230),
+        Color::Rgb(0, 0, 0),
+        Color::Rgb(100, 120, 130),
+        Color::Rgb(115, 115, 115),
+    ] {
+        assert!(colours.contains(&colour), "{:?}", colour);
+    }
+    assert!(!harness.screen_contains("↑"));
+
+    // The pose of another pinned echo, heading north
+    harness.keys("O");
+    assert!(harness.screen_contains(" /map (pose /amcl_pose) "));
+    assert!(harness.screen_contains("↑"));
+    harness.keys("O");
+    assert!(!harness.screen_contains("(pose /amcl_pose)"));
+
+    harness
+        .key(KeyCode::Esc)
+        .keys("d")
+        .key(KeyCode::Enter)
+        .keys("+");
+    assert!(harness.screen_contains(" 4x2 at 1 m, zoom 1.5x "));
+}
@@ -0,0 +1,1118 @@
+use std::{collections::HashMap, fmt, fs, path::PathBuf, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Color;
+use serde::Deserialize;
+
+/// Everything the user can bind a key to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    ToggleInstructions,
+    ToggleGraph,
+    ToggleDiagnostics,
+    ActivatePane,
+    Back,
+    NextPane,
+    PreviousPane,
+    FocusNodes,
+    FocusTopics,
+    FocusDetails,
+    Up,
+    Down,
+    Left,
+    Right,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    CenterView,
+    ToggleDebugTopics,
+    FilterNamespace,
+    ToggleFold,
+    IncreaseStaleTimeout,
+    DecreaseStaleTimeout,
+    Filter,
+    ToggleFilterTypes,
+    ToggleSortOrder,
+    ToggleTreeView,
+    ToggleHidden,
+    RestartBackend,
+    PageUp,
+    PageDown,
+    ScrollTop,
+    ScrollBottom,
+    ToggleWrap,
+    ToggleFollow,
+    TogglePause,
+    NextMatch,
+    PreviousMatch,
+    Echo,
+    Yank,
+    YankType,
+    Export,
+    NextEcho,
+    PreviousEcho,
+    ToggleEchoGrid,
+    ToggleDiff,
+    ToggleOnlyChanges,
+    TogglePreview,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    CycleColouring,
+    CyclePoseTopic,
+}
+
+impl Action {
+    /// The actions in the order they are listed in the instructions popup.
+    pub const ALL: [Action; 55] = [
+        Action::Quit,
+        Action::ToggleInstructions,
+        Action::ToggleGraph,
+        Action::ToggleDiagnostics,
+        Action::ActivatePane,
+        Action::Back,
+        Action::NextPane,
+        Action::PreviousPane,
+        Action::FocusNodes,
+        Action::FocusTopics,
+        Action::FocusDetails,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::PanUp,
+        Action::PanDown,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::CenterView,
+        Action::ToggleDebugTopics,
+        Action::FilterNamespace,
+        Action::ToggleFold,
+        Action::IncreaseStaleTimeout,
+        Action::DecreaseStaleTimeout,
+        Action::Filter,
+        Action::ToggleFilterTypes,
+        Action::ToggleSortOrder,
+        Action::ToggleTreeView,
+        Action::ToggleHidden,
+        Action::RestartBackend,
+        Action::PageUp,
+        Action::PageDown,
+        Action::ScrollTop,
+        Action::ScrollBottom,
+        Action::ToggleWrap,
+        Action::ToggleFollow,
+        Action::TogglePause,
+        Action::NextMatch,
+        Action::PreviousMatch,
+        Action::Echo,
+        Action::Yank,
+        Action::YankType,
+        Action::Export,
+        Action::NextEcho,
+        Action::PreviousEcho,
+        Action::ToggleEchoGrid,
+        Action::ToggleDiff,
+        Action::ToggleOnlyChanges,
+        Action::TogglePreview,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ResetZoom,
+        Action::CycleColouring,
+        Action::CyclePoseTopic,
+    ];
+
+    /// Headings of the instructions popup, in the order they are listed.
+    pub const SECTIONS: [&'static str; 8] = [
+        "General",
+        "Panes",
+        "Nodes and topics",
+        "Details",
+        "Echo",
+        "Plot and map",
+        "Graph",
+        "Diagnostics",
+    ];
+
+    /// The heading the action is listed under in the instructions popup.
+    pub fn section(&self) -> &'static str {
+        match self {
+            Action::Quit
+            | Action::ToggleInstructions
+            | Action::ToggleGraph
+            | Action::ToggleDiagnostics
+            | Action::ToggleHidden
+            | Action::RestartBackend
+            | Action::Yank => "General",
+            Action::ActivatePane
+            | Action::Back
+            | Action::NextPane
+            | Action::PreviousPane
+            | Action::FocusNodes
+            | Action::FocusTopics
+            | Action::FocusDetails
+            | Action::Up
+            | Action::Down
+            | Action::Left
+            | Action::Right => "Panes",
+            Action::Filter
+            | Action::ToggleFilterTypes
+            | Action::ToggleSortOrder
+            | Action::ToggleTreeView
+            | Action::ToggleFold
+            | Action::Echo
+            | Action::YankType => "Nodes and topics",
+            Action::PageUp
+            | Action::PageDown
+            | Action::ScrollTop
+            | Action::ScrollBottom
+            | Action::ToggleWrap
+            | Action::ToggleFollow
+            | Action::TogglePause
+            | Action::NextMatch
+            | Action::PreviousMatch => "Details",
+            Action::Export
+            | Action::NextEcho
+            | Action::PreviousEcho
+            | Action::ToggleEchoGrid
+            | Action::ToggleDiff
+            | Action::ToggleOnlyChanges
+            | Action::TogglePreview => "Echo",
+            Action::ZoomIn
+            | Action::ZoomOut
+            | Action::ResetZoom
+            | Action::CycleColouring
+            | Action::CyclePoseTopic => "Plot and map",
+            Action::PanUp
+            | Action::PanDown
+            | Action::PanLeft
+            | Action::PanRight
+            | Action::CenterView
+            | Action::ToggleDebugTopics
+            | Action::FilterNamespace => "Graph",
+            Action::IncreaseStaleTimeout | Action::DecreaseStaleTimeout => "Diagnostics",
+        }
+    }
+
+    /// Where the app matches the action, which decides the actions it cannot
+    /// share a key with.
+    fn scope(&self) -> Scope {
+        match self {
+            Action::Quit => Scope::Everywhere,
+            Action::ToggleInstructions
+            | Action::ToggleHidden
+            | Action::RestartBackend
+            | Action::Echo
+            | Action::NextEcho
+            | Action::PreviousEcho
+            | Action::ToggleEchoGrid
+            | Action::TogglePreview
+            | Action::CyclePoseTopic
+            | Action::ToggleDiff
+            | Action::ToggleOnlyChanges
+            | Action::Export
+            | Action::Yank
+            | Action::YankType
+            | Action::Filter => Scope::MainView,
+            Action::ToggleGraph
+            | Action::ToggleDiagnostics
+            | Action::ActivatePane
+            | Action::NextPane
+            | Action::PreviousPane
+            | Action::FocusNodes
+            | Action::FocusTopics
+            | Action::FocusDetails => Scope::Navigation,
+            Action::PanUp
+            | Action::PanDown
+            | Action::PanLeft
+            | Action::PanRight
+            | Action::CenterView
+            | Action::ToggleDebugTopics
+            | Action::FilterNamespace
+            | Action::IncreaseStaleTimeout
+            | Action::DecreaseStaleTimeout => Scope::View,
+            _ => Scope::Pane,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::ToggleInstructions => "Toggle instructions",
+            Action::ToggleGraph => "Toggle graph view",
+            Action::ToggleDiagnostics => "Toggle diagnostics dashboard",
+            Action::ActivatePane => "Focus window",
+            Action::Back => "Escape focused window",
+            Action::NextPane => "Next pane",
+            Action::PreviousPane => "Previous pane",
+            Action::FocusNodes => "Go to nodes pane",
+            Action::FocusTopics => "Go to topics pane",
+            Action::FocusDetails => "Go to details pane",
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::Left => "Left / sort by previous column",
+            Action::Right => "Right / sort by next column",
+            Action::PanUp => "Graph: pan up",
+            Action::PanDown => "Graph: pan down",
+            Action::PanLeft => "Graph: pan left",
+            Action::PanRight => "Graph: pan right",
+            Action::CenterView => "Graph: center on selected node",
+            Action::ToggleDebugTopics => "Graph: toggle debug topics",
+            Action::FilterNamespace => "Graph: filter by namespace",
+            Action::ToggleFold => "Fold/unfold diagnostics group or namespace",
+            Action::IncreaseStaleTimeout => "Diagnostics: increase stale timeout",
+            Action::DecreaseStaleTimeout => "Diagnostics: decrease stale timeout",
+            Action::Filter => "Nodes/Topics: filter by name, Details: search",
+            Action::ToggleFilterTypes => "Filter: also match message types",
+            Action::ToggleSortOrder => "Nodes/Topics: reverse sort order",
+            Action::ToggleTreeView => "Nodes/Topics: switch between list and namespace tree",
+            Action::ToggleHidden => "Nodes/Topics: show/hide hidden and infrastructure names",
+            Action::RestartBackend => "Restart the ROS backend",
+            Action::PageUp => "Details: page up",
+            Action::PageDown => "Details: page down",
+            Action::ScrollTop => "Details: go to the oldest line",
+            Action::ScrollBottom => "Details: go to the newest line and follow",
+            Action::ToggleWrap => "Details: wrap long lines",
+            Action::ToggleFollow => "Details: follow new lines",
+            Action::TogglePause => "Details: pause/resume new lines",
+            Action::NextMatch => "Details: next search match",
+            Action::PreviousMatch => "Details: previous search match",
+            Action::Echo => "Topics: pin an echo of the selected topic, again to unpin",
+            Action::Yank => "Copy the selected name or the details to the clipboard",
+            Action::YankType => "Topics/Echo: copy the message type to the clipboard",
+            Action::Export => "Echo: save messages to a JSON, YAML or CSV file",
+            Action::NextEcho => "Echo: switch to the next pinned topic",
+            Action::PreviousEcho => "Echo: switch to the previous pinned topic",
+            Action::ToggleEchoGrid => "Echo: show the pinned topics as tabs or side by side",
+            Action::ToggleDiff => {
+                "Echo: show the newest message as a field tree with changes marked"
+            }
+            Action::ToggleOnlyChanges => "Echo: show only the fields that changed",
+            Action::TogglePreview => "Echo: switch between the preview and the messages",
+            Action::ZoomIn => "Plot: zoom in",
+            Action::ZoomOut => "Plot: zoom out",
+            Action::ResetZoom => "Plot: reset zoom and pan",
+            Action::CycleColouring => "Plot: colour points by intensity, height or not at all",
+            Action::CyclePoseTopic => {
+                "Map: overlay the pose of the next pinned pose or odometry echo"
+            }
+        }
+    }
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["q"],
+            Action::ToggleInstructions => &["i"],
+            Action::ToggleGraph => &["g"],
+            Action::ToggleDiagnostics => &["D"],
+            Action::ActivatePane => &["enter"],
+            Action::Back => &["esc"],
+            Action::NextPane => &["l", "right"],
+            Action::PreviousPane => &["h", "left"],
+            Action::FocusNodes => &["n"],
+            Action::FocusTopics => &["t"],
+            Action::FocusDetails => &["d"],
+            Action::Up => &["k", "up"],
+            Action::Down => &["j", "down"],
+            Action::Left => &["h", "left"],
+            Action::Right => &["l", "right"],
+            Action::PanUp => &["up"],
+            Action::PanDown => &["down"],
+            Action::PanLeft => &["left"],
+            Action::PanRight => &["right"],
+            Action::CenterView => &["c"],
+            Action::ToggleDebugTopics => &["x"],
+            Action::FilterNamespace => &["f"],
+            Action::ToggleFold => &["enter", "space"],
+            Action::IncreaseStaleTimeout => &["+"],
+            Action::DecreaseStaleTimeout => &["-"],
+            Action::Filter => &["/"],
+            Action::ToggleFilterTypes => &["tab"],
+            Action::ToggleSortOrder => &["s"],
+            Action::ToggleTreeView => &["v"],
+            Action::ToggleHidden => &["."],
+            Action::RestartBackend => &["R"],
+            Action::PageUp => &["pageup"],
+            Action::PageDown => &["pagedown"],
+            Action::ScrollTop => &["g", "home"],
+            Action::ScrollBottom => &["G", "end"],
+            Action::ToggleWrap => &["w"],
+            Action::ToggleFollow => &["f"],
+            Action::TogglePause => &["p"],
+            Action::NextMatch => &["n"],
+            Action::PreviousMatch => &["N"],
+            Action::Echo => &["e"],
+            Action::Yank => &["y"],
+            Action::YankType => &["Y"],
+            Action::Export => &["S"],
+            Action::NextEcho => &["]"],
+            Action::PreviousEcho => &["["],
+            Action::ToggleEchoGrid => &["o"],
+            Action::ToggleDiff => &["c"],
+            Action::ToggleOnlyChanges => &["C"],
+            Action::TogglePreview => &["P"],
+            Action::ZoomIn => &["+", "="],
+            Action::ZoomOut => &["-"],
+            Action::ResetZoom => &["0"],
+            Action::CycleColouring => &["m"],
+            Action::CyclePoseTopic => &["O"],
+        }
+    }
+}
+
+/// Where a key is matched against an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// In every view, like quitting.
+    Everywhere,
+    /// In the main view, before the focused pane gets the key.
+    MainView,
+    /// In the main view while no pane is focused, where switching to another
+    /// view comes before moving between the panes.
+    Navigation,
+    /// By the focused pane of the main view.
+    Pane,
+    /// Only in one view, such as the graph or the diagnostics dashboard.
+    View,
+}
+
+impl Scope {
+    /// Whether an action of `self` takes the keys of an action of `other`
+    /// away, so the two cannot share a key.
+    fn shadows(self, other: Scope) -> bool {
+        matches!(
+            (self, other),
+            (Scope::Everywhere, _)
+                | (_, Scope::Everywhere)
+                | (
+                    Scope::MainView,
+                    Scope::MainView | Scope::Navigation | Scope::Pane
+                )
+                | (Scope::Navigation | Scope::Pane, Scope::MainView)
+                | (Scope::Navigation, Scope::Navigation)
+        )
+    }
+}
+
+/// A key together with its modifiers, written as e.g. `q`, `ctrl-c` or `shift-tab`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn matches(&self, key_event: &KeyEvent) -> bool {
+        // Shift is already part of the character for printable keys and back tab
+        let modifiers = match key_event.code {
+            KeyCode::Char(_) | KeyCode::BackTab => key_event.modifiers - KeyModifiers::SHIFT,
+            _ => key_event.modifiers,
+        };
+        self.code == key_event.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // A single '-' is a key, not a separator
+        while let Some((modifier, key)) = rest.split_once('-').filter(|(_, key)| !key.is_empty()) {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in key '{}'", modifier, s)),
+            };
+            rest = key;
+        }
+
+        let code = match rest.to_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            name => match (name.strip_prefix('f'), rest.chars().count()) {
+                (_, 1) => KeyCode::Char(rest.chars().next().unwrap()),
+                (Some(number), _) if number.parse::<u8>().is_ok_and(|n| (1..=12).contains(&n)) => {
+                    KeyCode::F(number.parse().unwrap())
+                }
+                _ => return Err(format!("unknown key '{}'", s)),
+            },
+        };
+
+        // `shift-d` arrives as `D`, `shift-tab` as back tab
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers -= KeyModifiers::SHIFT;
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers -= KeyModifiers::SHIFT;
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+
+        Ok(KeyBinding { code, modifiers })
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl-"),
+            (KeyModifiers::ALT, "alt-"),
+            (KeyModifiers::SHIFT, "shift-"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::BackTab => write!(f, "shift-tab"),
+            code => write!(f, "{}", format!("{:?}", code).to_lowercase()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = Action::ALL
+            .iter()
+            .map(|action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .map(|key| key.parse().expect("default key bindings are valid"))
+                    .collect();
+                (*action, keys)
+            })
+            .collect();
+        KeyBindings { bindings }
+    }
+}
+
+impl KeyBindings {
+    pub fn matches(&self, action: Action, key_event: &KeyEvent) -> bool {
+        self.bindings
+            .get(&action)
+            .is_some_and(|keys| keys.iter().any(|key| key.matches(key_event)))
+    }
+
+    /// Two actions sharing a key where one takes it away from the other, and
+    /// the key. The actions come in the order of `Action::ALL`.
+    fn conflict(&self) -> Option<(Action, Action, KeyBinding)> {
+        let keys = |action: &Action| self.bindings.get(action).map(Vec::as_slice).unwrap_or(&[]);
+        Action::ALL.iter().enumerate().find_map(|(i, a)| {
+            Action::ALL[i + 1..]
+                .iter()
+                .filter(|b| a.scope().shadows(b.scope()))
+                .find_map(|b| {
+                    let key = keys(a).iter().find(|key| keys(b).contains(key))?;
+                    Some((*a, *b, *key))
+                })
+        })
+    }
+
+    /// Human readable keys of an action, e.g. `k/up`.
+    pub fn keys(&self, action: Action) -> String {
+        self.bindings
+            .get(&action)
+            .map(|keys| {
+                keys.iter()
+                    .map(|key| key.to_string())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub border: Color,
+    pub focused_border: Color,
+    pub active_border: Color,
+    pub header: Color,
+    pub text: Color,
+    pub selection: Color,
+    pub key_hint: Color,
+    pub search_match: Color,
+    pub error: Color,
+    /// Fields of a message that changed since the previous one.
+    pub changed: Color,
+    /// Node and topic boxes of the graph view.
+    pub node: Color,
+    pub topic: Color,
+    /// Edges of the graph view outside the selected node's neighbourhood.
+    pub edge: Color,
+    /// Diagnostic levels, errors take `error`.
+    pub ok: Color,
+    pub warn: Color,
+    pub stale: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            border: Color::White,
+            focused_border: Color::Blue,
+            active_border: Color::Green,
+            header: Color::Yellow,
+            text: Color::White,
+            selection: Color::Green,
+            key_hint: Color::Blue,
+            search_match: Color::Magenta,
+            error: Color::Red,
+            changed: Color::Yellow,
+            node: Color::Cyan,
+            topic: Color::Yellow,
+            edge: Color::DarkGray,
+            ok: Color::Green,
+            warn: Color::Yellow,
+            stale: Color::DarkGray,
+        }
+    }
+}
+
+/// Split percentages of the main view.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Width of the column holding the nodes and topics panes.
+    pub left_column_percent: u16,
+    /// Height of the nodes pane inside the left column.
+    pub nodes_pane_percent: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            left_column_percent: 40,
+            nodes_pane_percent: 50,
+        }
+    }
+}
+
+/// A name pattern of the ignore list: a glob, or a regex when prefixed with `re:`.
+#[derive(Debug, Clone)]
+pub enum NamePattern {
+    Glob(glob::Pattern),
+    Regex(regex::Regex),
+}
+
+impl NamePattern {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            // `*` stays inside one namespace segment, `**` crosses them
+            NamePattern::Glob(pattern) => pattern.matches_with(
+                name,
+                glob::MatchOptions {
+                    require_literal_separator: true,
+                    ..Default::default()
+                },
+            ),
+            NamePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+impl FromStr for NamePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix("re:") {
+            Some(regex) => regex::Regex::new(regex)
+                .map(NamePattern::Regex)
+                .map_err(|e| format!("invalid regex '{}': {}", regex, e)),
+            None => glob::Pattern::new(s)
+                .map(NamePattern::Glob)
+                .map_err(|e| format!("invalid glob '{}': {}", s, e)),
+        }
+    }
+}
+
+/// Which nodes and topics the panes list.
+#[derive(Debug, Clone, Default)]
+pub struct FilterConfig {
+    /// Start with hidden and infrastructure names shown.
+    pub show_hidden: bool,
+    /// Names never listed, whatever the hidden toggle says.
+    pub ignore: Vec<NamePattern>,
+}
+
+impl FilterConfig {
+    pub fn is_ignored(&self, name: &str) -> bool {
+        self.ignore.iter().any(|pattern| pattern.matches(name))
+    }
+}
+
+/// How the terminal is drawn.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    /// Upper bound on redraws per second caused by ROS messages. Input is
+    /// always drawn right away.
+    pub max_fps: u32,
+    /// Lines the details pane keeps before dropping the oldest ones.
+    pub details_max_lines: usize,
+    /// Capture the mouse to click, scroll and drag. Off leaves selecting text
+    /// to the terminal.
+    pub mouse: bool,
+    /// Age in seconds of the newest message after which a topic is flagged as
+    /// stale, by its header stamp or else by when it came in.
+    pub stale_after: f64,
+    /// Seconds without an update after which a diagnostic status is stale, to
+    /// start with. The dashboard changes it while running.
+    pub diagnostics_stale_after: f64,
+    /// How previews draw pictures.
+    pub graphics: Graphics,
+    /// Upper bound on the pictures a preview decodes per second, the
+    /// messages in between are skipped.
+    pub preview_fps: u32,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        UiConfig {
+            max_fps: 30,
+            details_max_lines: 10_000,
+            mouse: true,
+            stale_after: 5.0,
+            diagnostics_stale_after: 5.0,
+            graphics: Graphics::Auto,
+            preview_fps: 10,
+        }
+    }
+}
+
+/// The ways to draw a picture in the terminal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Graphics {
+    /// Kitty or sixel graphics where the terminal is known to have them,
+    /// half-blocks elsewhere.
+    #[default]
+    Auto,
+    /// Two pixels per character with `▀`, works in every true colour terminal.
+    Halfblocks,
+    Kitty,
+    Sixel,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub keys: KeyBindings,
+    pub theme: Theme,
+    pub layout: LayoutConfig,
+    pub filter: FilterConfig,
+    pub ui: UiConfig,
+}
+
+/// The file as written by the user, before validation.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    keys: HashMap<Action, Vec<String>>,
+    theme: HashMap<String, String>,
+    layout: LayoutConfig,
+    filter: FilterFile,
+    ui: UiConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FilterFile {
+    show_hidden: bool,
+    ignore: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ConfigError {
+    path: PathBuf,
+    message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid config {}: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/lazyros/config.toml`, falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("lazyros").join("config.toml"))
+    }
+
+    /// Load the config at `path`. A missing file gives the default config.
+    pub fn load(path: PathBuf) -> Result<Config, ConfigError> {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => {
+                return Err(ConfigError {
+                    path,
+                    message: e.to_string(),
+                })
+            }
+        };
+        Config::parse(&content).map_err(|message| ConfigError { path, message })
+    }
+
+    /// The configuration of a file with `content`.
+    pub fn parse(content: &str) -> Result<Config, String> {
+        let file: ConfigFile = toml::from_str(content).map_err(|e| e.to_string())?;
+        let mut config = Config::default();
+
+        for (action, keys) in file.keys {
+            let keys = keys
+                .iter()
+                .map(|key| key.parse())
+                .collect::<Result<Vec<KeyBinding>, String>>()
+                .map_err(|e| format!("keys.{}: {}", action_name(action), e))?;
+            config.keys.bindings.insert(action, keys);
+        }
+        if let Some((a, b, key)) = config.keys.conflict() {
+            return Err(format!(
+                "keys: '{}' is bound to both {} and {}",
+                key,
+                action_name(a),
+                action_name(b)
+            ));
+        }
+
+        for (name, value) in file.theme {
+            let color = value
+                .parse::<Color>()
+                .map_err(|_| format!("theme.{}: unknown colour '{}'", name, value))?;
+            let field = match name.as_str() {
+                "border" => &mut config.theme.border,
+                "focused_border" => &mut config.theme.focused_border,
+                "active_border" => &mut config.theme.active_border,
+                "header" => &mut config.theme.header,
+                "text" => &mut config.theme.text,
+                "selection" => &mut config.theme.selection,
+                "key_hint" => &mut config.theme.key_hint,
+                "search_match" => &mut config.theme.search_match,
+                "error" => &mut config.theme.error,
+                "changed" => &mut config.theme.changed,
+                "node" => &mut config.theme.node,
+                "topic" => &mut config.theme.topic,
+                "edge" => &mut config.theme.edge,
+                "ok" => &mut config.theme.ok,
+                "warn" => &mut config.theme.warn,
+                "stale" => &mut config.theme.stale,
+                _ => return Err(format!("theme: unknown colour setting '{}'", name)),
+            };
+            *field = color;
+        }
+
+        for (name, percent) in [
+            ("left_column_percent", file.layout.left_column_percent),
+            ("nodes_pane_percent", file.layout.nodes_pane_percent),
+        ] {
+            if !(1..=99).contains(&percent) {
+                return Err(format!(
+                    "layout.{} must be between 1 and 99, got {}",
+                    name, percent
+                ));
+            }
+        }
+        config.layout = file.layout;
+
+        if !(1..=240).contains(&file.ui.max_fps) {
+            return Err(format!(
+                "ui.max_fps must be between 1 and 240, got {}",
+                file.ui.max_fps
+            ));
+        }
+        if file.ui.details_max_lines == 0 {
+            return Err("ui.details_max_lines must be at least 1".to_string());
+        }
+        if !(1..=240).contains(&file.ui.preview_fps) {
+            return Err(format!(
+                "ui.preview_fps must be between 1 and 240, got {}",
+                file.ui.preview_fps
+            ));
+        }
+        for (name, seconds) in [
+            ("stale_after", file.ui.stale_after),
+            ("diagnostics_stale_after", file.ui.diagnostics_stale_after),
+        ] {
+            if seconds <= 0.0 || !seconds.is_finite() {
+                return Err(format!(
+                    "ui.{} must be a number of seconds above 0, got {}",
+                    name, seconds
+                ));
+            }
+        }
+        config.ui = file.ui;
+
+        config.filter = FilterConfig {
+            show_hidden: file.filter.show_hidden,
+            ignore: file
+                .filter
+                .ignore
+                .iter()
+                .map(|pattern| pattern.parse())
+                .collect::<Result<_, String>>()
+                .map_err(|e| format!("filter.ignore: {}", e))?,
+        };
+
+        Ok(config)
+    }
+}
+
+fn action_name(action: Action) -> String {
+    // Same spelling as in the file, e.g. `toggle_graph`
+    let name = format!("{:?}", action);
+    let mut snake_case = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake_case.push('_');
+        }
+        snake_case.push(c.to_ascii_lowercase());
+    }
+    snake_case
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_event(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn keys_are_parsed_with_their_modifiers() {
+        let key = |s: &str| s.parse::<KeyBinding>().unwrap();
+        assert_eq!(key("q").to_string(), "q");
+        assert_eq!(key("ctrl-c").to_string(), "ctrl-c");
+        assert_eq!(key("shift-tab").to_string(), "shift-tab");
+        assert_eq!(key("f5").to_string(), "f5");
+        assert_eq!(key("-").to_string(), "-");
+        assert_eq!(key("ctrl--").to_string(), "ctrl--");
+        // `shift-d` arrives as `D`
+        assert_eq!(key("shift-d"), key("D"));
+        assert!(key("D").matches(&key_event(KeyCode::Char('D'), KeyModifiers::SHIFT)));
+        // and `shift-tab` as back tab, with or without shift
+        assert_eq!(key("shift-tab"), key("backtab"));
+        assert!(key("shift-tab").matches(&key_event(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert!(key("shift-tab").matches(&key_event(KeyCode::BackTab, KeyModifiers::NONE)));
+        assert!(!key("tab").matches(&key_event(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert!(!key("d").matches(&key_event(KeyCode::Char('d'), KeyModifiers::CONTROL)));
+
+        assert!("hyper-x".parse::<KeyBinding>().is_err());
+        assert!("f13".parse::<KeyBinding>().is_err());
+        assert!("nokey".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn an_empty_file_gives_the_defaults() {
+        let config = Config::parse("").unwrap();
+        let default = Config::default();
+        assert_eq!(config.ui.max_fps, default.ui.max_fps);
+        assert_eq!(config.layout.left_column_percent, 40);
+        assert_eq!(config.keys.keys(Action::Quit), "q");
+        assert!(config.filter.ignore.is_empty());
+    }
+
+    #[test]
+    fn every_section_of_the_file_is_read() {
+        let config = Config::parse(
+            r##"
+            [keys]
+            quit = ["ctrl-q", "Q"]
+
+            [theme]
+            border = "gray"
+            selection = "#ff8800"
+
+            [layout]
+            left_column_percent = 30
+
+            [filter]
+            show_hidden = true
+            ignore = ["/rosout", "re:^/camera/.*/compressed$"]
+
+            [ui]
+            max_fps = 60
+            graphics = "sixel"
+            diagnostics_stale_after = 2.5
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(config.keys.keys(Action::Quit), "ctrl-q/Q");
+        // The other actions keep their default keys
+        assert_eq!(config.keys.keys(Action::ToggleGraph), "g");
+        assert_eq!(config.theme.border, Color::Gray);
+        assert_eq!(config.theme.selection, Color::Rgb(0xff, 0x88, 0x00));
+        assert_eq!(config.layout.left_column_percent, 30);
+        assert_eq!(config.layout.nodes_pane_percent, 50);
+        assert!(config.filter.show_hidden);
+        assert!(config.filter.is_ignored("/rosout"));
+        assert!(config.filter.is_ignored("/camera/front/compressed"));
+        assert!(!config.filter.is_ignored("/camera/front/raw"));
+        assert_eq!(config.ui.max_fps, 60);
+        assert_eq!(config.ui.graphics, Graphics::Sixel);
+        assert_eq!(config.ui.preview_fps, 10);
+        assert_eq!(config.ui.diagnostics_stale_after, 2.5);
+    }
+
+    #[test]
+    fn mistakes_in_the_file_are_reported() {
+        let error = |content: &str| Config::parse(content).unwrap_err();
+        assert!(error("[ui]\nfps = 30").contains("unknown field"));
+        assert!(error("[keys]\nteleport = [\"t\"]").contains("unknown variant"));
+        assert!(error("[keys]\nquit = [\"hyper-q\"]").starts_with("keys.quit:"));
+        assert!(error("[theme]\nborder = \"blurple\"").contains("unknown colour 'blurple'"));
+        assert!(error("[theme]\nfont = \"red\"").contains("unknown colour setting 'font'"));
+        assert!(error("[filter]\nignore = [\"re:(\"]").starts_with("filter.ignore:"));
+        assert!(error("[ui]\ngraphics = \"ascii\"").contains("unknown variant"));
+    }
+
+    #[test]
+    fn numbers_outside_their_range_are_rejected() {
+        let error = |content: &str| Config::parse(content).err();
+        assert!(Config::parse("[ui]\nmax_fps = 1").is_ok());
+        assert!(Config::parse("[ui]\nmax_fps = 240").is_ok());
+        assert_eq!(
+            error("[ui]\nmax_fps = 0").as_deref(),
+            Some("ui.max_fps must be between 1 and 240, got 0")
+        );
+        assert!(error("[ui]\nmax_fps = 241").is_some());
+        assert!(error("[ui]\nmax_fps = -1").is_some());
+
+        assert!(Config::parse("[ui]\npreview_fps = 240").is_ok());
+        assert_eq!(
+            error("[ui]\npreview_fps = 0").as_deref(),
+            Some("ui.preview_fps must be between 1 and 240, got 0")
+        );
+        assert!(error("[ui]\npreview_fps = 1000").is_some());
+
+        assert!(error("[ui]\ndetails_max_lines = 0").is_some());
+        assert!(error("[ui]\nstale_after = 0.0").is_some());
+        assert!(error("[ui]\nstale_after = nan").is_some());
+        assert!(error("[ui]\nstale_after = inf").is_some());
+        assert_eq!(
+            error("[ui]\ndiagnostics_stale_after = -1.0").as_deref(),
+            Some("ui.diagnostics_stale_after must be a number of seconds above 0, got -1")
+        );
+        assert!(error("[layout]\nleft_column_percent = 0").is_some());
+        assert!(error("[layout]\nnodes_pane_percent = 100").is_some());
+    }
+
+    #[test]
+    fn globs_stay_in_one_segment_and_regexes_match_anywhere() {
+        let names = [
+            "/scan",
+            "/camera/front/compressed",
+            "/rosout",
+            "/nav/_action/feedback",
+        ];
+        let ignored = |pattern: &str| {
+            let pattern: NamePattern = pattern.parse().unwrap();
+            names
+                .into_iter()
+                .filter(|name| pattern.matches(name))
+                .collect::<Vec<_>>()
+        };
+        assert!(ignored("/none").is_empty());
+
+        assert!(ignored("/camera/*").is_empty());
+        assert_eq!(ignored("/camera/**"), ["/camera/front/compressed"]);
+        assert_eq!(
+            ignored("/camera/*/compressed"),
+            ["/camera/front/compressed"]
+        );
+        assert_eq!(ignored("/*"), ["/scan", "/rosout"]);
+
+        // Regexes are not anchored unless they say so
+        assert_eq!(ignored("re:compressed"), ignored("/camera/**"));
+        assert_eq!(ignored("re:^/scan"), ignored("/scan"));
+        assert!(ignored("re:^compressed$").is_empty());
+    }
+
+    #[test]
+    fn the_default_keys_do_not_conflict() {
+        assert_eq!(KeyBindings::default().conflict(), None);
+    }
+
+    #[test]
+    fn keys_taken_away_from_another_action_are_rejected() {
+        // Echo is matched before the focused pane gets the key
+        assert_eq!(
+            Config::parse("[keys]\necho = [\"j\"]").err().as_deref(),
+            Some("keys: 'j' is bound to both down and echo")
+        );
+        // Quit is matched everywhere
+        assert!(Config::parse("[keys]\ncenter_view = [\"q\"]").is_err());
+        // While no pane is focused the views open before the panes are switched
+        assert_eq!(
+            Config::parse("[keys]\ntoggle_graph = [\"n\"]")
+                .err()
+                .as_deref(),
+            Some("keys: 'n' is bound to both toggle_graph and focus_nodes")
+        );
+        assert!(Config::parse("[keys]\ntoggle_diagnostics = [\"l\"]").is_err());
+        // Moving in a pane and panning the graph never compete for a key
+        assert!(Config::parse("[keys]\npan_up = [\"k\"]").is_ok());
+        // Neither do two pane actions, the focused pane decides
+        assert!(Config::parse("[keys]\ntoggle_wrap = [\"s\"]").is_ok());
+    }
+
+    #[test]
+    fn every_action_is_listed_under_a_heading() {
+        for action in Action::ALL {
+            assert!(
+                Action::SECTIONS.contains(&action.section()),
+                "{:?} has no heading",
+                action
+            );
+        }
+    }
+}
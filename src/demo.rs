@@ -0,0 +1,825 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    f64::consts::PI,
+    io,
+    sync::mpsc::{self, TryRecvError},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    diagnostics::{DiagnosticLevel, DiagnosticStatus, DIAGNOSTICS_TOPICS},
+    event::{Event, ROSEvent, TopicRate},
+    graph::{EndpointInfo, GraphSnapshot, QosInfo, ServiceInfo, TopicInfo},
+    ros::{Backend, RosCommand},
+};
+
+/// Simulated time between two steps, the demo thread also sleeps that long.
+pub const STEP: Duration = Duration::from_millis(100);
+
+/// Steps between two graph, rate and diagnostics updates.
+const STEPS_PER_UPDATE: u64 = 10;
+
+/// Name of the node lazyros shows for itself, as the real backend does.
+const LAZYROS_NODE: &str = "/lazyros";
+
+/// Half the side of the square room the robot drives around in, in metres.
+const ROOM_HALF_SIZE: f64 = 5.0;
+/// A round pillar in the room: centre and radius in metres.
+const PILLAR: (f64, f64, f64) = (3.0, 3.0, 0.5);
+
+struct DemoTopic {
+    name: &'static str,
+    msg_type: &'static str,
+    publishers: &'static [&'static str],
+    subscribers: &'static [&'static str],
+    /// Messages per second.
+    rate: f64,
+    best_effort: bool,
+    transient_local: bool,
+}
+
+/// A small mobile robot with a navigation stack.
+const TOPICS: &[DemoTopic] = &[
+    topic(
+        "/topic",
+        "std_msgs/msg/String",
+        &["/talker"],
+        &["/listener"],
+        1.0,
+    ),
+    topic(
+        "/odom",
+        "nav_msgs/msg/Odometry",
+        &["/robot/base_controller"],
+        &["/nav/localization", "/nav/controller"],
+        20.0,
+    ),
+    topic(
+        "/tf",
+        "tf2_msgs/msg/TFMessage",
+        &["/robot/base_controller", "/nav/localization"],
+        &["/nav/planner", "/nav/controller"],
+        50.0,
+    ),
+    topic(
+        "/cmd_vel",
+        "geometry_msgs/msg/Twist",
+        &["/nav/controller", "/teleop"],
+        &["/robot/base_controller"],
+        10.0,
+    ),
+    DemoTopic {
+        best_effort: true,
+        ..topic(
+            "/scan",
+            "sensor_msgs/msg/LaserScan",
+            &["/robot/lidar_driver"],
+            &["/nav/localization"],
+            10.0,
+        )
+    },
+    DemoTopic {
+        best_effort: true,
+        ..topic(
+            "/robot/camera/image_raw",
+            "sensor_msgs/msg/Image",
+            &["/robot/camera/driver"],
+            &[],
+            15.0,
+        )
+    },
+    DemoTopic {
+        transient_local: true,
+        ..topic(
+            "/map",
+            "nav_msgs/msg/OccupancyGrid",
+            &["/nav/map_server"],
+            &["/nav/localization", "/nav/planner"],
+            0.5,
+        )
+    },
+    topic(
+        "/amcl_pose",
+        "geometry_msgs/msg/PoseWithCovarianceStamped",
+        &["/nav/localization"],
+        &["/nav/planner"],
+        5.0,
+    ),
+    topic(
+        "/plan",
+        "nav_msgs/msg/Path",
+        &["/nav/planner"],
+        &["/nav/controller"],
+        1.0,
+    ),
+    topic(
+        "/navigate_to_pose/_action/feedback",
+        "nav2_msgs/action/NavigateToPose_FeedbackMessage",
+        &["/nav/planner"],
+        &[],
+        2.0,
+    ),
+    topic(
+        "/diagnostics",
+        "diagnostic_msgs/msg/DiagnosticArray",
+        &["/robot/base_controller", "/robot/lidar_driver"],
+        &["/diagnostic_aggregator"],
+        1.0,
+    ),
+    topic(
+        "/diagnostics_agg",
+        "diagnostic_msgs/msg/DiagnosticArray",
+        &["/diagnostic_aggregator"],
+        &[],
+        1.0,
+    ),
+];
+
+const fn topic(
+    name: &'static str,
+    msg_type: &'static str,
+    publishers: &'static [&'static str],
+    subscribers: &'static [&'static str],
+    rate: f64,
+) -> DemoTopic {
+    DemoTopic {
+        name,
+        msg_type,
+        publishers,
+        subscribers,
+        rate,
+        best_effort: false,
+        transient_local: false,
+    }
+}
+
+const SERVICES: &[(&str, &str)] = &[
+    ("/add_two_ints", "example_interfaces/srv/AddTwoInts"),
+    ("/nav/map_server/load_map", "nav2_msgs/srv/LoadMap"),
+];
+
+/// The backend running a simulated robot instead of talking to ROS.
+pub struct DemoBackend;
+
+impl Backend for DemoBackend {
+    fn spawn(&self, events: mpsc::Sender<Event>) -> io::Result<mpsc::Sender<RosCommand>> {
+        let (command_tx, command_rx) = mpsc::channel();
+        let start = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        thread::Builder::new()
+            .name("demo".to_string())
+            .spawn(move || {
+                let mut system = DemoSystem::new(start);
+                let send = |event| events.send(Event::ROSEvent { event }).is_ok();
+                loop {
+                    loop {
+                        match command_rx.try_recv() {
+                            Ok(command) => {
+                                if !system.execute(command).into_iter().all(send) {
+                                    return;
+                                }
+                            }
+                            Err(TryRecvError::Empty) => break,
+                            Err(TryRecvError::Disconnected) => return,
+                        }
+                    }
+                    if !system.step().into_iter().all(send) {
+                        return;
+                    }
+                    thread::sleep(STEP);
+                }
+            })?;
+        Ok(command_tx)
+    }
+}
+
+/// The state of the simulated robot. It only moves on with `step`, so the
+/// events it produces are the same on every run.
+#[derive(Debug, Default)]
+pub struct DemoSystem {
+    /// Header stamp of the first step, as time since the Unix epoch.
+    start: Duration,
+    steps: u64,
+    subscriptions: BTreeSet<String>,
+    rate_topics: BTreeSet<String>,
+    publishers: BTreeMap<String, String>,
+    clients: BTreeSet<String>,
+}
+
+impl DemoSystem {
+    pub fn new(start: Duration) -> DemoSystem {
+        DemoSystem {
+            start,
+            ..Default::default()
+        }
+    }
+
+    /// The graph as the real backend would see it, including lazyros itself.
+    pub fn graph(&self) -> GraphSnapshot {
+        let mut topics: Vec<TopicInfo> = TOPICS
+            .iter()
+            .map(|topic| {
+                let qos = QosInfo {
+                    reliability: match topic.best_effort {
+                        true => "BestEffort",
+                        false => "Reliable",
+                    }
+                    .to_string(),
+                    durability: match topic.transient_local {
+                        true => "TransientLocal",
+                        false => "Volatile",
+                    }
+                    .to_string(),
+                    history: "KeepLast".to_string(),
+                    depth: 10,
+                };
+                let endpoints = |nodes: &[&str]| -> Vec<EndpointInfo> {
+                    nodes
+                        .iter()
+                        .map(|node| EndpointInfo {
+                            node_name: node.to_string(),
+                            qos: qos.clone(),
+                        })
+                        .collect()
+                };
+
+                let mut subscribers = endpoints(topic.subscribers);
+                if self.subscriptions.contains(topic.name)
+                    || DIAGNOSTICS_TOPICS.contains(&topic.name)
+                {
+                    subscribers.extend(endpoints(&[LAZYROS_NODE]));
+                }
+                let mut publishers = endpoints(topic.publishers);
+                if self.publishers.contains_key(topic.name) {
+                    publishers.extend(endpoints(&[LAZYROS_NODE]));
+                }
+                TopicInfo {
+                    name: topic.name.to_string(),
+                    msg_types: vec![topic.msg_type.to_string()],
+                    publishers,
+                    subscribers,
+                }
+            })
+            .collect();
+
+        // Publishers lazyros created on topics nobody else uses
+        for (name, msg_type) in &self.publishers {
+            if TOPICS.iter().all(|topic| topic.name != name) {
+                topics.push(TopicInfo {
+                    name: name.clone(),
+                    msg_types: vec![msg_type.clone()],
+                    publishers: vec![EndpointInfo {
+                        node_name: LAZYROS_NODE.to_string(),
+                        qos: QosInfo::default(),
+                    }],
+                    subscribers: vec![],
+                });
+            }
+        }
+
+        // Every node logs to /rosout and announces its parameter changes
+        let mut nodes: BTreeSet<&str> = TOPICS
+            .iter()
+            .flat_map(|topic| topic.publishers.iter().chain(topic.subscribers))
+            .copied()
+            .collect();
+        nodes.insert(LAZYROS_NODE);
+        for (name, msg_type) in [
+            ("/rosout", "rcl_interfaces/msg/Log"),
+            ("/parameter_events", "rcl_interfaces/msg/ParameterEvent"),
+        ] {
+            topics.push(TopicInfo {
+                name: name.to_string(),
+                msg_types: vec![msg_type.to_string()],
+                publishers: nodes
+                    .iter()
+                    .map(|node| EndpointInfo {
+                        node_name: node.to_string(),
+                        qos: QosInfo::default(),
+                    })
+                    .collect(),
+                subscribers: vec![],
+            });
+        }
+
+        let mut graph = GraphSnapshot::from_topics(topics);
+        graph.services = SERVICES
+            .iter()
+            .map(|(name, srv_type)| ServiceInfo {
+                name: name.to_string(),
+                srv_types: vec![srv_type.to_string()],
+            })
+            .collect();
+        graph
+    }
+
+    /// Apply a command of the UI and return the events it causes right away.
+    pub fn execute(&mut self, command: RosCommand) -> Vec<ROSEvent> {
+        match command {
+            RosCommand::Subscribe { topic, msg_type } => {
+                if !self.simulates(&msg_type) {
+                    return vec![ROSEvent::BackendError(format!(
+                        "{}: the demo does not simulate {} messages",
+                        topic, msg_type
+                    ))];
+                }
+                self.subscriptions.insert(topic);
+            }
+            RosCommand::Unsubscribe { topic } => {
+                self.subscriptions.remove(&topic);
+            }
+            RosCommand::MeasureRates { topics } => {
+                self.rate_topics = topics.into_iter().collect();
+            }
+            RosCommand::CreatePublisher { topic, msg_type } => {
+                self.publishers.insert(topic, msg_type);
+            }
+            RosCommand::Publish { topic, message } => {
+                if !self.publishers.contains_key(&topic) {
+                    return vec![ROSEvent::BackendError(format!(
+                        "no publisher for {}",
+                        topic
+                    ))];
+                }
+                // The subscription of lazyros receives its own messages
+                if self.subscriptions.contains(&topic) {
+                    return vec![ROSEvent::TopicMessage(topic, message)];
+                }
+            }
+            RosCommand::DestroyPublisher { topic } => {
+                self.publishers.remove(&topic);
+            }
+            RosCommand::CreateClient { service, .. } => {
+                self.clients.insert(service);
+            }
+            RosCommand::CallService { service, request } => {
+                if !self.clients.contains(&service) {
+                    return vec![ROSEvent::BackendError(format!("no client for {}", service))];
+                }
+                let response = match service.as_str() {
+                    "/add_two_ints" => {
+                        let operand = |name| request[name].as_i64().unwrap_or_default();
+                        json!({ "sum": operand("a") + operand("b") })
+                    }
+                    "/nav/map_server/load_map" => json!({ "map": map(), "result": 0 }),
+                    _ => {
+                        return vec![ROSEvent::BackendError(format!(
+                            "{}: service not available",
+                            service
+                        ))]
+                    }
+                };
+                return vec![ROSEvent::ServiceResponse(service, response)];
+            }
+            RosCommand::DestroyClient { service } => {
+                self.clients.remove(&service);
+            }
+        }
+        vec![]
+    }
+
+    /// Advance the simulation by one `STEP` and return what happened meanwhile:
+    /// the messages of subscribed topics and, every second, the graph, the rates
+    /// and the diagnostics. The very first step sends the graph right away.
+    pub fn step(&mut self) -> Vec<ROSEvent> {
+        let step = self.steps;
+        self.steps += 1;
+        let mut events = vec![];
+
+        if step.is_multiple_of(STEPS_PER_UPDATE) {
+            events.push(ROSEvent::GraphUpdate(self.graph()));
+            events.push(ROSEvent::TopicRates(
+                TOPICS
+                    .iter()
+                    .filter(|topic| self.rate_topics.contains(topic.name))
+                    .map(|topic| {
+                        // A little jitter, as measured rates have
+                        let jitter = 1.0 + 0.02 * (step as f64 * 0.7 + topic.rate).sin();
+                        // Stamp of the last message published so far
+                        let elapsed = step as f64 * STEP.as_secs_f64();
+                        let last = (topic.rate * elapsed).floor() / topic.rate;
+                        let rate = TopicRate {
+                            hz: topic.rate * jitter,
+                            newest: Some(UNIX_EPOCH + self.start + Duration::from_secs_f64(last)),
+                        };
+                        (topic.name.to_string(), rate)
+                    })
+                    .collect(),
+            ));
+            for topic in DIAGNOSTICS_TOPICS {
+                events.push(ROSEvent::Diagnostics(
+                    topic.to_string(),
+                    diagnostics(topic, step),
+                ));
+            }
+        }
+
+        for topic in TOPICS {
+            if !self.subscriptions.contains(topic.name) {
+                continue;
+            }
+            // Messages whose publication time falls into this step
+            let step_seconds = STEP.as_secs_f64();
+            let sent_before = (topic.rate * step as f64 * step_seconds).floor() as u64;
+            let sent_after = (topic.rate * (step + 1) as f64 * step_seconds).floor() as u64;
+            for sequence in sent_before..sent_after {
+                let time = sequence as f64 / topic.rate;
+                if let Some(message) = self.message(topic.name, topic.msg_type, sequence, time) {
+                    events.push(ROSEvent::TopicMessage(topic.name.to_string(), message));
+                }
+            }
+        }
+        events
+    }
+
+    fn simulates(&self, msg_type: &str) -> bool {
+        self.message("", msg_type, 0, 0.0).is_some()
+    }
+
+    /// Message number `sequence` of a topic, published `time` seconds after the start.
+    fn message(&self, topic: &str, msg_type: &str, sequence: u64, time: f64) -> Option<Value> {
+        let header = |frame_id: &str| {
+            let stamp = self.start + Duration::from_secs_f64(time);
+            json!({
+                "stamp": { "sec": stamp.as_secs(), "nanosec": stamp.subsec_nanos() },
+                "frame_id": frame_id,
+            })
+        };
+        let (x, y, yaw) = robot_pose(time);
+
+        Some(match msg_type {
+            "std_msgs/msg/String" => json!({ "data": format!("Hello World: {}", sequence) }),
+            "geometry_msgs/msg/Twist" => twist(time),
+            "nav_msgs/msg/Odometry" => json!({
+                "header": header("odom"),
+                "child_frame_id": "base_link",
+                "pose": { "pose": pose(x, y, yaw), "covariance": vec![0.0; 36] },
+                "twist": { "twist": twist(time), "covariance": vec![0.0; 36] },
+            }),
+            "geometry_msgs/msg/PoseWithCovarianceStamped" => json!({
+                "header": header("map"),
+                "pose": { "pose": pose(x, y, yaw), "covariance": vec![0.0; 36] },
+            }),
+            "tf2_msgs/msg/TFMessage" => json!({
+                "transforms": [{
+                    "header": header("odom"),
+                    "child_frame_id": "base_link",
+                    "transform": {
+                        "translation": { "x": x, "y": y, "z": 0.0 },
+                        "rotation": quaternion(yaw),
+                    },
+                }],
+            }),
+            "nav_msgs/msg/Path" => json!({
+                "header": header("map"),
+                "poses": (1..=10)
+                    .map(|i| {
+                        let (x, y, yaw) = robot_pose(time + i as f64);
+                        json!({ "header": header("map"), "pose": pose(x, y, yaw) })
+                    })
+                    .collect::<Vec<_>>(),
+            }),
+            "sensor_msgs/msg/LaserScan" => {
+                let (ranges, intensities) = scan(x, y, yaw);
+                json!({
+                    "header": header("laser"),
+                    "angle_min": -PI,
+                    "angle_max": PI,
+                    "angle_increment": 2.0 * PI / ranges.len() as f64,
+                    "time_increment": 0.0,
+                    "scan_time": 0.1,
+                    "range_min": 0.1,
+                    "range_max": 12.0,
+                    "ranges": ranges,
+                    "intensities": intensities,
+                })
+            }
+            "sensor_msgs/msg/Image" => {
+                let (width, height) = (32, 24);
+                let data: Vec<u8> = (0..height)
+                    .flat_map(|row| (0..width).map(move |column| (row, column)))
+                    .flat_map(|(row, column)| {
+                        [
+                            ((column * 8 + sequence as usize * 4) % 256) as u8,
+                            (row * 10) as u8,
+                            128,
+                        ]
+                    })
+                    .collect();
+                json!({
+                    "header": header("camera"),
+                    "height": height,
+                    "width": width,
+                    "encoding": "rgb8",
+                    "is_bigendian": 0,
+                    "step": width * 3,
+                    "data": data,
+                })
+            }
+            "nav_msgs/msg/OccupancyGrid" => map(),
+            "diagnostic_msgs/msg/DiagnosticArray" => json!({
+                "header": header(""),
+                "status": diagnostics(topic, (time / STEP.as_secs_f64()) as u64)
+                    .iter()
+                    .map(|status| json!({
+                        "level": status.level as u8,
+                        "name": status.name,
+                        "message": status.message,
+                        "hardware_id": status.hardware_id,
+                        "values": status
+                            .values
+                            .iter()
+                            .map(|(key, value)| json!({ "key": key, "value": value }))
+                            .collect::<Vec<_>>(),
+                    }))
+                    .collect::<Vec<_>>(),
+            }),
+            _ => return None,
+        })
+    }
+}
+
+/// The robot drives a circle of 2 m around the centre of the room.
+fn robot_pose(time: f64) -> (f64, f64, f64) {
+    let angle = 0.2 * time;
+    (2.0 * angle.cos(), 2.0 * angle.sin(), angle + PI / 2.0)
+}
+
+fn twist(time: f64) -> Value {
+    json!({
+        "linear": { "x": 0.4, "y": 0.0, "z": 0.0 },
+        "angular": { "x": 0.0, "y": 0.0, "z": 0.2 + 0.05 * time.sin() },
+    })
+}
+
+fn quaternion(yaw: f64) -> Value {
+    json!({ "x": 0.0, "y": 0.0, "z": (yaw / 2.0).sin(), "w": (yaw / 2.0).cos() })
+}
+
+fn pose(x: f64, y: f64, yaw: f64) -> Value {
+    json!({
+        "position": { "x": x, "y": y, "z": 0.0 },
+        "orientation": quaternion(yaw),
+    })
+}
+
+/// One ray per degree from the robot to the walls of the room or the pillar.
+/// The pillar reflects more than the walls.
+fn scan(x: f64, y: f64, yaw: f64) -> (Vec<f64>, Vec<f64>) {
+    (0..360)
+        .map(|i| {
+            let angle = yaw - PI + (i as f64).to_radians();
+            let (dx, dy) = (angle.cos(), angle.sin());
+            let wall = |position: f64, direction: f64| match direction {
+                d if d > 1e-9 => (ROOM_HALF_SIZE - position) / d,
+                d if d < -1e-9 => (-ROOM_HALF_SIZE - position) / d,
+                _ => f64::INFINITY,
+            };
+            let wall = wall(x, dx).min(wall(y, dy));
+
+            // Nearest intersection of the ray with the pillar circle
+            let (cx, cy, radius) = PILLAR;
+            let (ox, oy) = (x - cx, y - cy);
+            let b = ox * dx + oy * dy;
+            let discriminant = b * b - (ox * ox + oy * oy - radius * radius);
+            let pillar = match discriminant >= 0.0 {
+                true => Some(-b - discriminant.sqrt()).filter(|t| *t > 0.0),
+                false => None,
+            };
+
+            match pillar {
+                Some(pillar) if pillar < wall => (pillar, 200.0),
+                _ => (wall, 100.0),
+            }
+        })
+        .unzip()
+}
+
+/// The room at 25 cm per cell: walls, the pillar, and a corner nobody mapped yet.
+fn map() -> Value {
+    let resolution = 0.25;
+    let size = (2.0 * ROOM_HALF_SIZE / resolution) as usize;
+    let data: Vec<i8> = (0..size * size)
+        .map(|i| {
+            let (column, row) = (i % size, i / size);
+            let x = -ROOM_HALF_SIZE + (column as f64 + 0.5) * resolution;
+            let y = -ROOM_HALF_SIZE + (row as f64 + 0.5) * resolution;
+            let (cx, cy, radius) = PILLAR;
+            let wall = column == 0 || row == 0 || column == size - 1 || row == size - 1;
+            if wall || (x - cx).hypot(y - cy) <= radius {
+                100
+            } else if x > 3.0 && y < -3.0 {
+                -1
+            } else {
+                0
+            }
+        })
+        .collect();
+    json!({
+        "header": { "stamp": { "sec": 0, "nanosec": 0 }, "frame_id": "map" },
+        "info": {
+            "map_load_time": { "sec": 0, "nanosec": 0 },
+            "resolution": resolution,
+            "width": size,
+            "height": size,
+            "origin": pose(-ROOM_HALF_SIZE, -ROOM_HALF_SIZE, 0.0),
+        },
+        "data": data,
+    })
+}
+
+/// The statuses published on a diagnostics topic at a given step. The motors
+/// heat up and cool down again, and the camera drops frames now and then.
+fn diagnostics(topic: &str, step: u64) -> Vec<DiagnosticStatus> {
+    let seconds = step / STEPS_PER_UPDATE;
+    let temperature = 45.0 + 20.0 * (seconds as f64 / 20.0).sin();
+    let motor_level = match temperature {
+        t if t > 62.0 => DiagnosticLevel::Error,
+        t if t > 55.0 => DiagnosticLevel::Warn,
+        _ => DiagnosticLevel::Ok,
+    };
+    let camera_level = match seconds % 30 {
+        20..=24 => DiagnosticLevel::Warn,
+        _ => DiagnosticLevel::Ok,
+    };
+    let battery = 100.0 - (seconds as f64 * 0.05) % 80.0;
+
+    let statuses = vec![
+        status(
+            motor_level,
+            "base_controller: Motors",
+            match motor_level {
+                DiagnosticLevel::Ok => "OK",
+                DiagnosticLevel::Warn => "Motor temperature high",
+                _ => "Motor overheating",
+            },
+            "motor_driver",
+            &[("temperature", format!("{:.1}", temperature))],
+        ),
+        status(
+            DiagnosticLevel::Ok,
+            "base_controller: Battery",
+            "OK",
+            "bms",
+            &[("percentage", format!("{:.1}", battery))],
+        ),
+        status(
+            DiagnosticLevel::Ok,
+            "lidar_driver: Scan",
+            "OK",
+            "lidar",
+            &[("frequency", "10.0".to_string())],
+        ),
+        status(
+            camera_level,
+            "camera_driver: Stream",
+            match camera_level {
+                DiagnosticLevel::Ok => "OK",
+                _ => "Dropping frames",
+            },
+            "camera",
+            &[("frame_rate", "15.0".to_string())],
+        ),
+    ];
+
+    // The aggregator sorts the raw statuses into a hierarchy
+    match topic {
+        "/diagnostics_agg" => statuses
+            .into_iter()
+            .map(|status| {
+                let (node, name) = status.name.split_once(": ").unwrap_or_default();
+                let group = match node {
+                    "base_controller" => "Base",
+                    _ => "Sensors",
+                };
+                DiagnosticStatus {
+                    name: format!("/Robot/{}/{}", group, name),
+                    ..status
+                }
+            })
+            .collect(),
+        _ => statuses,
+    }
+}
+
+fn status(
+    level: DiagnosticLevel,
+    name: &str,
+    message: &str,
+    hardware_id: &str,
+    values: &[(&str, String)],
+) -> DiagnosticStatus {
+    DiagnosticStatus {
+        level,
+        name: name.to_string(),
+        message: message.to_string(),
+        hardware_id: hardware_id.to_string(),
+        values: values
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lazyros_publishes(system: &DemoSystem, topic: &str) -> bool {
+        let graph = system.graph();
+        let node = graph.nodes.iter().find(|node| node.name == LAZYROS_NODE);
+        node.is_some_and(|node| node.publishes.iter().any(|name| name == topic))
+    }
+
+    #[test]
+    fn publishers_show_in_the_graph_and_reach_the_subscription() {
+        let mut system = DemoSystem::default();
+        let publish = RosCommand::Publish {
+            topic: "/chatter".to_string(),
+            message: json!({ "data": "hello" }),
+        };
+        assert_eq!(
+            system.execute(publish.clone()),
+            [ROSEvent::BackendError(
+                "no publisher for /chatter".to_string()
+            )]
+        );
+
+        system.execute(RosCommand::CreatePublisher {
+            topic: "/chatter".to_string(),
+            msg_type: "std_msgs/msg/String".to_string(),
+        });
+        assert!(lazyros_publishes(&system, "/chatter"));
+        // Nobody subscribes yet
+        assert_eq!(system.execute(publish.clone()), []);
+        system.subscriptions.insert("/chatter".to_string());
+        assert_eq!(
+            system.execute(publish.clone()),
+            [ROSEvent::TopicMessage(
+                "/chatter".to_string(),
+                json!({ "data": "hello" })
+            )]
+        );
+
+        system.execute(RosCommand::DestroyPublisher {
+            topic: "/chatter".to_string(),
+        });
+        assert!(!lazyros_publishes(&system, "/chatter"));
+        assert_eq!(system.execute(publish).len(), 1);
+    }
+
+    #[test]
+    fn clients_call_the_simulated_services() {
+        let mut system = DemoSystem::default();
+        let call = RosCommand::CallService {
+            service: "/add_two_ints".to_string(),
+            request: json!({ "a": 2, "b": 3 }),
+        };
+        assert_eq!(
+            system.execute(call.clone()),
+            [ROSEvent::BackendError(
+                "no client for /add_two_ints".to_string()
+            )]
+        );
+
+        system.execute(RosCommand::CreateClient {
+            service: "/add_two_ints".to_string(),
+            srv_type: "example_interfaces/srv/AddTwoInts".to_string(),
+        });
+        assert_eq!(
+            system.execute(call.clone()),
+            [ROSEvent::ServiceResponse(
+                "/add_two_ints".to_string(),
+                json!({ "sum": 5 })
+            )]
+        );
+
+        system.execute(RosCommand::DestroyClient {
+            service: "/add_two_ints".to_string(),
+        });
+        assert_eq!(system.execute(call).len(), 1);
+    }
+
+    #[test]
+    fn unknown_services_are_reported() {
+        let mut system = DemoSystem::default();
+        system.execute(RosCommand::CreateClient {
+            service: "/missing".to_string(),
+            srv_type: "std_srvs/srv/Trigger".to_string(),
+        });
+        let events = system.execute(RosCommand::CallService {
+            service: "/missing".to_string(),
+            request: json!({}),
+        });
+        assert_eq!(
+            events,
+            [ROSEvent::BackendError(
+                "/missing: service not available".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,380 @@
+use std::{cell::Cell, collections::VecDeque, io};
+
+use ratatui::{
+    layout::{Constraint, Layout, Margin},
+    prelude::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Widget, Wrap},
+    Frame,
+};
+
+use crate::{
+    config::{Action, KeyBindings, Theme, UiConfig},
+    filter::highlight,
+};
+
+/// Characters scrolled sideways per key press.
+const HORIZONTAL_STEP: usize = 8;
+
+/// The details pane: the newest lines of output, at most `max_lines` of them,
+/// with scrolling, follow mode, pause and search.
+#[derive(Debug)]
+pub struct DetailsView {
+    lines: VecDeque<String>,
+    /// Which of the lines are drawn in the changed colour, only for a text
+    /// shown with `set_marked_lines`.
+    marked: Vec<bool>,
+    max_lines: usize,
+    /// Lines that arrived while paused, added on resume.
+    pending: VecDeque<String>,
+    paused: bool,
+    /// Keep the newest line in view.
+    follow: bool,
+    wrap: bool,
+    /// First line in view while not following.
+    top: usize,
+    /// Characters scrolled to the right, only without wrapping.
+    left: usize,
+    search: SearchPrompt,
+    /// Size of the text area at the last render, for paging and clamping.
+    viewport: Cell<(usize, usize)>,
+    /// First line in view at the last render, where scrolling starts while following.
+    rendered_top: Cell<usize>,
+}
+
+/// The `/` prompt of the details pane. Matches are case-insensitive unless the
+/// query has an uppercase letter.
+#[derive(Debug, Default)]
+struct SearchPrompt {
+    query: String,
+    editing: bool,
+}
+
+impl Default for DetailsView {
+    fn default() -> Self {
+        DetailsView::new(UiConfig::default().details_max_lines)
+    }
+}
+
+impl DetailsView {
+    pub fn new(max_lines: usize) -> DetailsView {
+        DetailsView {
+            lines: VecDeque::new(),
+            marked: vec![],
+            max_lines,
+            pending: VecDeque::new(),
+            paused: false,
+            follow: true,
+            wrap: false,
+            top: 0,
+            left: 0,
+            search: SearchPrompt::default(),
+            viewport: Cell::new((0, 0)),
+            rendered_top: Cell::new(0),
+        }
+    }
+
+    /// Add every line of `text`, dropping the oldest lines beyond the cap.
+    pub fn push(&mut self, text: &str) {
+        self.marked.clear();
+        for line in text.lines() {
+            if self.paused {
+                self.pending.push_back(line.to_string());
+                if self.pending.len() > self.max_lines {
+                    self.pending.pop_front();
+                }
+            } else {
+                self.append(line.to_string());
+            }
+        }
+    }
+
+    /// Show `text` instead of the current lines. Unlike a log it does not
+    /// follow, the view stays where it was.
+    pub fn set_text(&mut self, text: &str) {
+        self.set_marked_lines(text.lines().map(|line| (line.to_string(), false)));
+    }
+
+    /// Like `set_text`, drawing the lines marked true in the changed colour.
+    /// Only the first `max_lines` lines are kept.
+    pub fn set_marked_lines(&mut self, lines: impl IntoIterator<Item = (String, bool)>) {
+        (self.lines, self.marked) = lines.into_iter().take(self.max_lines).unzip();
+        self.pending.clear();
+        self.follow = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Every line kept, the ones held back by a pause aside.
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| format!("{}\n", line))
+            .collect()
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.follow = false;
+        self.top = 0;
+        self.left = 0;
+    }
+
+    fn append(&mut self, line: String) {
+        self.lines.push_back(line);
+        if self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+            // Keep the same lines in view
+            self.top = self.top.saturating_sub(1);
+        }
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.search.editing
+    }
+
+    pub fn start_search(&mut self) {
+        self.search.editing = true;
+    }
+
+    pub fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> io::Result<()> {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        if self.search.editing {
+            match key_event.code {
+                KeyCode::Esc => {
+                    self.search.query.clear();
+                    self.search.editing = false;
+                }
+                KeyCode::Enter => {
+                    self.search.editing = false;
+                    self.jump_to_match(self.current_top(), true);
+                }
+                KeyCode::Backspace => {
+                    self.search.query.pop();
+                }
+                KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.search.query.push(c)
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        let pressed = |action| keys.matches(action, &key_event);
+        let page = self.viewport.get().1.max(1) as isize;
+        if pressed(Action::Filter) {
+            self.start_search();
+        } else if pressed(Action::Up) {
+            self.scroll_by(-1);
+        } else if pressed(Action::Down) {
+            self.scroll_by(1);
+        } else if pressed(Action::PageUp) {
+            self.scroll_by(-page);
+        } else if pressed(Action::PageDown) {
+            self.scroll_by(page);
+        } else if pressed(Action::ScrollTop) {
+            self.follow = false;
+            self.top = 0;
+        } else if pressed(Action::ScrollBottom) {
+            self.follow = true;
+        } else if pressed(Action::Left) {
+            self.left = self.left.saturating_sub(HORIZONTAL_STEP);
+        } else if pressed(Action::Right) && !self.wrap {
+            self.left += HORIZONTAL_STEP;
+        } else if pressed(Action::ToggleWrap) {
+            self.wrap = !self.wrap;
+            self.left = 0;
+        } else if pressed(Action::ToggleFollow) {
+            self.top = self.current_top();
+            self.follow = !self.follow;
+        } else if pressed(Action::TogglePause) {
+            self.paused = !self.paused;
+            if !self.paused {
+                for line in std::mem::take(&mut self.pending) {
+                    self.append(line);
+                }
+            }
+        } else if pressed(Action::NextMatch) {
+            self.jump_to_match(self.current_top() + 1, true);
+        } else if pressed(Action::PreviousMatch) {
+            self.jump_to_match(self.current_top().wrapping_sub(1), false);
+        }
+        Ok(())
+    }
+
+    /// The first line in view, also while following.
+    fn current_top(&self) -> usize {
+        match self.follow {
+            true => self.rendered_top.get(),
+            false => self.top,
+        }
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let top = self.current_top().saturating_add_signed(delta);
+        self.follow = false;
+        self.top = top.min(self.bottom_top());
+    }
+
+    /// Scroll to `fraction` of the way down, where the scrollbar was dragged to.
+    pub fn scroll_to_fraction(&mut self, fraction: f64) {
+        // Without a scrollbar everything is in view already
+        let bottom = self.bottom_top();
+        if bottom > 0 {
+            self.follow = false;
+            self.top = (fraction * bottom as f64).round() as usize;
+        }
+    }
+
+    /// The first line in view when the newest line is at the bottom.
+    fn bottom_top(&self) -> usize {
+        let (width, height) = self.viewport.get();
+        let mut rows = 0;
+        for (i, line) in self.lines.iter().enumerate().rev() {
+            rows += match self.wrap {
+                true => line.chars().count().div_ceil(width.max(1)).max(1),
+                false => 1,
+            };
+            if rows > height {
+                return i + 1;
+            }
+        }
+        0
+    }
+
+    /// Scroll to the nearest matching line from `start` on, searching forward or
+    /// backward and wrapping around at the ends.
+    fn jump_to_match(&mut self, start: usize, forward: bool) {
+        let count = self.lines.len();
+        if self.search.query.is_empty() || count == 0 {
+            return;
+        }
+        let start = start.min(count - 1);
+        let found = (0..count)
+            .map(|offset| match forward {
+                true => (start + offset) % count,
+                false => (start + count - offset) % count,
+            })
+            .find(|&i| !match_indices(&self.lines[i], &self.search.query).is_empty());
+        if let Some(i) = found {
+            self.follow = false;
+            self.top = i;
+        }
+    }
+
+    pub fn render(&self, block: Block, area: Rect, frame: &mut Frame, theme: &Theme) {
+        let mut status = vec![];
+        if self.paused {
+            status.push(format!("paused, {} new", self.pending.len()));
+        } else if self.follow {
+            status.push("following".to_string());
+        }
+        if self.wrap {
+            status.push("wrap".to_string());
+        }
+        let mut block = block;
+        if !status.is_empty() {
+            block = block.title(Line::from(format!(" {} ", status.join(", "))).right_aligned());
+        }
+        if self.search.editing || !self.search.query.is_empty() {
+            let cursor = if self.search.editing { "█" } else { "" };
+            let count = self
+                .lines
+                .iter()
+                .filter(|line| !match_indices(line, &self.search.query).is_empty())
+                .count();
+            block = block.title_bottom(format!(
+                " /{}{} ({} lines) ",
+                self.search.query, cursor, count
+            ));
+        }
+        frame.render_widget(block, area);
+
+        let inner_area = area.inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+        let [text_area, scrollbar_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(1)]).areas(inner_area);
+        let (width, height) = (text_area.width as usize, text_area.height as usize);
+        self.viewport.set((width, height));
+
+        let top = match self.follow {
+            true => self.bottom_top(),
+            false => self.top.min(self.lines.len().saturating_sub(1)),
+        };
+        self.rendered_top.set(top);
+
+        let text_style = Style::default().fg(theme.text);
+        let changed_style = Style::default().fg(theme.changed).bold();
+        let matched = Style::default().fg(theme.search_match).bold();
+        let lines: Vec<Line> = self
+            .lines
+            .iter()
+            .enumerate()
+            .skip(top)
+            .take(height)
+            .map(|(i, line)| {
+                let style = match self.marked.get(i) {
+                    Some(true) => changed_style,
+                    _ => text_style,
+                };
+                let visible: String = match self.wrap {
+                    true => line.clone(),
+                    false => line.chars().skip(self.left).take(width).collect(),
+                };
+                let indices = match_indices(&visible, &self.search.query);
+                highlight(&visible, &indices, style, matched)
+            })
+            .collect();
+        let mut paragraph = Paragraph::new(lines);
+        if self.wrap {
+            paragraph = paragraph.wrap(Wrap { trim: false });
+        }
+        paragraph.render(text_area, frame.buffer_mut());
+
+        if self.lines.len() > height {
+            let mut scrollbar_state = ScrollbarState::new(self.bottom_top() + 1).position(top);
+            frame.render_stateful_widget(
+                Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                    .begin_symbol(None)
+                    .end_symbol(None),
+                scrollbar_area,
+                &mut scrollbar_state,
+            );
+        }
+    }
+}
+
+/// Character positions of every occurrence of `query` in `text`.
+fn match_indices(text: &str, query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return vec![];
+    }
+    let ignore_case = !query.chars().any(char::is_uppercase);
+    let fold = |c: char| match ignore_case {
+        true => c.to_lowercase().next().unwrap_or(c),
+        false => c,
+    };
+    let text: Vec<char> = text.chars().map(fold).collect();
+    let query: Vec<char> = query.chars().map(fold).collect();
+
+    let mut indices = vec![];
+    let mut i = 0;
+    while i + query.len() <= text.len() {
+        if text[i..i + query.len()] == query[..] {
+            indices.extend(i..i + query.len());
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+    indices
+}
@@ -0,0 +1,492 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io,
+    time::{Duration, Instant},
+};
+
+use crate::config::{Action, KeyBindings, Theme};
+
+use ratatui::{
+    layout::{Constraint, Layout, Margin},
+    prelude::Rect,
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, BorderType, Paragraph, Row, Table, TableState, Widget, Wrap},
+    Frame,
+};
+
+pub const DIAGNOSTICS_TOPICS: [&str; 2] = ["/diagnostics", "/diagnostics_agg"];
+
+const STALE_TIMEOUT_STEP: Duration = Duration::from_secs(1);
+
+/// Level of a `diagnostic_msgs/DiagnosticStatus`, ordered from best to worst.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiagnosticLevel {
+    #[default]
+    Ok,
+    Warn,
+    Error,
+    Stale,
+}
+
+impl DiagnosticLevel {
+    pub fn from_byte(level: u8) -> DiagnosticLevel {
+        match level {
+            0 => DiagnosticLevel::Ok,
+            1 => DiagnosticLevel::Warn,
+            2 => DiagnosticLevel::Error,
+            _ => DiagnosticLevel::Stale,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            DiagnosticLevel::Ok => "OK",
+            DiagnosticLevel::Warn => "WARN",
+            DiagnosticLevel::Error => "ERROR",
+            DiagnosticLevel::Stale => "STALE",
+        }
+    }
+
+    fn color(&self, theme: &Theme) -> Color {
+        match self {
+            DiagnosticLevel::Ok => theme.ok,
+            DiagnosticLevel::Warn => theme.warn,
+            DiagnosticLevel::Error => theme.error,
+            DiagnosticLevel::Stale => theme.stale,
+        }
+    }
+}
+
+/// One entry of a `diagnostic_msgs/DiagnosticArray`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DiagnosticStatus {
+    pub level: DiagnosticLevel,
+    pub name: String,
+    pub message: String,
+    pub hardware_id: String,
+    pub values: Vec<(String, String)>,
+}
+
+impl DiagnosticStatus {
+    /// Position of the status in the tree. The aggregator publishes names like
+    /// `/Robot/Motors/Left`, raw statuses are grouped by their hardware id.
+    fn path(&self) -> Vec<String> {
+        if self.name.starts_with('/') {
+            return self
+                .name
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+                .collect();
+        }
+        let group = match self.hardware_id.is_empty() {
+            true => "(no hardware id)".to_string(),
+            false => self.hardware_id.clone(),
+        };
+        vec![group, self.name.clone()]
+    }
+}
+
+#[derive(Debug)]
+struct DiagnosticEntry {
+    status: DiagnosticStatus,
+    topic: String,
+    last_update: Instant,
+}
+
+/// A visible line of the diagnostics tree.
+struct TreeRow<'a> {
+    path: Vec<String>,
+    entry: Option<&'a DiagnosticEntry>,
+    level: DiagnosticLevel,
+    has_children: bool,
+}
+
+/// Hierarchical table of the statuses published on the diagnostics topics.
+#[derive(Debug)]
+pub struct DiagnosticsView {
+    entries: BTreeMap<Vec<String>, DiagnosticEntry>,
+    collapsed: HashSet<Vec<String>>,
+    selected: Option<Vec<String>>,
+    stale_timeout: Duration,
+}
+
+impl DiagnosticsView {
+    /// A dashboard where statuses go stale after `stale_timeout` without an update.
+    pub fn new(stale_timeout: Duration) -> DiagnosticsView {
+        DiagnosticsView {
+            entries: BTreeMap::new(),
+            collapsed: HashSet::new(),
+            selected: None,
+            stale_timeout,
+        }
+    }
+
+    pub fn update(&mut self, topic: &str, statuses: Vec<DiagnosticStatus>) {
+        let now = Instant::now();
+        for status in statuses {
+            self.entries.insert(
+                status.path(),
+                DiagnosticEntry {
+                    status,
+                    topic: topic.to_string(),
+                    last_update: now,
+                },
+            );
+        }
+    }
+
+    pub fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> io::Result<()> {
+        use crossterm::event::KeyEventKind;
+
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+
+        if keys.matches(Action::Down, &key_event) {
+            self.move_selection(1);
+        } else if keys.matches(Action::Up, &key_event) {
+            self.move_selection(-1);
+        } else if keys.matches(Action::ToggleFold, &key_event) {
+            self.toggle_selected();
+        } else if keys.matches(Action::IncreaseStaleTimeout, &key_event) {
+            self.stale_timeout += STALE_TIMEOUT_STEP;
+        } else if keys.matches(Action::DecreaseStaleTimeout, &key_event) {
+            self.stale_timeout = self
+                .stale_timeout
+                .saturating_sub(STALE_TIMEOUT_STEP)
+                .max(STALE_TIMEOUT_STEP);
+        }
+
+        Ok(())
+    }
+
+    fn move_selection(&mut self, step: isize) {
+        let rows = self.rows(Instant::now());
+        if rows.is_empty() {
+            return;
+        }
+        let current = self
+            .selected
+            .as_ref()
+            .and_then(|selected| rows.iter().position(|row| &row.path == selected));
+        let next = match current {
+            Some(i) => (i as isize + step).rem_euclid(rows.len() as isize) as usize,
+            None => 0,
+        };
+        self.selected = Some(rows[next].path.clone());
+    }
+
+    fn toggle_selected(&mut self) {
+        if let Some(selected) = &self.selected {
+            if !self.collapsed.remove(selected) {
+                self.collapsed.insert(selected.clone());
+            }
+        }
+    }
+
+    fn level(&self, entry: &DiagnosticEntry, now: Instant) -> DiagnosticLevel {
+        match now.duration_since(entry.last_update) > self.stale_timeout {
+            true => DiagnosticLevel::Stale,
+            false => entry.status.level,
+        }
+    }
+
+    /// Flatten the tree into the rows that are not hidden by a collapsed parent.
+    fn rows(&self, now: Instant) -> Vec<TreeRow<'_>> {
+        // Every prefix of every path is a row, with the worst level found below it
+        let mut tree: BTreeMap<Vec<String>, (DiagnosticLevel, bool)> = BTreeMap::new();
+        for (path, entry) in &self.entries {
+            let level = self.level(entry, now);
+            for depth in 1..=path.len() {
+                let node = tree.entry(path[..depth].to_vec()).or_default();
+                node.0 = node.0.max(level);
+                node.1 |= depth < path.len();
+            }
+        }
+
+        tree.into_iter()
+            .filter(|(path, _)| {
+                (1..path.len()).all(|depth| !self.collapsed.contains(&path[..depth]))
+            })
+            .map(|(path, (level, has_children))| TreeRow {
+                entry: self.entries.get(&path),
+                path,
+                level,
+                has_children,
+            })
+            .collect()
+    }
+
+    pub fn render(&self, area: Rect, frame: &mut Frame, theme: &Theme, keys: &KeyBindings) {
+        let now = Instant::now();
+        let rows = self.rows(now);
+
+        let layout = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]);
+        let [tree_area, details_area] = layout.areas(area);
+
+        let title = format!(
+            " Diagnostics (stale after {}s <{}/{}>) ",
+            self.stale_timeout.as_secs(),
+            keys.keys(Action::IncreaseStaleTimeout),
+            keys.keys(Action::DecreaseStaleTimeout),
+        );
+        let block = Block::bordered()
+            .title(Line::from(title))
+            .style(theme.focused_border)
+            .border_type(BorderType::Rounded);
+        frame.render_widget(block, tree_area);
+        let inner_area = tree_area.inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+
+        if rows.is_empty() {
+            Paragraph::new(format!(
+                "Waiting for messages on {}",
+                DIAGNOSTICS_TOPICS.join(" and ")
+            ))
+            .style(Style::default().fg(theme.text))
+            .centered()
+            .render(inner_area, frame.buffer_mut());
+        } else {
+            let header =
+                Row::new(vec!["Level", "Name", "Message"]).style(Style::default().fg(theme.header));
+            let table_rows: Vec<Row> = rows
+                .iter()
+                .map(|row| self.table_row(row, now, theme))
+                .collect();
+            let table = Table::new(
+                table_rows,
+                [
+                    Constraint::Length(6),
+                    Constraint::Percentage(50),
+                    Constraint::Percentage(50),
+                ],
+            )
+            .header(header)
+            .row_highlight_style(Style::default().bold().reversed());
+
+            let selected = self
+                .selected
+                .as_ref()
+                .and_then(|selected| rows.iter().position(|row| &row.path == selected));
+            let mut state = TableState::default().with_selected(selected);
+            frame.render_stateful_widget(table, inner_area, &mut state);
+        }
+
+        let selected_entry = self
+            .selected
+            .as_ref()
+            .and_then(|selected| self.entries.get(selected));
+        self.render_entry_details(selected_entry, now, details_area, frame, theme, keys);
+    }
+
+    fn table_row(&self, row: &TreeRow, now: Instant, theme: &Theme) -> Row<'static> {
+        let depth = row.path.len() - 1;
+        let marker = match (row.has_children, self.collapsed.contains(&row.path)) {
+            (false, _) => "  ",
+            (true, true) => "▸ ",
+            (true, false) => "▾ ",
+        };
+        let mut name = format!("{}{}{}", "  ".repeat(depth), marker, row.path[depth]);
+        let message = row
+            .entry
+            .map(|entry| entry.status.message.clone())
+            .unwrap_or_default();
+        if let Some(entry) = row.entry {
+            if self.level(entry, now) == DiagnosticLevel::Stale {
+                name += &format!(
+                    " (stale {}s)",
+                    now.duration_since(entry.last_update).as_secs()
+                );
+            }
+        }
+
+        Row::new(vec![
+            Line::from(row.level.label()).style(Style::default().fg(row.level.color(theme)).bold()),
+            Line::from(name),
+            Line::from(message),
+        ])
+        .style(Style::default().fg(theme.text))
+    }
+
+    fn render_entry_details(
+        &self,
+        entry: Option<&DiagnosticEntry>,
+        now: Instant,
+        area: Rect,
+        frame: &mut Frame,
+        theme: &Theme,
+        keys: &KeyBindings,
+    ) {
+        let block = Block::bordered()
+            .title(Line::from(" Status "))
+            .style(theme.border)
+            .border_type(BorderType::Rounded);
+
+        let Some(entry) = entry else {
+            let hint = format!(
+                "Select a status with <{}> <{}>, fold with <{}>",
+                keys.keys(Action::Down),
+                keys.keys(Action::Up),
+                keys.keys(Action::ToggleFold)
+            );
+            Paragraph::new(hint)
+                .block(block)
+                .wrap(Wrap { trim: true })
+                .render(area, frame.buffer_mut());
+            return;
+        };
+
+        let level = self.level(entry, now);
+        let mut lines = vec![
+            Line::from(vec![
+                "Name: ".fg(theme.header),
+                entry.status.name.clone().into(),
+            ]),
+            Line::from(vec![
+                "Level: ".fg(theme.header),
+                level.label().fg(level.color(theme)).bold(),
+            ]),
+            Line::from(vec![
+                "Message: ".fg(theme.header),
+                entry.status.message.clone().into(),
+            ]),
+            Line::from(vec![
+                "Hardware ID: ".fg(theme.header),
+                entry.status.hardware_id.clone().into(),
+            ]),
+            Line::from(vec!["Topic: ".fg(theme.header), entry.topic.clone().into()]),
+            Line::from(vec![
+                "Last update: ".fg(theme.header),
+                format!("{}s ago", now.duration_since(entry.last_update).as_secs()).into(),
+            ]),
+            Line::from(""),
+        ];
+        lines.extend(entry.status.values.iter().map(|(key, value)| {
+            Line::from(vec![format!("{}: ", key).cyan(), value.clone().into()])
+        }));
+
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(area, frame.buffer_mut());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(level: DiagnosticLevel, name: &str, hardware_id: &str) -> DiagnosticStatus {
+        DiagnosticStatus {
+            level,
+            name: name.to_string(),
+            hardware_id: hardware_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn paths(rows: &[TreeRow]) -> Vec<String> {
+        rows.iter().map(|row| row.path.join("/")).collect()
+    }
+
+    #[test]
+    fn statuses_are_grouped_by_their_path_or_hardware_id() {
+        let path = |name, hardware_id| status(DiagnosticLevel::Ok, name, hardware_id).path();
+        assert_eq!(
+            path("/Robot/Motors/Left", "driver"),
+            ["Robot", "Motors", "Left"]
+        );
+        assert_eq!(path("Motors", "driver"), ["driver", "Motors"]);
+        assert_eq!(path("Motors", ""), ["(no hardware id)", "Motors"]);
+    }
+
+    #[test]
+    fn groups_take_the_worst_level_below_them() {
+        let mut view = DiagnosticsView::new(Duration::from_secs(5));
+        view.update(
+            "/diagnostics_agg",
+            vec![
+                status(DiagnosticLevel::Ok, "/Robot/Motors/Left", ""),
+                status(DiagnosticLevel::Warn, "/Robot/Motors/Right", ""),
+                status(DiagnosticLevel::Ok, "/Robot/Battery", ""),
+            ],
+        );
+        let now = Instant::now();
+        let levels: Vec<(String, DiagnosticLevel)> = view
+            .rows(now)
+            .iter()
+            .map(|row| (row.path.join("/"), row.level))
+            .collect();
+        assert_eq!(
+            levels,
+            [
+                ("Robot".to_string(), DiagnosticLevel::Warn),
+                ("Robot/Battery".to_string(), DiagnosticLevel::Ok),
+                ("Robot/Motors".to_string(), DiagnosticLevel::Warn),
+                ("Robot/Motors/Left".to_string(), DiagnosticLevel::Ok),
+                ("Robot/Motors/Right".to_string(), DiagnosticLevel::Warn),
+            ]
+        );
+
+        // Statuses without updates go stale, which is worse than an error
+        view.update(
+            "/diagnostics_agg",
+            vec![status(DiagnosticLevel::Error, "/Robot/Battery", "")],
+        );
+        let later = now + Duration::from_secs(6);
+        view.entries
+            .get_mut(&vec!["Robot".to_string(), "Battery".to_string()])
+            .unwrap()
+            .last_update = later;
+        let rows = view.rows(later);
+        assert_eq!(rows[0].level, DiagnosticLevel::Stale);
+        assert_eq!(rows[1].level, DiagnosticLevel::Error);
+        assert_eq!(rows[2].level, DiagnosticLevel::Stale);
+    }
+
+    #[test]
+    fn folded_groups_hide_the_rows_below_them() {
+        let mut view = DiagnosticsView::new(Duration::from_secs(5));
+        view.update(
+            "/diagnostics",
+            vec![
+                status(DiagnosticLevel::Ok, "Motors", "driver"),
+                status(DiagnosticLevel::Ok, "Temperature", "driver"),
+                status(DiagnosticLevel::Ok, "Battery", "bms"),
+            ],
+        );
+        let now = Instant::now();
+        assert_eq!(
+            paths(&view.rows(now)),
+            [
+                "bms",
+                "bms/Battery",
+                "driver",
+                "driver/Motors",
+                "driver/Temperature"
+            ]
+        );
+
+        view.move_selection(1);
+        view.move_selection(1);
+        view.move_selection(1);
+        assert_eq!(view.selected, Some(vec!["driver".to_string()]));
+        view.toggle_selected();
+        assert_eq!(paths(&view.rows(now)), ["bms", "bms/Battery", "driver"]);
+        // The selection stays on the folded group
+        view.move_selection(-1);
+        view.move_selection(1);
+        assert_eq!(view.selected, Some(vec!["driver".to_string()]));
+
+        view.toggle_selected();
+        assert_eq!(view.rows(now).len(), 5);
+    }
+}
@@ -0,0 +1,163 @@
+use serde_json::Value;
+
+use crate::details::DetailsView;
+
+/// The newest message of an echo as a field tree, with the leaves whose value
+/// changed since the previous message marked.
+#[derive(Debug)]
+pub struct DiffView {
+    pub view: DetailsView,
+    /// Leave out the fields that did not change.
+    pub only_changes: bool,
+    /// The tree is older than the newest message or the mode, `update` builds
+    /// it again.
+    pub outdated: bool,
+}
+
+impl DiffView {
+    pub fn new(max_lines: usize, only_changes: bool) -> DiffView {
+        DiffView {
+            view: DetailsView::new(max_lines),
+            only_changes,
+            outdated: true,
+        }
+    }
+
+    /// Show `newest` compared to `previous`, unless the view is paused.
+    pub fn update(&mut self, newest: &Value, previous: Option<&Value>) {
+        if self.view.is_paused() {
+            return;
+        }
+        self.outdated = false;
+        let mut lines = field_tree(newest, previous, self.only_changes);
+        if lines.is_empty() {
+            lines.push((
+                "No field changed since the previous message".to_string(),
+                false,
+            ));
+        }
+        self.view.set_marked_lines(lines);
+    }
+}
+
+/// One line per field of `message`, indented by depth, e.g. `  x: 1.5`. Leaves
+/// are marked when `previous` is given and has another value there or none.
+/// With `only_changes` only the marked leaves and their parents are listed.
+pub fn field_tree(
+    message: &Value,
+    previous: Option<&Value>,
+    only_changes: bool,
+) -> Vec<(String, bool)> {
+    let mut lines = vec![];
+    match message {
+        Value::Object(_) | Value::Array(_) => {
+            for (label, value) in children(message) {
+                let old = previous.map(|previous| child(previous, &label));
+                write_field(&mut lines, &label, value, old, 0, only_changes);
+            }
+        }
+        leaf => {
+            let changed = previous.is_some_and(|previous| previous != leaf);
+            if changed || !only_changes {
+                lines.push((leaf.to_string(), changed));
+            }
+        }
+    }
+    lines
+}
+
+/// `old` is `None` without a previous message and `Some(None)` when the
+/// previous message did not have the field.
+fn write_field(
+    lines: &mut Vec<(String, bool)>,
+    label: &str,
+    value: &Value,
+    old: Option<Option<&Value>>,
+    depth: usize,
+    only_changes: bool,
+) {
+    let indent = "  ".repeat(depth);
+    let is_container = match value {
+        Value::Object(object) => !object.is_empty(),
+        Value::Array(array) => !array.is_empty(),
+        _ => false,
+    };
+    if !is_container {
+        let changed = old.is_some_and(|old| old != Some(value));
+        if changed || !only_changes {
+            lines.push((format!("{}{}: {}", indent, label, value), changed));
+        }
+        return;
+    }
+
+    let start = lines.len();
+    for (child_label, child_value) in children(value) {
+        let child_old = old.map(|old| old.and_then(|old| child(old, &child_label)));
+        write_field(
+            lines,
+            &child_label,
+            child_value,
+            child_old,
+            depth + 1,
+            only_changes,
+        );
+    }
+    // A parent is only listed for the changes below it
+    if lines.len() > start || !only_changes {
+        lines.insert(start, (format!("{}{}", indent, label), false));
+    }
+}
+
+/// The fields of an object by name, or the items of an array as `[i]`.
+fn children(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Object(object) => object.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("[{}]", i), v))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// The field of `value` called `label` by `children`.
+fn child<'a>(value: &'a Value, label: &str) -> Option<&'a Value> {
+    match label.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+        Some(index) if value.is_array() => value.get(index.parse::<usize>().ok()?),
+        _ => value.get(label),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_tree_lists_array_items_and_missing_fields_as_changed() {
+        let previous = serde_json::json!({ "ranges": [1, 2], "name": "a" });
+        let message = serde_json::json!({ "ranges": [1, 3, 4], "name": "a", "new": true });
+        assert_eq!(
+            field_tree(&message, Some(&previous), true),
+            [
+                ("new: true".to_string(), true),
+                ("ranges".to_string(), false),
+                ("  [1]: 3".to_string(), true),
+                ("  [2]: 4".to_string(), true),
+            ]
+        );
+        assert!(field_tree(&message, None, false)
+            .iter()
+            .all(|(_, changed)| !changed));
+    }
+
+    #[test]
+    fn updates_keep_at_most_max_lines() {
+        let message = serde_json::json!({ "a": 1, "b": 2, "c": 3 });
+        let mut diff = DiffView::new(2, false);
+        assert!(diff.outdated);
+        diff.update(&message, None);
+        assert!(!diff.outdated);
+        assert_eq!(diff.view.text(), "a: 1\nb: 2\n");
+    }
+}
@@ -0,0 +1,119 @@
+use std::{io, time::Duration};
+
+use serde::Serialize;
+
+use crate::graph::{ActionInfo, EndpointInfo, GraphSnapshot, NodeInfo, ServiceInfo};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum DumpFormat {
+    Json,
+    Yaml,
+}
+
+/// The document printed by `lazyros dump`.
+#[derive(Serialize)]
+struct GraphDump<'a> {
+    nodes: &'a [NodeInfo],
+    topics: Vec<TopicDump<'a>>,
+    services: &'a [ServiceInfo],
+    actions: &'a [ActionInfo],
+}
+
+#[derive(Serialize)]
+struct TopicDump<'a> {
+    name: &'a str,
+    types: &'a [String],
+    publisher_count: usize,
+    subscriber_count: usize,
+    publishers: &'a [EndpointInfo],
+    subscribers: &'a [EndpointInfo],
+}
+
+/// Serialize the whole graph in the requested format.
+pub fn format_graph(graph: &GraphSnapshot, format: DumpFormat) -> io::Result<String> {
+    let dump = GraphDump {
+        nodes: &graph.nodes,
+        topics: graph
+            .topics
+            .iter()
+            .map(|topic| TopicDump {
+                name: &topic.name,
+                types: &topic.msg_types,
+                publisher_count: topic.publishers.len(),
+                subscriber_count: topic.subscribers.len(),
+                publishers: &topic.publishers,
+                subscribers: &topic.subscribers,
+            })
+            .collect(),
+        services: &graph.services,
+        actions: &graph.actions,
+    };
+
+    match format {
+        DumpFormat::Json => serde_json::to_string_pretty(&dump).map_err(io::Error::other),
+        DumpFormat::Yaml => serde_yaml::to_string(&dump).map_err(io::Error::other),
+    }
+}
+
+/// Parse the `--settle-time` seconds, which must be a finite, non-negative number.
+pub fn parse_settle_time(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value.trim().parse().map_err(|e| format!("{}", e))?;
+    Duration::try_from_secs_f64(seconds).map_err(|_| {
+        format!(
+            "expected a finite, non-negative number of seconds, got {}",
+            value
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo::DemoSystem;
+
+    #[test]
+    fn demo_graph_dumps_as_in_the_golden_files() {
+        let graph = DemoSystem::default().graph();
+        let json = format_graph(&graph, DumpFormat::Json).unwrap();
+        assert_eq!(
+            json.trim_end(),
+            include_str!("../tests/golden/demo_dump.json").trim_end()
+        );
+        let yaml = format_graph(&graph, DumpFormat::Yaml).unwrap();
+        assert_eq!(
+            yaml.trim_end(),
+            include_str!("../tests/golden/demo_dump.yaml").trim_end()
+        );
+    }
+
+    #[test]
+    fn dumps_count_the_endpoints_of_every_topic() {
+        let graph = DemoSystem::default().graph();
+        let json = format_graph(&graph, DumpFormat::Json).unwrap();
+        let dump: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let topics = dump["topics"].as_array().unwrap();
+        assert_eq!(topics.len(), graph.topics.len());
+        for (topic, info) in topics.iter().zip(&graph.topics) {
+            assert_eq!(topic["name"], info.name.as_str());
+            assert_eq!(topic["publisher_count"], info.publishers.len());
+            assert_eq!(topic["subscriber_count"], info.subscribers.len());
+        }
+    }
+
+    #[test]
+    fn settle_time_is_a_finite_non_negative_number_of_seconds() {
+        assert_eq!(parse_settle_time("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_settle_time(" 0.5 "), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_settle_time("0"), Ok(Duration::ZERO));
+        for value in ["-1", "NaN", "inf", "1e300"] {
+            assert!(
+                parse_settle_time(value)
+                    .unwrap_err()
+                    .contains("non-negative"),
+                "{}",
+                value
+            );
+        }
+        assert!(parse_settle_time("soon").is_err());
+    }
+}
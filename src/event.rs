@@ -1,11 +1,40 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use crate::{diagnostics::DiagnosticStatus, graph::GraphSnapshot};
+
 pub enum Event {
     Input(crossterm::event::KeyEvent),
+    Mouse(crossterm::event::MouseEvent),
     Resize(u16, u16),
-    ROSEvent { event: ROSEvent },
+    ROSEvent {
+        event: ROSEvent,
+    },
+    /// Reading the terminal failed, the input thread stops after sending it.
+    InputError(std::io::Error),
 }
 
+#[derive(Debug, PartialEq)]
 pub enum ROSEvent {
-    SubscriptionMessage(String),
-    NewNode(String),
-    NewTopic(String, String),
+    GraphUpdate(GraphSnapshot),
+    Diagnostics(String, Vec<DiagnosticStatus>),
+    /// The topics asked for with `RosCommand::MeasureRates` that were measured
+    /// this time, which on large graphs are only some of them.
+    TopicRates(HashMap<String, TopicRate>),
+    /// A message of a topic subscribed to with `RosCommand::Subscribe`.
+    TopicMessage(String, serde_json::Value),
+    ServiceResponse(String, serde_json::Value),
+    /// A command or a subscription failed.
+    BackendError(String),
+    /// The backend failed or panicked and is gone until it is restarted.
+    BackendStopped(String),
+}
+
+/// What the rate monitor saw of a topic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopicRate {
+    /// Messages per second.
+    pub hz: f64,
+    /// The `header.stamp` of the newest message, or when it was received for
+    /// messages without a header. `None` while nothing came in.
+    pub newest: Option<SystemTime>,
 }
@@ -0,0 +1,325 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+    io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::Value;
+
+use crate::latency;
+
+/// A message of an echo together with the time lazyros received it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedMessage {
+    pub received: SystemTime,
+    pub message: Value,
+}
+
+/// How many of the buffered messages an export writes, switched with tab.
+const COUNTS: [Option<usize>; 4] = [Some(1), Some(10), Some(100), None];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Yaml,
+    /// One row per message, one column per leaf field.
+    Csv,
+}
+
+impl ExportFormat {
+    const ALL: [ExportFormat; 3] = [ExportFormat::Json, ExportFormat::Yaml, ExportFormat::Csv];
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    /// The format a file name asks for with its extension.
+    pub fn from_path(path: &str) -> Option<ExportFormat> {
+        match Path::new(path).extension()?.to_str()? {
+            "json" => Some(ExportFormat::Json),
+            "yaml" | "yml" => Some(ExportFormat::Yaml),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// The prompt for the file an echo is saved to.
+#[derive(Debug)]
+pub struct ExportPrompt {
+    pub path: String,
+    count: usize,
+}
+
+/// What a key did to the export prompt.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PromptKey {
+    Edited,
+    Cancelled,
+    Confirmed,
+}
+
+impl ExportPrompt {
+    /// A prompt for the newest message of `topic`, saved as JSON next to
+    /// where lazyros was started.
+    pub fn new(topic: &str, now: SystemTime) -> ExportPrompt {
+        ExportPrompt {
+            path: default_file_name(topic, now, ExportFormat::Json),
+            count: 0,
+        }
+    }
+
+    /// The number of messages to save, `None` for all of them.
+    pub fn count(&self) -> Option<usize> {
+        COUNTS[self.count]
+    }
+
+    pub fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> PromptKey {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        match key_event.code {
+            KeyCode::Esc => return PromptKey::Cancelled,
+            KeyCode::Enter => return PromptKey::Confirmed,
+            KeyCode::Backspace => {
+                self.path.pop();
+            }
+            KeyCode::Tab => self.count = (self.count + 1) % COUNTS.len(),
+            KeyCode::BackTab => self.next_format(),
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.path.push(c)
+            }
+            _ => {}
+        }
+        PromptKey::Edited
+    }
+
+    /// Change the extension to the one of the next format.
+    fn next_format(&mut self) {
+        let format = ExportFormat::from_path(&self.path).unwrap_or(ExportFormat::Csv);
+        let index = ExportFormat::ALL
+            .iter()
+            .position(|f| *f == format)
+            .unwrap_or(0);
+        let next = ExportFormat::ALL[(index + 1) % ExportFormat::ALL.len()];
+        self.path = Path::new(&self.path)
+            .with_extension(next.extension())
+            .to_string_lossy()
+            .into_owned();
+    }
+
+    /// E.g. `Save the last 10 messages to: scan.csv█`.
+    pub fn prompt(&self, buffered: usize) -> String {
+        let what = match self.count() {
+            Some(1) => "the newest message".to_string(),
+            Some(count) => format!("the last {} messages", count.min(buffered)),
+            None => format!("all {} messages", buffered),
+        };
+        format!(
+            " Save {} to: {}█ (tab: how many, shift-tab: format) ",
+            what, self.path
+        )
+    }
+}
+
+/// E.g. `robot_scan_20261018-101500.json` for `/robot/scan`, in UTC.
+pub fn default_file_name(topic: &str, now: SystemTime, format: ExportFormat) -> String {
+    let name = topic.trim_start_matches('/').replace('/', "_");
+    format!("{}_{}.{}", name, utc_stamp(now), format.extension())
+}
+
+/// Write the newest `count` messages to `path`, in the format of its extension.
+/// Returns how many were written.
+pub fn save(
+    path: &str,
+    messages: &VecDeque<ReceivedMessage>,
+    count: Option<usize>,
+) -> io::Result<usize> {
+    let format = ExportFormat::from_path(path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "unknown format, the file name should end in .json, .yaml or .csv",
+        )
+    })?;
+    let count = count.unwrap_or(messages.len()).min(messages.len());
+    let newest: Vec<&ReceivedMessage> = messages.iter().skip(messages.len() - count).collect();
+    std::fs::write(path, format_messages(&newest, format)?)?;
+    Ok(count)
+}
+
+/// A single message is written as it is, more of them as a list. Only CSV
+/// has the receive times.
+pub fn format_messages(messages: &[&ReceivedMessage], format: ExportFormat) -> io::Result<String> {
+    let values: Value = match messages {
+        [single] => single.message.clone(),
+        _ => messages.iter().map(|m| m.message.clone()).collect(),
+    };
+    match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&values)
+            .map(|json| json + "\n")
+            .map_err(io::Error::other),
+        ExportFormat::Yaml => serde_yaml::to_string(&values).map_err(io::Error::other),
+        ExportFormat::Csv => Ok(csv(messages)),
+    }
+}
+
+/// One row per message with the receive time, the header stamp if the message
+/// has one, and every leaf field as a column named by its path, e.g.
+/// `pose.position.x` or `ranges.3`.
+fn csv(messages: &[&ReceivedMessage]) -> String {
+    let rows: Vec<Vec<(String, String)>> = messages
+        .iter()
+        .map(|m| {
+            let mut fields = vec![];
+            flatten("", &m.message, &mut fields);
+            fields
+        })
+        .collect();
+
+    // Columns in the order they first appear, messages may differ in their arrays
+    let mut columns: Vec<&str> = vec![];
+    let mut column_index = HashMap::new();
+    for (name, _) in rows.iter().flatten() {
+        if !column_index.contains_key(name.as_str()) {
+            column_index.insert(name.as_str(), columns.len());
+            columns.push(name);
+        }
+    }
+
+    let mut text = String::from("receive_time,header_time");
+    for column in &columns {
+        text.push(',');
+        text.push_str(&csv_field(column));
+    }
+    text.push('\n');
+    for (message, fields) in messages.iter().zip(&rows) {
+        let mut cells = vec![String::new(); columns.len()];
+        for (name, value) in fields {
+            cells[column_index[name.as_str()]] = csv_field(value);
+        }
+        let _ = writeln!(
+            text,
+            "{},{},{}",
+            unix_seconds(message.received),
+            latency::header_stamp(&message.message)
+                .map(|stamp| format!("{}.{:09}", stamp.as_secs(), stamp.subsec_nanos()))
+                .unwrap_or_default(),
+            cells.join(",")
+        );
+    }
+    text
+}
+
+fn flatten(path: &str, value: &Value, fields: &mut Vec<(String, String)>) {
+    let child = |key: &dyn std::fmt::Display| match path {
+        "" => key.to_string(),
+        _ => format!("{}.{}", path, key),
+    };
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                flatten(&child(key), value, fields);
+            }
+        }
+        Value::Array(array) => {
+            for (i, value) in array.iter().enumerate() {
+                flatten(&child(&i), value, fields);
+            }
+        }
+        Value::String(string) => fields.push((path.to_string(), string.clone())),
+        Value::Null => fields.push((path.to_string(), String::new())),
+        other => fields.push((path.to_string(), other.to_string())),
+    }
+}
+
+/// Quote a field when it holds a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    format!(
+        "{}.{:09}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_nanos()
+    )
+}
+
+/// E.g. `20261018-101500`.
+fn utc_stamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, time_of_day) = (seconds / 86_400, seconds % 86_400);
+
+    // Civil date from days since 1970-01-01, after Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time_of_day / 3_600,
+        time_of_day % 3_600 / 60,
+        time_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn export_formats_one_message_as_is_and_more_as_a_list() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_760_781_300);
+        assert_eq!(
+            default_file_name("/robot/scan", time, ExportFormat::Csv),
+            "robot_scan_20251018-095500.csv"
+        );
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951_868_799);
+        assert!(default_file_name("/a", leap_day, ExportFormat::Json).contains("20000229-235959"));
+
+        let messages: Vec<ReceivedMessage> = (1..=2)
+            .map(|sec| ReceivedMessage {
+                received: time,
+                message: serde_json::json!({ "data": sec }),
+            })
+            .collect();
+        let one = [&messages[0]];
+        let both = [&messages[0], &messages[1]];
+        assert_eq!(
+            format_messages(&one, ExportFormat::Json).unwrap(),
+            "{\n  \"data\": 1\n}\n"
+        );
+        assert_eq!(
+            format_messages(&both, ExportFormat::Yaml).unwrap(),
+            "- data: 1\n- data: 2\n"
+        );
+        assert_eq!(
+            format_messages(&both, ExportFormat::Csv).unwrap(),
+            "receive_time,header_time,data\n1760781300.000000000,,1\n1760781300.000000000,,2\n"
+        );
+    }
+}
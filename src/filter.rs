@@ -0,0 +1,151 @@
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+use crate::config::{Action, KeyBindings};
+
+/// The `/` prompt of a table pane, fuzzy matching the names of its rows.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TableFilter {
+    query: String,
+    editing: bool,
+    /// Also match the message type column, where the pane has one.
+    match_types: bool,
+}
+
+/// A row that passed the filter, with the positions of the matched characters.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FilterMatch {
+    /// Index into the unfiltered rows of the pane.
+    pub index: usize,
+    pub name_indices: Vec<usize>,
+    pub type_indices: Vec<usize>,
+}
+
+impl TableFilter {
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    pub fn start_editing(&mut self) {
+        self.editing = true;
+    }
+
+    /// Edit the query. Returns whether the set of matching rows may have changed.
+    pub fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> bool {
+        use crossterm::event::{KeyCode, KeyModifiers};
+
+        if keys.matches(Action::ToggleFilterTypes, &key_event) {
+            self.match_types = !self.match_types;
+            return true;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.query.clear();
+                self.editing = false;
+                true
+            }
+            KeyCode::Enter => {
+                self.editing = false;
+                false
+            }
+            KeyCode::Backspace => self.query.pop().is_some(),
+            KeyCode::Char(c) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.query.push(c);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Match `(name, type)` pairs against the query, keeping their order.
+    pub fn apply<'a>(
+        &self,
+        rows: impl Iterator<Item = (&'a str, Option<&'a str>)>,
+    ) -> Vec<FilterMatch> {
+        if self.query.is_empty() {
+            return rows
+                .enumerate()
+                .map(|(index, _)| FilterMatch {
+                    index,
+                    ..Default::default()
+                })
+                .collect();
+        }
+
+        let matcher = SkimMatcherV2::default();
+        rows.enumerate()
+            .filter_map(|(index, (name, msg_type))| {
+                let name_indices = matcher
+                    .fuzzy_indices(name, &self.query)
+                    .map(|(_, indices)| indices);
+                let type_indices = msg_type
+                    .filter(|_| self.match_types)
+                    .and_then(|msg_type| matcher.fuzzy_indices(msg_type, &self.query))
+                    .map(|(_, indices)| indices);
+                if name_indices.is_none() && type_indices.is_none() {
+                    return None;
+                }
+                Some(FilterMatch {
+                    index,
+                    name_indices: name_indices.unwrap_or_default(),
+                    type_indices: type_indices.unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Pane title with the number of shown rows, e.g. ` Topics (3 of 120) `.
+    pub fn title(&self, name: &str, shown: usize, total: usize) -> String {
+        match self.is_active() {
+            true => format!(" {} ({} of {}) ", name, shown, total),
+            false => format!(" {} ", name),
+        }
+    }
+
+    /// The prompt shown at the bottom of the pane while the filter is used.
+    pub fn prompt(&self, with_types: bool) -> Option<Line<'static>> {
+        if !self.editing && !self.is_active() {
+            return None;
+        }
+        let cursor = if self.editing { "█" } else { "" };
+        let types = if with_types && self.match_types {
+            " (names and types)"
+        } else {
+            ""
+        };
+        Some(Line::from(format!(" /{}{}{} ", self.query, cursor, types)))
+    }
+}
+
+/// Render `text` with the characters at `indices` in the `matched` style.
+pub fn highlight(text: &str, indices: &[usize], style: Style, matched: Style) -> Line<'static> {
+    let mut spans: Vec<Span> = vec![];
+    let mut current = String::new();
+    let mut current_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_match = indices.contains(&i);
+        if is_match != current_matched && !current.is_empty() {
+            let style = if current_matched { matched } else { style };
+            spans.push(Span::styled(std::mem::take(&mut current), style));
+        }
+        current_matched = is_match;
+        current.push(c);
+    }
+    if !current.is_empty() {
+        let style = if current_matched { matched } else { style };
+        spans.push(Span::styled(current, style));
+    }
+    Line::from(spans)
+}
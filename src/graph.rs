@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Topics that every ROS 2 node creates for logging and parameters. They connect
+/// almost every node to every other one and hide the interesting structure.
+const DEBUG_TOPICS: [&str; 2] = ["/rosout", "/parameter_events"];
+
+/// Every action server publishes its feedback on `<action>/_action/feedback`.
+const ACTION_FEEDBACK_SUFFIX: &str = "/_action/feedback";
+
+/// A point-in-time copy of the ROS computation graph as seen by the lazyros node.
+///
+/// The ROS thread rebuilds it periodically and sends it to the UI, where it feeds
+/// the node and topic panes as well as the graph view.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphSnapshot {
+    pub nodes: Vec<NodeInfo>,
+    pub topics: Vec<TopicInfo>,
+    pub services: Vec<ServiceInfo>,
+    pub actions: Vec<ActionInfo>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeInfo {
+    /// Fully qualified node name, e.g. `/robot1/controller`.
+    pub name: String,
+    pub publishes: Vec<String>,
+    pub subscribes: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct TopicInfo {
+    pub name: String,
+    pub msg_types: Vec<String>,
+    pub publishers: Vec<EndpointInfo>,
+    pub subscribers: Vec<EndpointInfo>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub srv_types: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ActionInfo {
+    pub name: String,
+    pub action_type: String,
+    /// Nodes publishing the feedback of the action.
+    pub servers: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct EndpointInfo {
+    /// Fully qualified name of the node owning the endpoint.
+    pub node_name: String,
+    pub qos: QosInfo,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct QosInfo {
+    pub reliability: String,
+    pub durability: String,
+    pub history: String,
+    pub depth: usize,
+}
+
+impl GraphSnapshot {
+    /// A copy with only the nodes and topics whose name passes `keep`.
+    pub fn filtered(&self, keep: impl Fn(&str) -> bool) -> GraphSnapshot {
+        let keep_topics = |topics: &[String]| -> Vec<String> {
+            topics.iter().filter(|t| keep(t)).cloned().collect()
+        };
+
+        GraphSnapshot {
+            nodes: self
+                .nodes
+                .iter()
+                .filter(|node| keep(&node.name))
+                .map(|node| NodeInfo {
+                    name: node.name.clone(),
+                    publishes: keep_topics(&node.publishes),
+                    subscribes: keep_topics(&node.subscribes),
+                })
+                .collect(),
+            topics: self
+                .topics
+                .iter()
+                .filter(|topic| keep(&topic.name))
+                .cloned()
+                .collect(),
+            services: self.services.clone(),
+            actions: self.actions.clone(),
+        }
+    }
+
+    /// Build a snapshot from the topic list, deriving the node list from the
+    /// endpoints of every topic. The services are left for the caller to fill in.
+    pub fn from_topics(mut topics: Vec<TopicInfo>) -> GraphSnapshot {
+        topics.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut nodes: BTreeMap<String, NodeInfo> = BTreeMap::new();
+        for topic in &topics {
+            for publisher in &topic.publishers {
+                node_entry(&mut nodes, &publisher.node_name)
+                    .publishes
+                    .push(topic.name.clone());
+            }
+            for subscriber in &topic.subscribers {
+                node_entry(&mut nodes, &subscriber.node_name)
+                    .subscribes
+                    .push(topic.name.clone());
+            }
+        }
+
+        // A node with several publishers on one topic still publishes it once
+        for node in nodes.values_mut() {
+            node.publishes.dedup();
+            node.subscribes.dedup();
+        }
+
+        GraphSnapshot {
+            nodes: nodes.into_values().collect(),
+            actions: actions_from_topics(&topics),
+            topics,
+            services: vec![],
+        }
+    }
+}
+
+/// Actions are not part of the topic graph, but their hidden feedback topics are.
+fn actions_from_topics(topics: &[TopicInfo]) -> Vec<ActionInfo> {
+    topics
+        .iter()
+        .filter_map(|topic| {
+            let name = topic.name.strip_suffix(ACTION_FEEDBACK_SUFFIX)?;
+            // The feedback type is `pkg/action/Name_FeedbackMessage`
+            let action_type = topic
+                .msg_types
+                .first()
+                .map(|t| t.trim_end_matches("_FeedbackMessage").to_string())
+                .unwrap_or_default();
+            Some(ActionInfo {
+                name: name.to_string(),
+                action_type,
+                servers: topic
+                    .publishers
+                    .iter()
+                    .map(|p| p.node_name.clone())
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn node_entry<'a>(nodes: &'a mut BTreeMap<String, NodeInfo>, name: &str) -> &'a mut NodeInfo {
+    nodes.entry(name.to_string()).or_insert_with(|| NodeInfo {
+        name: name.to_string(),
+        ..Default::default()
+    })
+}
+
+/// Join a namespace and a node name into a fully qualified name.
+pub fn fully_qualified_name(namespace: &str, name: &str) -> String {
+    if namespace.ends_with('/') {
+        format!("{}{}", namespace, name)
+    } else {
+        format!("{}/{}", namespace, name)
+    }
+}
+
+/// Whether `name` lives in `namespace`. An empty namespace or `/` matches everything.
+pub fn in_namespace(name: &str, namespace: &str) -> bool {
+    let namespace = namespace.trim_end_matches('/');
+    if namespace.is_empty() {
+        return true;
+    }
+    name == namespace
+        || name
+            .strip_prefix(namespace)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Logging and parameter topics that are present for every node.
+pub fn is_debug_topic(name: &str) -> bool {
+    DEBUG_TOPICS.contains(&name)
+}
+
+/// Infrastructure the ROS 2 tools hide by default: the debug topics and every
+/// name with a `_`-prefixed segment, such as action internals under `_action/`.
+pub fn is_hidden(name: &str) -> bool {
+    is_debug_topic(name) || name.split('/').any(|segment| segment.starts_with('_'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topic(name: &str, msg_type: &str, publishers: &[&str], subscribers: &[&str]) -> TopicInfo {
+        let endpoints = |nodes: &[&str]| {
+            nodes
+                .iter()
+                .map(|node| EndpointInfo {
+                    node_name: node.to_string(),
+                    qos: QosInfo::default(),
+                })
+                .collect()
+        };
+        TopicInfo {
+            name: name.to_string(),
+            msg_types: vec![msg_type.to_string()],
+            publishers: endpoints(publishers),
+            subscribers: endpoints(subscribers),
+        }
+    }
+
+    fn graph() -> GraphSnapshot {
+        GraphSnapshot::from_topics(vec![
+            topic(
+                "/scan",
+                "sensor_msgs/msg/LaserScan",
+                &["/robot/lidar"],
+                &["/nav/planner"],
+            ),
+            topic(
+                "/camera/front/compressed",
+                "sensor_msgs/msg/CompressedImage",
+                &["/camera/driver"],
+                &["/nav/planner"],
+            ),
+            topic(
+                "/rosout",
+                "rcl_interfaces/msg/Log",
+                &["/robot/lidar", "/nav/planner"],
+                &[],
+            ),
+            topic(
+                "/nav/_action/feedback",
+                "nav2_msgs/action/Navigate_FeedbackMessage",
+                &["/nav/_server"],
+                &[],
+            ),
+        ])
+    }
+
+    fn names<T>(items: &[T], name: impl Fn(&T) -> &str) -> Vec<&str> {
+        items.iter().map(name).collect()
+    }
+
+    #[test]
+    fn nodes_and_actions_are_derived_from_the_topics() {
+        let graph = graph();
+        assert_eq!(
+            names(&graph.nodes, |n| &n.name),
+            [
+                "/camera/driver",
+                "/nav/_server",
+                "/nav/planner",
+                "/robot/lidar"
+            ]
+        );
+        let planner = &graph.nodes[2];
+        assert_eq!(planner.publishes, ["/rosout"]);
+        assert_eq!(planner.subscribes, ["/camera/front/compressed", "/scan"]);
+        assert_eq!(graph.actions.len(), 1);
+        assert_eq!(graph.actions[0].name, "/nav");
+        assert_eq!(graph.actions[0].action_type, "nav2_msgs/action/Navigate");
+        assert_eq!(graph.actions[0].servers, ["/nav/_server"]);
+    }
+
+    #[test]
+    fn debug_topics_and_underscore_segments_are_hidden() {
+        assert!(is_hidden("/rosout"));
+        assert!(is_hidden("/parameter_events"));
+        assert!(is_hidden("/nav/_action/feedback"));
+        assert!(is_hidden("/_private"));
+        assert!(!is_hidden("/scan"));
+        assert!(!is_hidden("/robot/joint_states_"));
+        assert!(!is_hidden("/robot/rosout"));
+    }
+
+    #[test]
+    fn filtered_drops_names_from_nodes_and_topics() {
+        let shown = graph().filtered(|name| !is_hidden(name));
+        assert_eq!(
+            names(&shown.nodes, |n| &n.name),
+            ["/camera/driver", "/nav/planner", "/robot/lidar"]
+        );
+        assert_eq!(
+            names(&shown.topics, |t| &t.name),
+            ["/camera/front/compressed", "/scan"]
+        );
+        // The endpoints of the nodes that stay lose the hidden topics as well
+        assert!(shown.nodes[1].publishes.is_empty());
+        assert_eq!(shown.nodes[2].publishes, ["/scan"]);
+        assert_eq!(shown.actions, graph().actions);
+    }
+
+    #[test]
+    fn names_join_and_match_namespaces() {
+        assert_eq!(fully_qualified_name("/", "talker"), "/talker");
+        assert_eq!(fully_qualified_name("/robot", "talker"), "/robot/talker");
+        assert!(in_namespace("/robot/talker", "/robot/"));
+        assert!(in_namespace("/robot", "/robot"));
+        assert!(!in_namespace("/robot1/talker", "/robot"));
+        assert!(in_namespace("/anything", "/"));
+    }
+}
@@ -0,0 +1,768 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io,
+};
+
+use crate::{
+    config::{Action, KeyBindings, Theme},
+    graph::{self, GraphSnapshot},
+};
+
+use ratatui::{
+    layout::Margin,
+    prelude::Rect,
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+    Frame,
+};
+
+const BOX_HEIGHT: i32 = 3;
+const ROW_GAP: i32 = 1;
+/// Horizontal space between two layers, used to route the edges.
+const LAYER_GAP: i32 = 8;
+const MAX_LABEL_WIDTH: usize = 32;
+const ORDERING_SWEEPS: usize = 4;
+const PAN_STEP_X: i32 = 4;
+const PAN_STEP_Y: i32 = 2;
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+/// Draws the computation graph as boxes connected by edges, similar to `rqt_graph`.
+#[derive(Debug)]
+pub struct GraphView {
+    hide_debug_topics: bool,
+    namespace_filter: String,
+    filter_input: Option<String>,
+    pan_x: i32,
+    pan_y: i32,
+}
+
+impl Default for GraphView {
+    fn default() -> Self {
+        GraphView {
+            hide_debug_topics: true,
+            namespace_filter: String::new(),
+            filter_input: None,
+            pan_x: 0,
+            pan_y: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VertexKind {
+    Node,
+    Topic,
+}
+
+#[derive(Debug)]
+struct Vertex {
+    kind: VertexKind,
+    name: String,
+    layer: usize,
+    x: i32,
+    y: i32,
+    width: i32,
+}
+
+impl Vertex {
+    fn label(&self) -> String {
+        let count = self.name.chars().count();
+        if count <= MAX_LABEL_WIDTH {
+            self.name.clone()
+        } else {
+            // Keep the end of the name, which is the most specific part
+            let tail: String = self
+                .name
+                .chars()
+                .skip(count - MAX_LABEL_WIDTH + 1)
+                .collect();
+            format!("…{}", tail)
+        }
+    }
+}
+
+/// Positions of all boxes of the graph in a virtual canvas.
+#[derive(Debug, Default)]
+struct GraphLayout {
+    vertices: Vec<Vertex>,
+    /// Directed edges as indices into `vertices`.
+    edges: Vec<(usize, usize)>,
+    layer_x: Vec<i32>,
+    width: i32,
+    height: i32,
+}
+
+impl GraphView {
+    pub fn is_editing(&self) -> bool {
+        self.filter_input.is_some()
+    }
+
+    /// Forget the manual panning so the view centers on the selected node again.
+    pub fn reset_pan(&mut self) {
+        self.pan_x = 0;
+        self.pan_y = 0;
+    }
+
+    pub fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> io::Result<()> {
+        use crossterm::event::{KeyCode, KeyEventKind};
+
+        if key_event.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+
+        if let Some(input) = &mut self.filter_input {
+            match key_event.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    self.namespace_filter = normalize_namespace(input);
+                    self.filter_input = None;
+                    self.reset_pan();
+                }
+                KeyCode::Esc => self.filter_input = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if keys.matches(Action::PanLeft, &key_event) {
+            self.pan_x -= PAN_STEP_X;
+        } else if keys.matches(Action::PanRight, &key_event) {
+            self.pan_x += PAN_STEP_X;
+        } else if keys.matches(Action::PanUp, &key_event) {
+            self.pan_y -= PAN_STEP_Y;
+        } else if keys.matches(Action::PanDown, &key_event) {
+            self.pan_y += PAN_STEP_Y;
+        } else if keys.matches(Action::CenterView, &key_event) {
+            self.reset_pan();
+        } else if keys.matches(Action::ToggleDebugTopics, &key_event) {
+            self.hide_debug_topics = !self.hide_debug_topics;
+        } else if keys.matches(Action::FilterNamespace, &key_event) {
+            self.filter_input = Some(self.namespace_filter.clone());
+        }
+
+        Ok(())
+    }
+
+    pub fn render(
+        &self,
+        snapshot: &GraphSnapshot,
+        selected_node: Option<&str>,
+        area: Rect,
+        frame: &mut Frame,
+        theme: &Theme,
+        keys: &KeyBindings,
+    ) {
+        let layout = self.compute_layout(snapshot);
+
+        let block = Block::bordered()
+            .title(Line::from(" Graph "))
+            .title_bottom(self.status_line(&layout, keys))
+            .style(theme.focused_border)
+            .border_type(ratatui::widgets::BorderType::Rounded);
+        frame.render_widget(block, area);
+
+        let inner_area = area.inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+
+        if layout.vertices.is_empty() {
+            Paragraph::new("No nodes or topics to show")
+                .style(Style::default().fg(theme.text))
+                .centered()
+                .render(inner_area, frame.buffer_mut());
+            return;
+        }
+
+        let selected = selected_node.and_then(|name| {
+            layout
+                .vertices
+                .iter()
+                .position(|v| v.kind == VertexKind::Node && v.name == name)
+        });
+        let neighbourhood = selected
+            .map(|s| neighbourhood(&layout, s))
+            .unwrap_or_default();
+
+        // Scroll so that the selected node is centered, then apply the manual panning
+        let (center_x, center_y) = match selected {
+            Some(s) => {
+                let v = &layout.vertices[s];
+                (v.x + v.width / 2, v.y + BOX_HEIGHT / 2)
+            }
+            None => (0, 0),
+        };
+        let offset_x = clamp_offset(
+            center_x - inner_area.width as i32 / 2 + self.pan_x,
+            layout.width,
+            inner_area.width,
+        );
+        let offset_y = clamp_offset(
+            center_y - inner_area.height as i32 / 2 + self.pan_y,
+            layout.height,
+            inner_area.height,
+        );
+
+        let mut canvas = Canvas::new(inner_area, offset_x, offset_y, theme.selection, theme.edge);
+        self.draw_edges(&layout, &neighbourhood, &mut canvas);
+        for (i, vertex) in layout.vertices.iter().enumerate() {
+            let style = match (Some(i) == selected, neighbourhood.contains(&i), vertex.kind) {
+                (true, _, _) => Style::default()
+                    .fg(theme.selection)
+                    .add_modifier(Modifier::BOLD),
+                (false, true, _) => Style::default().fg(theme.selection),
+                (false, false, VertexKind::Node) => Style::default().fg(theme.node),
+                (false, false, VertexKind::Topic) => Style::default().fg(theme.topic),
+            };
+            canvas.draw_box(vertex, Some(i) == selected, style);
+        }
+        canvas.render(frame);
+    }
+
+    fn status_line(&self, layout: &GraphLayout, keys: &KeyBindings) -> Line<'static> {
+        if let Some(input) = &self.filter_input {
+            return Line::from(format!(" Namespace: {}█ ", input));
+        }
+
+        let num_nodes = layout
+            .vertices
+            .iter()
+            .filter(|v| v.kind == VertexKind::Node)
+            .count();
+        let namespace = match self.namespace_filter.is_empty() {
+            true => "/",
+            false => self.namespace_filter.as_str(),
+        };
+        let debug = match self.hide_debug_topics {
+            true => "hidden",
+            false => "shown",
+        };
+        Line::from(format!(
+            " {} nodes, {} topics | ns {} <{}> | debug topics {} <{}> | pan <{}/{}/{}/{}> center <{}> ",
+            num_nodes,
+            layout.vertices.len() - num_nodes,
+            namespace,
+            keys.keys(Action::FilterNamespace),
+            debug,
+            keys.keys(Action::ToggleDebugTopics),
+            keys.keys(Action::PanUp),
+            keys.keys(Action::PanDown),
+            keys.keys(Action::PanLeft),
+            keys.keys(Action::PanRight),
+            keys.keys(Action::CenterView),
+        ))
+    }
+
+    fn compute_layout(&self, snapshot: &GraphSnapshot) -> GraphLayout {
+        let mut vertices: Vec<Vertex> = vec![];
+        let mut edges: Vec<(usize, usize)> = vec![];
+        let mut node_index: HashMap<&str, usize> = HashMap::new();
+
+        for node in &snapshot.nodes {
+            if !graph::in_namespace(&node.name, &self.namespace_filter) {
+                continue;
+            }
+            node_index.insert(node.name.as_str(), vertices.len());
+            vertices.push(new_vertex(VertexKind::Node, &node.name));
+        }
+
+        // Topics are only shown when at least one visible node is connected to them
+        for topic in &snapshot.topics {
+            if self.hide_debug_topics && graph::is_debug_topic(&topic.name) {
+                continue;
+            }
+            let topic_index = vertices.len();
+            let mut topic_edges: Vec<(usize, usize)> = vec![];
+            for publisher in &topic.publishers {
+                if let Some(&n) = node_index.get(publisher.node_name.as_str()) {
+                    topic_edges.push((n, topic_index));
+                }
+            }
+            for subscriber in &topic.subscribers {
+                if let Some(&n) = node_index.get(subscriber.node_name.as_str()) {
+                    topic_edges.push((topic_index, n));
+                }
+            }
+            if topic_edges.is_empty() {
+                continue;
+            }
+            topic_edges.dedup();
+            vertices.push(new_vertex(VertexKind::Topic, &topic.name));
+            edges.extend(topic_edges);
+        }
+
+        let layers = assign_layers(vertices.len(), &edges);
+        for (vertex, layer) in vertices.iter_mut().zip(&layers) {
+            vertex.layer = *layer;
+        }
+        let ordering = order_layers(&vertices, &edges);
+
+        // Place the layers from left to right and the boxes of a layer from top to bottom
+        let mut layout = GraphLayout {
+            vertices,
+            edges,
+            ..Default::default()
+        };
+        let max_rows = ordering.iter().map(|l| l.len()).max().unwrap_or(0) as i32;
+        let mut x = 0;
+        for layer in &ordering {
+            let layer_width = layer
+                .iter()
+                .map(|&v| layout.vertices[v].width)
+                .max()
+                .unwrap_or(0);
+            let top = (max_rows - layer.len() as i32) / 2 * (BOX_HEIGHT + ROW_GAP);
+            for (row, &v) in layer.iter().enumerate() {
+                layout.vertices[v].x = x;
+                layout.vertices[v].y = top + row as i32 * (BOX_HEIGHT + ROW_GAP);
+            }
+            layout.layer_x.push(x);
+            x += layer_width + LAYER_GAP;
+        }
+        layout.width = (x - LAYER_GAP).max(0);
+        layout.height = (max_rows * (BOX_HEIGHT + ROW_GAP) - ROW_GAP).max(0);
+        layout
+    }
+
+    fn draw_edges(
+        &self,
+        layout: &GraphLayout,
+        neighbourhood: &HashSet<usize>,
+        canvas: &mut Canvas,
+    ) {
+        for (i, &(from, to)) in layout.edges.iter().enumerate() {
+            let source = &layout.vertices[from];
+            let target = &layout.vertices[to];
+            let highlighted = neighbourhood.contains(&from) && neighbourhood.contains(&to);
+            // Spread the vertical segments over the gap so parallel edges stay apart
+            let lane = (i as i32 % (LAYER_GAP - 3)) + 1;
+            let source_y = source.y + BOX_HEIGHT / 2;
+            let target_y = target.y + BOX_HEIGHT / 2;
+
+            if target.layer > source.layer {
+                // Forward edge: leave on the right, enter the target from the left
+                let start = source.x + source.width;
+                let lane_x = layout.layer_x[source.layer + 1] - LAYER_GAP + lane;
+                let end = target.x - 1;
+                canvas.line(start, source_y, lane_x, source_y, highlighted);
+                canvas.line(lane_x, source_y, lane_x, target_y, highlighted);
+                canvas.line(lane_x, target_y, end, target_y, highlighted);
+                canvas.arrow(end, target_y, '▶', highlighted);
+            } else {
+                // Backward edge: leave underneath, run below both boxes and enter
+                // the target from underneath
+                let source_x = source.x + source.width / 2;
+                let target_x = target.x + target.width / 2;
+                let detour_y = source.y.max(target.y) + BOX_HEIGHT;
+                let end = target.y + BOX_HEIGHT;
+                canvas.line(
+                    source_x,
+                    source.y + BOX_HEIGHT - 1,
+                    source_x,
+                    detour_y,
+                    highlighted,
+                );
+                canvas.line(source_x, detour_y, target_x, detour_y, highlighted);
+                canvas.line(target_x, detour_y, target_x, end, highlighted);
+                canvas.arrow(target_x, end, '▲', highlighted);
+            }
+        }
+    }
+}
+
+fn new_vertex(kind: VertexKind, name: &str) -> Vertex {
+    let mut vertex = Vertex {
+        kind,
+        name: name.to_string(),
+        layer: 0,
+        x: 0,
+        y: 0,
+        width: 0,
+    };
+    vertex.width = vertex.label().chars().count() as i32 + 4;
+    vertex
+}
+
+fn normalize_namespace(input: &str) -> String {
+    let namespace = input.trim().trim_end_matches('/');
+    if namespace.is_empty() || namespace.starts_with('/') {
+        namespace.to_string()
+    } else {
+        format!("/{}", namespace)
+    }
+}
+
+fn clamp_offset(offset: i32, content: i32, viewport: u16) -> i32 {
+    let max = (content - viewport as i32).max(0);
+    if max == 0 {
+        // The content fits, center it
+        -((viewport as i32 - content) / 2)
+    } else {
+        offset.clamp(0, max)
+    }
+}
+
+/// Indices of the selected vertex, its topics and the nodes on the other end of them.
+fn neighbourhood(layout: &GraphLayout, selected: usize) -> HashSet<usize> {
+    let mut result = HashSet::from([selected]);
+    for &(from, to) in &layout.edges {
+        if from == selected {
+            result.insert(to);
+        } else if to == selected {
+            result.insert(from);
+        }
+    }
+    let topics: Vec<usize> = result.iter().copied().filter(|&v| v != selected).collect();
+    for &(from, to) in &layout.edges {
+        if topics.contains(&from) {
+            result.insert(to);
+        } else if topics.contains(&to) {
+            result.insert(from);
+        }
+    }
+    result
+}
+
+/// Longest-path layering. Cycles are broken by ignoring the back edges of a depth
+/// first search, so every remaining edge points to a higher layer.
+fn assign_layers(num_vertices: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut successors: Vec<Vec<usize>> = vec![vec![]; num_vertices];
+    for &(from, to) in edges {
+        successors[from].push(to);
+    }
+
+    // 0: not visited, 1: on the stack, 2: finished
+    let mut state = vec![0u8; num_vertices];
+    let mut post_order: Vec<usize> = vec![];
+    let mut back_edges: HashSet<(usize, usize)> = HashSet::new();
+    for root in 0..num_vertices {
+        if state[root] != 0 {
+            continue;
+        }
+        let mut stack: Vec<(usize, usize)> = vec![(root, 0)];
+        state[root] = 1;
+        while let Some((vertex, next_child)) = stack.pop() {
+            if let Some(&child) = successors[vertex].get(next_child) {
+                stack.push((vertex, next_child + 1));
+                match state[child] {
+                    0 => {
+                        state[child] = 1;
+                        stack.push((child, 0));
+                    }
+                    1 => {
+                        back_edges.insert((vertex, child));
+                    }
+                    _ => {}
+                }
+            } else {
+                state[vertex] = 2;
+                post_order.push(vertex);
+            }
+        }
+    }
+
+    let mut layers = vec![0; num_vertices];
+    for &vertex in post_order.iter().rev() {
+        for &child in &successors[vertex] {
+            if !back_edges.contains(&(vertex, child)) {
+                layers[child] = layers[child].max(layers[vertex] + 1);
+            }
+        }
+    }
+    layers
+}
+
+/// Order the vertices inside every layer with the barycenter heuristic to reduce
+/// edge crossings.
+fn order_layers(vertices: &[Vertex], edges: &[(usize, usize)]) -> Vec<Vec<usize>> {
+    let num_layers = vertices.iter().map(|v| v.layer + 1).max().unwrap_or(0);
+    let mut layers: Vec<Vec<usize>> = vec![vec![]; num_layers];
+    for (i, vertex) in vertices.iter().enumerate() {
+        layers[vertex.layer].push(i);
+    }
+    for layer in &mut layers {
+        layer.sort_by(|&a, &b| vertices[a].name.cmp(&vertices[b].name));
+    }
+
+    let mut neighbours: Vec<Vec<usize>> = vec![vec![]; vertices.len()];
+    for &(from, to) in edges {
+        neighbours[from].push(to);
+        neighbours[to].push(from);
+    }
+
+    let mut position = vec![0.0; vertices.len()];
+    let update_positions = |layers: &Vec<Vec<usize>>, position: &mut Vec<f64>| {
+        for layer in layers {
+            for (p, &v) in layer.iter().enumerate() {
+                position[v] = p as f64;
+            }
+        }
+    };
+    update_positions(&layers, &mut position);
+
+    for sweep in 0..ORDERING_SWEEPS {
+        let downwards = sweep % 2 == 0;
+        let order: Vec<usize> = match downwards {
+            true => (1..num_layers).collect(),
+            false => (0..num_layers.saturating_sub(1)).rev().collect(),
+        };
+        for l in order {
+            let reference = if downwards { l - 1 } else { l + 1 };
+            let mut keyed: Vec<(f64, usize)> = layers[l]
+                .iter()
+                .map(|&v| {
+                    let adjacent: Vec<f64> = neighbours[v]
+                        .iter()
+                        .filter(|&&n| vertices[n].layer == reference)
+                        .map(|&n| position[n])
+                        .collect();
+                    match adjacent.is_empty() {
+                        true => (position[v], v),
+                        false => (adjacent.iter().sum::<f64>() / adjacent.len() as f64, v),
+                    }
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[l] = keyed.into_iter().map(|(_, v)| v).collect();
+            update_positions(&layers, &mut position);
+        }
+    }
+    layers
+}
+
+/// A window onto the virtual graph canvas, clipped to the area of the frame.
+struct Canvas {
+    area: Rect,
+    offset_x: i32,
+    offset_y: i32,
+    highlight: Color,
+    edge: Color,
+    /// Line directions of every cell, combined into box drawing characters
+    lines: HashMap<(i32, i32), (u8, bool)>,
+    glyphs: Vec<(i32, i32, char, Style)>,
+}
+
+impl Canvas {
+    fn new(area: Rect, offset_x: i32, offset_y: i32, highlight: Color, edge: Color) -> Canvas {
+        Canvas {
+            area,
+            offset_x,
+            offset_y,
+            highlight,
+            edge,
+            lines: HashMap::new(),
+            glyphs: vec![],
+        }
+    }
+
+    /// Add a horizontal or vertical line between two points.
+    fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, highlighted: bool) {
+        let mut mark = |x: i32, y: i32, bits: u8| {
+            let cell = self.lines.entry((x, y)).or_insert((0, false));
+            cell.0 |= bits;
+            cell.1 |= highlighted;
+        };
+        if y0 == y1 {
+            let (from, to) = (x0.min(x1), x0.max(x1));
+            for x in from..=to {
+                let mut bits = 0;
+                if x > from {
+                    bits |= LEFT;
+                }
+                if x < to {
+                    bits |= RIGHT;
+                }
+                mark(x, y0, if from == to { LEFT | RIGHT } else { bits });
+            }
+        } else {
+            let (from, to) = (y0.min(y1), y0.max(y1));
+            for y in from..=to {
+                let mut bits = 0;
+                if y > from {
+                    bits |= UP;
+                }
+                if y < to {
+                    bits |= DOWN;
+                }
+                mark(x0, y, bits);
+            }
+        }
+    }
+
+    fn arrow(&mut self, x: i32, y: i32, symbol: char, highlighted: bool) {
+        let style = self.edge_style(highlighted);
+        self.glyphs.push((x, y, symbol, style));
+    }
+
+    fn draw_box(&mut self, vertex: &Vertex, thick: bool, style: Style) {
+        let corners = match (thick, vertex.kind) {
+            (true, _) => ['┏', '┓', '┗', '┛', '━', '┃'],
+            (false, VertexKind::Node) => ['╭', '╮', '╰', '╯', '─', '│'],
+            (false, VertexKind::Topic) => ['┌', '┐', '└', '┘', '─', '│'],
+        };
+        let (left, right) = (vertex.x, vertex.x + vertex.width - 1);
+        let (top, bottom) = (vertex.y, vertex.y + BOX_HEIGHT - 1);
+
+        for x in left..=right {
+            for y in top..=bottom {
+                self.lines.remove(&(x, y));
+                let symbol = match (x, y) {
+                    _ if x == left && y == top => corners[0],
+                    _ if x == right && y == top => corners[1],
+                    _ if x == left && y == bottom => corners[2],
+                    _ if x == right && y == bottom => corners[3],
+                    _ if y == top || y == bottom => corners[4],
+                    _ if x == left || x == right => corners[5],
+                    _ => ' ',
+                };
+                self.glyphs.push((x, y, symbol, style));
+            }
+        }
+        for (i, c) in vertex.label().chars().enumerate() {
+            self.glyphs.push((left + 2 + i as i32, top + 1, c, style));
+        }
+    }
+
+    fn edge_style(&self, highlighted: bool) -> Style {
+        match highlighted {
+            true => Style::default()
+                .fg(self.highlight)
+                .add_modifier(Modifier::BOLD),
+            false => Style::default().fg(self.edge),
+        }
+    }
+
+    fn render(self, frame: &mut Frame) {
+        let buffer = frame.buffer_mut();
+        let mut put = |x: i32, y: i32, symbol: char, style: Style| {
+            let (sx, sy) = (x - self.offset_x, y - self.offset_y);
+            if sx < 0 || sy < 0 || sx >= self.area.width as i32 || sy >= self.area.height as i32 {
+                return;
+            }
+            let position = (self.area.x + sx as u16, self.area.y + sy as u16);
+            if let Some(cell) = buffer.cell_mut(position) {
+                cell.set_char(symbol).set_style(style);
+            }
+        };
+
+        for (&(x, y), &(bits, highlighted)) in &self.lines {
+            put(x, y, line_symbol(bits), self.edge_style(highlighted));
+        }
+        for &(x, y, symbol, style) in &self.glyphs {
+            put(x, y, symbol, style);
+        }
+    }
+}
+
+fn line_symbol(bits: u8) -> char {
+    match bits {
+        b if b == UP | DOWN | LEFT | RIGHT => '┼',
+        b if b == UP | DOWN | RIGHT => '├',
+        b if b == UP | DOWN | LEFT => '┤',
+        b if b == LEFT | RIGHT | DOWN => '┬',
+        b if b == LEFT | RIGHT | UP => '┴',
+        b if b == DOWN | RIGHT => '┌',
+        b if b == DOWN | LEFT => '┐',
+        b if b == UP | RIGHT => '└',
+        b if b == UP | LEFT => '┘',
+        b if b & (UP | DOWN) != 0 && b & (LEFT | RIGHT) == 0 => '│',
+        _ => '─',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::demo::DemoSystem;
+
+    fn layout(view: &GraphView) -> GraphLayout {
+        view.compute_layout(&DemoSystem::default().graph())
+    }
+
+    fn index(layout: &GraphLayout, name: &str) -> usize {
+        layout
+            .vertices
+            .iter()
+            .position(|v| v.name == name)
+            .unwrap_or_else(|| panic!("{} is not laid out", name))
+    }
+
+    #[test]
+    fn boxes_do_not_overlap_and_fit_the_canvas() {
+        let layout = layout(&GraphView::default());
+        assert!(layout.vertices.len() > 10);
+        for (i, a) in layout.vertices.iter().enumerate() {
+            assert!(a.x >= 0 && a.x + a.width <= layout.width, "{}", a.name);
+            assert!(a.y >= 0 && a.y + BOX_HEIGHT <= layout.height, "{}", a.name);
+            assert_eq!(a.x, layout.layer_x[a.layer], "{}", a.name);
+            for b in &layout.vertices[i + 1..] {
+                let apart_x = a.x + a.width <= b.x || b.x + b.width <= a.x;
+                let apart_y = a.y + BOX_HEIGHT <= b.y || b.y + BOX_HEIGHT <= a.y;
+                assert!(apart_x || apart_y, "{} overlaps {}", a.name, b.name);
+            }
+        }
+        // Room is left between the layers to route the edges
+        for pair in layout.layer_x.windows(2) {
+            assert!(pair[1] - pair[0] > LAYER_GAP);
+        }
+    }
+
+    #[test]
+    fn edges_run_from_publishers_through_topics_to_subscribers() {
+        let layout = layout(&GraphView::default());
+        let edge = |from: &str, to: &str| {
+            layout
+                .edges
+                .contains(&(index(&layout, from), index(&layout, to)))
+        };
+        assert!(edge("/robot/lidar_driver", "/scan"));
+        assert!(edge("/scan", "/nav/localization"));
+        assert!(edge("/talker", "/topic"));
+        assert!(edge("/topic", "/listener"));
+        for &(from, to) in &layout.edges {
+            assert_ne!(layout.vertices[from].kind, layout.vertices[to].kind);
+        }
+    }
+
+    #[test]
+    fn debug_topics_and_other_namespaces_are_left_out() {
+        let mut view = GraphView::default();
+        let names = |view: &GraphView| -> Vec<String> {
+            layout(view).vertices.into_iter().map(|v| v.name).collect()
+        };
+        assert!(!names(&view).contains(&"/rosout".to_string()));
+        view.hide_debug_topics = false;
+        assert!(names(&view).contains(&"/rosout".to_string()));
+
+        view.hide_debug_topics = true;
+        view.namespace_filter = normalize_namespace("nav/");
+        let layout = layout(&view);
+        for vertex in &layout.vertices {
+            if vertex.kind == VertexKind::Node {
+                assert!(vertex.name.starts_with("/nav/"), "{}", vertex.name);
+            }
+        }
+        assert!(layout.vertices.iter().any(|v| v.name == "/cmd_vel"));
+    }
+
+    #[test]
+    fn long_names_keep_their_end() {
+        let vertex = new_vertex(VertexKind::Topic, &format!("/{}/scan", "a".repeat(40)));
+        let label = vertex.label();
+        assert_eq!(label.chars().count(), MAX_LABEL_WIDTH);
+        assert!(label.starts_with('…') && label.ends_with("aaa/scan"));
+        assert_eq!(vertex.width, MAX_LABEL_WIDTH as i32 + 4);
+    }
+}
@@ -0,0 +1,89 @@
+use std::{fmt::Write, time::Duration};
+
+use crate::graph::{EndpointInfo, GraphSnapshot, QosInfo};
+
+/// The details pane text of a node: the topics it publishes and subscribes to.
+pub fn node_info(graph: &GraphSnapshot, name: &str) -> String {
+    let Some(node) = graph.nodes.iter().find(|node| node.name == name) else {
+        return format!("{} is gone", name);
+    };
+    let msg_type = |topic: &str| {
+        graph
+            .topics
+            .iter()
+            .find(|t| t.name == topic)
+            .and_then(|t| t.msg_types.first())
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    let mut text = format!("Node {}\n", node.name);
+    for (title, topics) in [
+        ("Publishes", &node.publishes),
+        ("Subscribes", &node.subscribes),
+    ] {
+        let _ = writeln!(text, "\n{} ({})", title, topics.len());
+        let width = topics.iter().map(|t| t.chars().count()).max().unwrap_or(0);
+        for topic in topics {
+            let _ = writeln!(text, "  {:width$}  {}", topic, msg_type(topic));
+        }
+    }
+    text
+}
+
+/// The details pane text of a topic: its types, rate and endpoints with their QoS.
+/// `stale_for` is the age of the newest message of a stale topic.
+pub fn topic_info(
+    graph: &GraphSnapshot,
+    name: &str,
+    rate: Option<f64>,
+    stale_for: Option<Duration>,
+) -> String {
+    let Some(topic) = graph.topics.iter().find(|topic| topic.name == name) else {
+        return format!("{} is gone", name);
+    };
+
+    let mut text = format!("Topic {}\n\n", topic.name);
+    let _ = writeln!(text, "Type  {}", topic.msg_types.join(", "));
+    let _ = match (rate, stale_for) {
+        (_, Some(age)) => writeln!(
+            text,
+            "Rate  stale, newest message {:.0} s old",
+            age.as_secs_f64()
+        ),
+        (Some(rate), None) => writeln!(text, "Rate  {:.1} Hz", rate),
+        (None, None) => writeln!(text, "Rate  -"),
+    };
+    for (title, endpoints) in [
+        ("Publishers", &topic.publishers),
+        ("Subscribers", &topic.subscribers),
+    ] {
+        let _ = writeln!(text, "\n{} ({})", title, endpoints.len());
+        write_endpoints(&mut text, endpoints);
+    }
+    text
+}
+
+fn write_endpoints(text: &mut String, endpoints: &[EndpointInfo]) {
+    let width = endpoints
+        .iter()
+        .map(|e| e.node_name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for endpoint in endpoints {
+        let _ = writeln!(
+            text,
+            "  {:width$}  {}",
+            endpoint.node_name,
+            qos_summary(&endpoint.qos)
+        );
+    }
+}
+
+/// E.g. `Reliable, Volatile, KeepLast (10)`.
+fn qos_summary(qos: &QosInfo) -> String {
+    format!(
+        "{}, {}, {} ({})",
+        qos.reliability, qos.durability, qos.history, qos.depth
+    )
+}
@@ -0,0 +1,99 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+/// The delay between the header stamps of an echo's messages and the time
+/// lazyros received them, and when the last one came in.
+#[derive(Debug, Default)]
+pub struct LatencyStats {
+    /// Messages with a header, the others only count for the age.
+    count: u32,
+    /// In seconds, negative when the stamp lies in the future of the receiver.
+    last: f64,
+    min: f64,
+    max: f64,
+    sum: f64,
+    last_received: Option<SystemTime>,
+}
+
+impl LatencyStats {
+    pub fn record(&mut self, received: SystemTime, message: &Value) {
+        self.last_received = Some(received);
+        let Some(stamp) = header_stamp(message) else {
+            return;
+        };
+        let received = received.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let latency = received.as_secs_f64() - stamp.as_secs_f64();
+        if self.count == 0 {
+            (self.min, self.max) = (latency, latency);
+        }
+        self.count += 1;
+        self.last = latency;
+        self.min = self.min.min(latency);
+        self.max = self.max.max(latency);
+        self.sum += latency;
+    }
+
+    /// E.g. `latency 12.3 ms, age 0.4 s (min 10.1 ms, mean 12.0 ms, max 20.5 ms)`,
+    /// or only the age for messages without a header. `None` before the first message.
+    pub fn summary(&self, now: SystemTime) -> Option<String> {
+        let age = now
+            .duration_since(self.last_received?)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let age = format!("age {}", format_seconds(age));
+        if self.count == 0 {
+            return Some(age);
+        }
+        Some(format!(
+            "latency {}, {} (min {}, mean {}, max {})",
+            format_seconds(self.last),
+            age,
+            format_seconds(self.min),
+            format_seconds(self.sum / self.count as f64),
+            format_seconds(self.max),
+        ))
+    }
+}
+
+/// `header.stamp` of a message, as time since the Unix epoch.
+pub fn header_stamp(message: &Value) -> Option<Duration> {
+    let stamp = message.pointer("/header/stamp")?;
+    let sec = stamp.get("sec")?.as_u64()?;
+    let nanosec = stamp.get("nanosec")?.as_u64()?;
+    Some(Duration::new(sec, u32::try_from(nanosec).ok()?))
+}
+
+/// Milliseconds below a second, e.g. `12.3 ms` or `1.25 s`.
+fn format_seconds(seconds: f64) -> String {
+    match seconds.abs() < 1.0 {
+        true => format!("{:.1} ms", seconds * 1000.0),
+        false => format!("{:.2} s", seconds),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_is_measured_from_the_header_stamp() {
+        let at = |seconds: f64| UNIX_EPOCH + Duration::from_secs_f64(seconds);
+        let stamp = |sec: u64, nanosec: u32| serde_json::json!({ "header": { "stamp": { "sec": sec, "nanosec": nanosec } } });
+        let mut stats = LatencyStats::default();
+        assert_eq!(stats.summary(at(100.0)), None);
+
+        stats.record(at(100.02), &stamp(100, 0));
+        stats.record(at(101.5), &stamp(100, 500_000_000));
+        stats.record(at(102.0), &stamp(102, 0));
+        assert_eq!(
+            stats.summary(at(102.25)).unwrap(),
+            "latency 0.0 ms, age 250.0 ms (min 0.0 ms, mean 340.0 ms, max 1.00 s)"
+        );
+
+        // Messages without a header only have an age
+        let mut stats = LatencyStats::default();
+        stats.record(at(100.0), &serde_json::json!({ "data": 1 }));
+        assert_eq!(stats.summary(at(103.0)).unwrap(), "age 3.00 s");
+    }
+}
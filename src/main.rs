@@ -1,17 +1,102 @@
-use futures::{future, stream::StreamExt};
-use r2r::QosProfile;
-use std::{
-    io,
-    sync::{mpsc, Arc, Mutex},
-    thread,
-};
-use tokio::task;
+use clap::{Parser, Subcommand};
+use std::{io, sync::mpsc, thread};
 
 mod app;
+mod config;
+mod demo;
+mod details;
+mod diagnostics;
+mod diff;
+mod dump;
 mod event;
+mod export;
+mod filter;
+mod graph;
+mod graph_view;
+mod info;
+mod latency;
+mod preview;
+mod rcl_graph;
+mod ros;
+mod sort;
+mod terminal;
+mod tree;
+
+/// A terminal UI to inspect a running ROS 2 system.
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Config file to use instead of $XDG_CONFIG_HOME/lazyros/config.toml
+    #[arg(long, global = true)]
+    config: Option<std::path::PathBuf>,
+
+    /// Show a simulated robot instead of the ROS graph
+    #[arg(long, global = true)]
+    demo: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Discover the ROS graph, print it to stdout and exit
+    Dump {
+        #[arg(long, value_enum, default_value = "json")]
+        format: dump::DumpFormat,
+
+        /// Seconds to wait for discovery before reading the graph
+        #[arg(long, default_value = "2", value_parser = dump::parse_settle_time)]
+        settle_time: std::time::Duration,
+    },
+}
 
 fn main() -> io::Result<()> {
-    let mut terminal = ratatui::init();
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Command::Dump { format, .. }) if cli.demo => {
+            let graph = demo::DemoSystem::default().graph();
+            println!("{}", dump::format_graph(&graph, format)?);
+            Ok(())
+        }
+        Some(Command::Dump {
+            format,
+            settle_time,
+        }) => dump_graph(format, settle_time),
+        None => {
+            // Report config errors before the terminal switches to raw mode
+            let config = match cli.config.or_else(config::Config::default_path) {
+                Some(path) => config::Config::load(path).unwrap_or_else(|e| {
+                    eprintln!("lazyros: {}", e);
+                    std::process::exit(1);
+                }),
+                None => config::Config::default(),
+            };
+            let backend: Box<dyn ros::Backend> = match cli.demo {
+                true => Box::new(demo::DemoBackend),
+                false => Box::new(ros::R2rBackend),
+            };
+            run_tui(config, backend).unwrap_or_else(|e| {
+                eprintln!("lazyros: {}", e);
+                std::process::exit(1);
+            });
+            Ok(())
+        }
+    }
+}
+
+fn run_tui(mut config: config::Config, backend: Box<dyn ros::Backend>) -> io::Result<()> {
+    if config.ui.graphics == config::Graphics::Auto {
+        config.ui.graphics = terminal::detect_graphics();
+    }
+    let mut terminal = terminal::init();
+    if config.ui.mouse {
+        if let Err(e) = terminal::set_mouse_capture(true) {
+            ratatui::restore();
+            return Err(e);
+        }
+    }
 
     let (event_tx, event_rx) = mpsc::channel::<event::Event>();
 
@@ -20,126 +105,43 @@ fn main() -> io::Result<()> {
         handle_input_events(tx_to_input_events);
     });
 
-    let tx_to_background_progress_events = event_tx.clone();
-    thread::spawn(move || {
-        run_ros_thread(tx_to_background_progress_events).unwrap();
-    });
-
-    let mut app = app::App::new();
+    // The app starts the ROS backend itself, so that it can restart it
+    let mut app = app::App::new(config, backend, event_tx);
 
     let app_result = app.run(&mut terminal, event_rx);
 
+    let _ = terminal::set_mouse_capture(false);
     ratatui::restore();
     app_result
 }
 
-fn handle_input_events(tx: mpsc::Sender<event::Event>) {
-    loop {
-        match crossterm::event::read().unwrap() {
-            crossterm::event::Event::Key(key_event) => {
-                tx.send(event::Event::Input(key_event)).unwrap()
-            }
-            crossterm::event::Event::Resize(cols, rows) => {
-                tx.send(event::Event::Resize(cols, rows)).unwrap()
-            }
-            _ => {}
-        }
-    }
-}
-
-#[tokio::main]
-async fn run_ros_thread(tx: mpsc::Sender<event::Event>) -> Result<(), Box<dyn std::error::Error>> {
-    let ctx = r2r::Context::create()?;
-    let node = Arc::new(Mutex::new(r2r::Node::create(ctx, "lazyros", "")?));
-
-    let sub_node = node.clone();
-    let sub_tx = tx.clone();
-    let timer_node = node.clone();
-    let timer_tx = tx.clone();
-
-    tx.send(event::Event::ROSEvent {
-        event: event::ROSEvent::SubscriptionMessage("Subscribing to /topic".to_string()),
-    })?;
-
-    task::spawn(async move { subscribe(sub_node, sub_tx).await.unwrap() });
-    task::spawn(async move { send_topics(timer_node, timer_tx).await.unwrap() });
-
-    let handle = tokio::task::spawn_blocking(move || loop {
-        node.lock()
-            .unwrap()
-            .spin_once(std::time::Duration::from_millis(10));
-        std::thread::sleep(std::time::Duration::from_millis(100));
-    });
-
-    handle.await?;
+fn dump_graph(format: dump::DumpFormat, settle_time: std::time::Duration) -> io::Result<()> {
+    let node = rcl_graph::GraphNode::create(ros::GRAPH_NODE).map_err(io::Error::other)?;
 
-    Ok(())
-}
+    // Discovery is asynchronous, give the other participants time to announce themselves
+    thread::sleep(settle_time);
 
-async fn subscribe(
-    arc_node: Arc<Mutex<r2r::Node>>,
-    tx: mpsc::Sender<event::Event>,
-) -> Result<(), r2r::Error> {
-    let subscriber = arc_node
-        .lock()
-        .unwrap()
-        .subscribe::<r2r::std_msgs::msg::String>("/topic", QosProfile::default())?;
-
-    subscriber
-        .for_each(|msg| {
-            let _ = tx.send(event::Event::ROSEvent {
-                event: event::ROSEvent::SubscriptionMessage(msg.data),
-            });
-            future::ready(())
-        })
-        .await;
+    let snapshot = ros::collect_graph(&node).map_err(io::Error::other)?;
+    println!("{}", dump::format_graph(&snapshot, format)?);
     Ok(())
 }
 
-async fn send_topics(
-    arc_node: Arc<Mutex<r2r::Node>>,
-    tx: mpsc::Sender<event::Event>,
-) -> Result<(), r2r::Error> {
-    let mut timer = arc_node
-        .lock()
-        .unwrap()
-        .create_wall_timer(std::time::Duration::from_secs(1))
-        .unwrap();
-
-    let mut topics: Vec<String> = vec![];
-
+fn handle_input_events(tx: mpsc::Sender<event::Event>) {
     loop {
-        if let Err(_e) = timer.tick().await {
-            // Handle error
-        };
-
-        // Get the latest node value
-        let detected_topic_names = arc_node
-            .lock()
-            .unwrap()
-            .get_topic_names_and_types()
-            .unwrap();
-
-        // find new topics
-        let new_topics: Vec<String> = detected_topic_names
-            .iter()
-            .filter(|(topic, _)| !topics.contains(*topic))
-            .map(|(topic, _)| topic.clone())
-            .collect();
-
-        for topic in &new_topics {
-            topics.push(topic.clone());
-            let new_msg_data = detected_topic_names
-                .get(topic.as_str())
-                .unwrap()
-                .get(0)
-                .unwrap();
-
-            if let Err(_e) = tx.send(event::Event::ROSEvent {
-                event: event::ROSEvent::NewTopic(topic.clone(), new_msg_data.to_string()),
-            }) {
-                // Handle error
+        let (event, stop) = match crossterm::event::read() {
+            Ok(crossterm::event::Event::Key(key_event)) => (event::Event::Input(key_event), false),
+            Ok(crossterm::event::Event::Mouse(mouse_event)) => {
+                (event::Event::Mouse(mouse_event), false)
             }
+            Ok(crossterm::event::Event::Resize(cols, rows)) => {
+                (event::Event::Resize(cols, rows), false)
+            }
+            Ok(_) => continue,
+            Err(e) => (event::Event::InputError(e), true),
+        };
+        // The app is gone once nobody receives anymore
+        if tx.send(event).is_err() || stop {
+            return;
         }
     }
 }
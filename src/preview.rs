@@ -0,0 +1,336 @@
+pub mod image;
+pub mod map;
+pub mod points;
+
+use std::time::{Duration, Instant};
+
+use ::image::{Rgb, RgbImage};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Margin, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Wrap},
+    Frame,
+};
+use serde_json::{Map, Value};
+
+use crate::config::{Action, KeyBindings, Theme};
+
+pub use map::{has_pose, pose, Pose};
+
+use map::Grid;
+use points::{PlotView, Points};
+
+const IMAGE: &str = "sensor_msgs/msg/Image";
+const COMPRESSED_IMAGE: &str = "sensor_msgs/msg/CompressedImage";
+const LASER_SCAN: &str = "sensor_msgs/msg/LaserScan";
+const POINT_CLOUD2: &str = "sensor_msgs/msg/PointCloud2";
+const OCCUPANCY_GRID: &str = "nav_msgs/msg/OccupancyGrid";
+
+/// What a message is drawn as.
+#[derive(Debug)]
+enum Content {
+    Picture(RgbImage),
+    /// Seen from above.
+    Points(Points),
+    Map(Grid),
+}
+
+/// The newest message of an echo drawn as a picture, a plot of its points or a map,
+/// decoded at most `fps` times a second however fast the topic publishes.
+#[derive(Debug)]
+pub struct Preview {
+    msg_type: String,
+    content: Option<Content>,
+    /// Why the newest message could not be drawn.
+    error: Option<String>,
+    /// E.g. `640x480 rgb8`, `720 points` or `384x384 at 0.05 m`.
+    description: String,
+    /// Pictures decoded so far, to tell when the terminal needs to draw again.
+    pub frame: u64,
+    interval: Duration,
+    last_decoded: Option<Instant>,
+    /// The newest message, held back by the frame-rate cap.
+    pending: Option<Value>,
+    plot: PlotView,
+}
+
+impl Preview {
+    /// A preview for messages of `msg_type`, `None` if they have nothing to draw.
+    pub fn new(msg_type: &str, fps: u32) -> Option<Preview> {
+        let drawable = matches!(
+            msg_type,
+            IMAGE | COMPRESSED_IMAGE | LASER_SCAN | POINT_CLOUD2 | OCCUPANCY_GRID
+        );
+        drawable.then(|| Preview {
+            msg_type: msg_type.to_string(),
+            content: None,
+            error: None,
+            description: String::new(),
+            frame: 0,
+            interval: Duration::from_secs(1) / fps,
+            last_decoded: None,
+            pending: None,
+            plot: PlotView::default(),
+        })
+    }
+
+    pub fn update(&mut self, message: Value, now: Instant) {
+        self.pending = Some(message);
+        self.tick(now);
+    }
+
+    /// Decode the newest message once the next frame is due. Returns whether
+    /// the picture changed.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if self
+            .last_decoded
+            .is_some_and(|last| now.duration_since(last) < self.interval)
+        {
+            return false;
+        }
+        let Some(message) = self.pending.take() else {
+            return false;
+        };
+        self.last_decoded = Some(now);
+        self.frame += 1;
+
+        let decoded = match self.msg_type.as_str() {
+            COMPRESSED_IMAGE => image::decode_compressed(&message).map(Content::Picture),
+            LASER_SCAN => points::decode_scan(&message).map(Content::Points),
+            POINT_CLOUD2 => points::decode_cloud(&message).map(Content::Points),
+            OCCUPANCY_GRID => map::decode_grid(&message).map(Content::Map),
+            _ => image::decode_raw(&message).map(Content::Picture),
+        };
+        match decoded {
+            Ok(content) => {
+                self.description = match &content {
+                    Content::Picture(picture) => {
+                        let encoding = ["encoding", "format"]
+                            .iter()
+                            .find_map(|name| message.get(name)?.as_str())
+                            .unwrap_or_default();
+                        let (width, height) = picture.dimensions();
+                        format!("{}x{} {}", width, height, encoding)
+                    }
+                    Content::Points(points) => format!("{} points", points.points.len()),
+                    Content::Map(grid) => {
+                        format!("{}x{} at {} m", grid.width, grid.height, grid.resolution)
+                    }
+                };
+                self.content = Some(content);
+                self.error = None;
+            }
+            Err(error) => self.error = Some(error),
+        }
+        true
+    }
+
+    pub fn picture(&self) -> Option<&RgbImage> {
+        match &self.content {
+            Some(Content::Picture(picture)) => Some(picture),
+            _ => None,
+        }
+    }
+
+    /// Whether the messages are drawn as points or a map, which zoom and pan.
+    pub fn is_plot(&self) -> bool {
+        matches!(
+            self.msg_type.as_str(),
+            LASER_SCAN | POINT_CLOUD2 | OCCUPANCY_GRID
+        )
+    }
+
+    pub fn is_map(&self) -> bool {
+        self.msg_type == OCCUPANCY_GRID
+    }
+
+    /// Zoom, pan or colour a plot, or zoom and pan a map. Returns whether the
+    /// key did one of those.
+    pub fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+    ) -> bool {
+        match &self.content {
+            Some(Content::Points(points)) => {
+                self.plot.handle_key_event(key_event, keys, points.extent)
+            }
+            // Maps have colours of their own
+            Some(Content::Map(_)) if keys.matches(Action::CycleColouring, &key_event) => false,
+            Some(Content::Map(grid)) => self.plot.handle_key_event(key_event, keys, grid.extent()),
+            _ => false,
+        }
+    }
+
+    /// Zoom a plot in by `steps`, or out for negative ones.
+    pub fn zoom_by(&mut self, steps: i32) {
+        self.plot.zoom_by(steps);
+    }
+
+    /// Draw the picture into `area` with half-blocks, or with `graphics` leave
+    /// the cells to the terminal to draw it into, and return them. Maps show
+    /// `pose` on top.
+    pub fn render(
+        &self,
+        block: Block,
+        area: Rect,
+        frame: &mut Frame,
+        theme: &Theme,
+        graphics: bool,
+        pose: Option<Pose>,
+    ) -> Option<Rect> {
+        let description = match &self.content {
+            Some(Content::Points(points)) => {
+                format!("{}, {}", self.description, self.plot.status(points.extent))
+            }
+            Some(Content::Map(_)) => format!("{}, zoom {:.1}x", self.description, self.plot.zoom),
+            _ => self.description.clone(),
+        };
+        let block = match description.is_empty() {
+            true => block,
+            false => block.title(Line::from(format!(" {} ", description)).right_aligned()),
+        };
+        frame.render_widget(block, area);
+        let inner = area.inner(Margin {
+            horizontal: 1,
+            vertical: 1,
+        });
+
+        let text = match (&self.content, &self.error) {
+            (_, Some(error)) => Paragraph::new(format!("Cannot draw the message: {}", error))
+                .style(Style::default().fg(theme.error)),
+            (None, None) => Paragraph::new("Waiting for the first message...")
+                .style(Style::default().fg(theme.text)),
+            (Some(Content::Points(points)), None) => {
+                self.plot.render(points, inner, frame, theme);
+                return None;
+            }
+            (Some(Content::Map(grid)), None) => {
+                map::render(grid, &self.plot, pose, inner, frame.buffer_mut(), theme);
+                return None;
+            }
+            (Some(Content::Picture(picture)), None) if !graphics => {
+                halfblocks(picture, inner, frame.buffer_mut());
+                return None;
+            }
+            (Some(_), None) => {
+                // ratatui must neither clear nor draw over the picture
+                for y in inner.top()..inner.bottom() {
+                    for x in inner.left()..inner.right() {
+                        if let Some(cell) = frame.buffer_mut().cell_mut((x, y)) {
+                            cell.reset();
+                            cell.set_skip(true);
+                        }
+                    }
+                }
+                return Some(inner);
+            }
+        };
+        frame.render_widget(text.wrap(Wrap { trim: false }), inner);
+        None
+    }
+}
+
+/// The message as one line of JSON, with the bytes of its picture or cloud counted
+/// instead of listed.
+pub fn summary(message: &Value) -> String {
+    let Some(fields) = message.as_object() else {
+        return message.to_string();
+    };
+    let fields: Map<String, Value> = fields
+        .iter()
+        .map(|(name, value)| match (name.as_str(), value) {
+            ("data", Value::Array(data)) => (
+                name.clone(),
+                Value::String(format!("<{} bytes>", data.len())),
+            ),
+            _ => (name.clone(), value.clone()),
+        })
+        .collect();
+    Value::Object(fields).to_string()
+}
+
+/// The largest size with the aspect ratio of `size` that fits into `bounds`,
+/// at least one pixel.
+pub fn fit((width, height): (u32, u32), (max_width, max_height): (u32, u32)) -> (u32, u32) {
+    let scale = f64::min(
+        f64::from(max_width) / f64::from(width),
+        f64::from(max_height) / f64::from(height),
+    );
+    let scaled = |size: u32, max: u32| {
+        (f64::from(size) * scale)
+            .floor()
+            .clamp(1.0, f64::from(max.max(1))) as u32
+    };
+    (scaled(width, max_width), scaled(height, max_height))
+}
+
+/// Two pixels per cell, the upper one in the foreground of `▀` and the lower
+/// one in its background. Pixels are about square in most fonts.
+fn halfblocks(picture: &RgbImage, area: Rect, buffer: &mut Buffer) {
+    if area.is_empty() {
+        return;
+    }
+    let (width, height) = fit(
+        picture.dimensions(),
+        (u32::from(area.width), u32::from(area.height) * 2),
+    );
+    let left = area.x + (area.width - width as u16) / 2;
+    let sample = |x: u32, y: u32| {
+        let pixel = picture.get_pixel(x * picture.width() / width, y * picture.height() / height);
+        Color::Rgb(pixel[0], pixel[1], pixel[2])
+    };
+    for y in 0..height.div_ceil(2) {
+        for x in 0..width {
+            let Some(cell) = buffer.cell_mut((left + x as u16, area.y + y as u16)) else {
+                continue;
+            };
+            cell.set_symbol("▀").set_fg(sample(x, 2 * y));
+            match 2 * y + 1 < height {
+                true => cell.set_bg(sample(x, 2 * y + 1)),
+                false => cell.set_bg(Color::Reset),
+            };
+        }
+    }
+}
+
+/// The `data` field, a list of bytes.
+fn bytes(message: &Value) -> Result<Vec<u8>, String> {
+    message
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or("no data in the message")?
+        .iter()
+        .map(|byte| byte.as_u64().and_then(|byte| u8::try_from(byte).ok()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| "data is not a list of bytes".to_string())
+}
+
+/// Blue for 0 over cyan, green and yellow to red for 1.
+fn colormap(t: f64) -> Rgb<u8> {
+    const STOPS: [[f64; 3]; 5] = [
+        [0.0, 0.0, 255.0],
+        [0.0, 255.0, 255.0],
+        [0.0, 255.0, 0.0],
+        [255.0, 255.0, 0.0],
+        [255.0, 0.0, 0.0],
+    ];
+    let position = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let (i, fraction) = (position.floor() as usize, position.fract());
+    let (from, to) = (STOPS[i], STOPS[(i + 1).min(STOPS.len() - 1)]);
+    Rgb(std::array::from_fn(|c| {
+        (from[c] + (to[c] - from[c]) * fraction).round() as u8
+    }))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use serde_json::Value;
+
+    /// The `std_msgs/Header` of the message fixtures.
+    pub fn header(frame_id: &str) -> Value {
+        serde_json::json!({ "stamp": { "sec": 1, "nanosec": 0 }, "frame_id": frame_id })
+    }
+}
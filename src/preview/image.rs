@@ -0,0 +1,293 @@
+use std::{collections::BTreeSet, fmt::Write as _};
+
+use image::{Rgb, RgbImage};
+use serde_json::Value;
+
+/// Base64 characters in one chunk of the kitty graphics protocol.
+const KITTY_CHUNK: usize = 4096;
+
+/// The picture of a `sensor_msgs/Image` in one of the common encodings.
+pub fn decode_raw(message: &Value) -> Result<RgbImage, String> {
+    let field = |name: &str| {
+        message
+            .get(name)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| format!("no {} in the message", name))
+    };
+    let (width, height, step) = (field("width")?, field("height")?, field("step")?);
+    let encoding = message
+        .get("encoding")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let big_endian = field("is_bigendian").unwrap_or(0) != 0;
+    let data = super::bytes(message)?;
+
+    let channels = match encoding {
+        "rgb8" | "bgr8" => 3,
+        "rgba8" | "bgra8" => 4,
+        "mono8" | "8UC1" => 1,
+        "mono16" | "16UC1" => 2,
+        _ => return Err(format!("unsupported encoding '{}'", encoding)),
+    };
+    if width == 0 || height == 0 {
+        return Err("empty image".to_string());
+    }
+    if step < width * channels || (data.len() as u64) < step * height {
+        return Err(format!(
+            "{} bytes are too few for {}x{} {}",
+            data.len(),
+            width,
+            height,
+            encoding
+        ));
+    }
+    let (width, height, step, channels) = (
+        width as u32,
+        height as u32,
+        step as usize,
+        channels as usize,
+    );
+    let pixel = |x: u32, y: u32| {
+        let start = y as usize * step + x as usize * channels;
+        &data[start..start + channels]
+    };
+    let word = |bytes: &[u8]| match big_endian {
+        true => u16::from_be_bytes([bytes[0], bytes[1]]),
+        false => u16::from_le_bytes([bytes[0], bytes[1]]),
+    };
+
+    Ok(match encoding {
+        "rgb8" | "rgba8" => RgbImage::from_fn(width, height, |x, y| {
+            let p = pixel(x, y);
+            Rgb([p[0], p[1], p[2]])
+        }),
+        "bgr8" | "bgra8" => RgbImage::from_fn(width, height, |x, y| {
+            let p = pixel(x, y);
+            Rgb([p[2], p[1], p[0]])
+        }),
+        "mono8" | "8UC1" => RgbImage::from_fn(width, height, |x, y| Rgb([pixel(x, y)[0]; 3])),
+        "mono16" => RgbImage::from_fn(width, height, |x, y| {
+            Rgb([(word(pixel(x, y)) >> 8) as u8; 3])
+        }),
+        // Depth in millimetres, from near to far over the range in view, 0 is no reading
+        _ => {
+            let depths = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+            let valid = depths.map(|(x, y)| word(pixel(x, y))).filter(|d| *d > 0);
+            let (near, far) = valid.fold((u16::MAX, 0), |(near, far), depth| {
+                (near.min(depth), far.max(depth))
+            });
+            RgbImage::from_fn(width, height, |x, y| match word(pixel(x, y)) {
+                0 => Rgb([0, 0, 0]),
+                depth => super::colormap(f64::from(depth - near) / f64::from((far - near).max(1))),
+            })
+        }
+    })
+}
+
+/// The picture of a `sensor_msgs/CompressedImage`, JPEG or PNG whatever its
+/// `format` says.
+pub fn decode_compressed(message: &Value) -> Result<RgbImage, String> {
+    image::load_from_memory(&super::bytes(message)?)
+        .map(|picture| picture.to_rgb8())
+        .map_err(|e| e.to_string())
+}
+
+/// Transmit and place `picture` over `columns` by `rows` cells at the cursor,
+/// with the kitty graphics protocol as 24 bit RGB in chunks.
+pub fn kitty(picture: &RgbImage, columns: u16, rows: u16) -> String {
+    let data = base64(picture.as_raw());
+    let chunks: Vec<&str> = data
+        .as_bytes()
+        .chunks(KITTY_CHUNK)
+        .map(|chunk| std::str::from_utf8(chunk).expect("base64 is ASCII"))
+        .collect();
+    let mut sequence = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        match i {
+            0 => write!(
+                sequence,
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},q=2,C=1,m={};{}\x1b\\",
+                picture.width(),
+                picture.height(),
+                columns,
+                rows,
+                more,
+                chunk
+            ),
+            _ => write!(sequence, "\x1b_Gm={};{}\x1b\\", more, chunk),
+        }
+        .expect("writing to a String");
+    }
+    sequence
+}
+
+/// `picture` as a sixel image at the cursor, in the colours of a 6×6×6 cube.
+pub fn sixel(picture: &RgbImage) -> String {
+    let level = |channel: u8| (u16::from(channel) * 5 + 127) / 255;
+    let colours: Vec<u16> = picture
+        .pixels()
+        .map(|pixel| level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2]))
+        .collect();
+    let (width, height) = (picture.width() as usize, picture.height() as usize);
+
+    let mut sequence = format!("\x1bPq\"1;1;{};{}", width, height);
+    for colour in colours.iter().collect::<BTreeSet<_>>() {
+        let percent = |level: u16| level * 20;
+        let _ = write!(
+            sequence,
+            "#{};2;{};{};{}",
+            colour,
+            percent(colour / 36),
+            percent(colour / 6 % 6),
+            percent(colour % 6)
+        );
+    }
+    // Bands of six rows, drawn once per colour with a carriage return between
+    for band in (0..height).step_by(6) {
+        let rows = band..(band + 6).min(height);
+        let band_colours: BTreeSet<u16> = rows
+            .clone()
+            .flat_map(|y| colours[y * width..(y + 1) * width].iter().copied())
+            .collect();
+        for (i, colour) in band_colours.iter().enumerate() {
+            if i > 0 {
+                sequence.push('$');
+            }
+            let _ = write!(sequence, "#{}", colour);
+            let columns = (0..width).map(|x| {
+                let bits = rows
+                    .clone()
+                    .filter(|y| colours[y * width + x] == *colour)
+                    .fold(0, |bits, y| bits | 1 << (y - band));
+                (63 + bits) as u8 as char
+            });
+            push_runs(&mut sequence, columns);
+        }
+        sequence.push('-');
+    }
+    sequence.push_str("\x1b\\");
+    sequence
+}
+
+/// Sixel characters, with runs of four or more repeated as `!count`.
+fn push_runs(sequence: &mut String, characters: impl Iterator<Item = char>) {
+    let mut run: Option<(char, usize)> = None;
+    let flush = |sequence: &mut String, run: Option<(char, usize)>| match run {
+        Some((c, count)) if count >= 4 => {
+            let _ = write!(sequence, "!{}{}", count, c);
+        }
+        Some((c, count)) => sequence.extend(std::iter::repeat_n(c, count)),
+        None => {}
+    };
+    for c in characters {
+        run = match run {
+            Some((previous, count)) if previous == c => Some((c, count + 1)),
+            _ => {
+                flush(sequence, run);
+                Some((c, 1))
+            }
+        };
+    }
+    flush(sequence, run);
+}
+
+/// Standard base64 with padding, as OSC 52 and kitty want it.
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | u32::from(byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::io::Cursor;
+
+    use image::ImageFormat;
+
+    use super::*;
+    use crate::preview::tests::header;
+
+    /// A `sensor_msgs/Image` without padding at the end of its rows.
+    pub fn image(width: u32, height: u32, encoding: &str, data: Vec<u8>) -> Value {
+        serde_json::json!({
+            "header": header("camera"),
+            "height": height,
+            "width": width,
+            "encoding": encoding,
+            "is_bigendian": 0,
+            "step": data.len() as u32 / height,
+            "data": data,
+        })
+    }
+
+    fn pixels(picture: Result<RgbImage, String>) -> Result<Vec<[u8; 3]>, String> {
+        picture.map(|picture| picture.pixels().map(|pixel| pixel.0).collect())
+    }
+
+    #[test]
+    fn images_are_decoded_in_the_common_encodings() {
+        let decoded = |message: Value| pixels(decode_raw(&message));
+        let rgb = image(2, 1, "rgb8", vec![255, 0, 0, 0, 0, 255]);
+        assert_eq!(decoded(rgb), Ok(vec![[255, 0, 0], [0, 0, 255]]));
+        let bgr = image(2, 1, "bgr8", vec![255, 0, 0, 0, 0, 255]);
+        assert_eq!(decoded(bgr), Ok(vec![[0, 0, 255], [255, 0, 0]]));
+        let mono = image(2, 1, "mono8", vec![0, 200]);
+        assert_eq!(decoded(mono), Ok(vec![[0, 0, 0], [200, 200, 200]]));
+
+        // Depth runs from blue when near to red when far, without a reading is black
+        let depth = image(3, 1, "16UC1", vec![0xe8, 0x03, 0x00, 0x00, 0xd0, 0x07]);
+        assert_eq!(
+            decoded(depth),
+            Ok(vec![[0, 0, 255], [0, 0, 0], [255, 0, 0]])
+        );
+
+        // Rows may be padded
+        let mut padded = image(1, 2, "mono8", vec![10, 0, 20, 0]);
+        padded["step"] = 2.into();
+        assert_eq!(decoded(padded), Ok(vec![[10; 3], [20; 3]]));
+
+        let mut png = vec![];
+        RgbImage::from_pixel(2, 1, Rgb([1, 2, 3]))
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        let compressed = serde_json::json!({ "format": "png", "data": png });
+        assert_eq!(
+            pixels(decode_compressed(&compressed)),
+            Ok(vec![[1, 2, 3]; 2])
+        );
+    }
+
+    #[test]
+    fn pictures_are_encoded_for_kitty_and_sixel() {
+        let pixels = RgbImage::from_raw(1, 1, vec![1, 2, 3]).unwrap();
+        assert_eq!(
+            kitty(&pixels, 4, 2),
+            "\x1b_Ga=T,f=24,s=1,v=1,c=4,r=2,q=2,C=1,m=0;AQID\x1b\\"
+        );
+        // Large pictures go in chunks
+        let wide = RgbImage::new(2000, 1);
+        let chunks = kitty(&wide, 4, 2);
+        assert!(chunks.starts_with("\x1b_Ga=T,f=24,s=2000,v=1,c=4,r=2,q=2,C=1,m=1;AAAA"));
+        assert!(chunks.contains("\x1b\\\x1b_Gm=0;AAAA"));
+
+        // Per six rows every colour in turn, with runs counted
+        let mut picture = RgbImage::new(6, 2);
+        picture.put_pixel(0, 0, Rgb([255, 0, 0]));
+        assert_eq!(
+            sixel(&picture),
+            "\x1bPq\"1;1;6;2#0;2;0;0;0#180;2;100;0;0#0A!5B$#180@!5?-\x1b\\"
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::f64::consts::FRAC_PI_4;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+};
+use serde_json::Value;
+
+use super::points::PlotView;
+use crate::config::Theme;
+
+/// Free cells are light and occupied ones black like in RViz, the costs between in grey.
+const FREE: u8 = 230;
+const UNKNOWN: Color = Color::Rgb(100, 120, 130);
+
+/// Cells looked at along each side of a pixel that covers many, so that thin
+/// walls survive the downsampling.
+const SAMPLES: usize = 4;
+
+/// Arrows for the heading of the pose, counter-clockwise from east.
+const ARROWS: [&str; 8] = ["→", "↗", "↑", "↖", "←", "↙", "↓", "↘"];
+
+/// The message types `pose` finds a pose in.
+const POSE_TYPES: [&str; 3] = [
+    "geometry_msgs/msg/PoseStamped",
+    "geometry_msgs/msg/PoseWithCovarianceStamped",
+    "nav_msgs/msg/Odometry",
+];
+
+/// The cells of a `nav_msgs/OccupancyGrid`, row by row from the origin.
+#[derive(Debug)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    /// Metres per cell.
+    pub resolution: f64,
+    /// The corner of the first cell in the map frame. The rotation of the
+    /// origin is left out, maps hardly ever have one.
+    origin: (f64, f64),
+    /// Occupancy from 0 to 100, -1 for unknown.
+    cells: Vec<i8>,
+}
+
+impl Grid {
+    /// Half the longer side, in metres.
+    pub fn extent(&self) -> f64 {
+        self.width.max(self.height) as f64 * self.resolution / 2.0
+    }
+
+    /// The occupancy of the cell at `x`, `y` in the map frame, `None` off the map.
+    fn at(&self, x: f64, y: f64) -> Option<i8> {
+        let column = ((x - self.origin.0) / self.resolution).floor();
+        let row = ((y - self.origin.1) / self.resolution).floor();
+        let inside =
+            (0.0..self.width as f64).contains(&column) && (0.0..self.height as f64).contains(&row);
+        inside.then(|| self.cells[row as usize * self.width + column as usize])
+    }
+}
+
+/// The cells of a `nav_msgs/OccupancyGrid`.
+pub fn decode_grid(message: &Value) -> Result<Grid, String> {
+    let info = message.get("info").ok_or("no info in the message")?;
+    let number = |name: &str| {
+        info.get(name)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| format!("no {} in the message", name))
+    };
+    let (width, height) = (number("width")? as usize, number("height")? as usize);
+    let resolution = number("resolution")?;
+    if width == 0 || height == 0 || resolution <= 0.0 || !resolution.is_finite() {
+        return Err("empty map".to_string());
+    }
+    let origin = |axis: &str| {
+        info.pointer(&format!("/origin/position/{}", axis))
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0)
+    };
+    let cells: Vec<i8> = message
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or("no data in the message")?
+        .iter()
+        .map(|cell| cell.as_i64().and_then(|cell| i8::try_from(cell).ok()))
+        .collect::<Option<_>>()
+        .ok_or("data is not a list of cells")?;
+    if width
+        .checked_mul(height)
+        .is_none_or(|size| cells.len() < size)
+    {
+        return Err(format!(
+            "{} cells are too few for {}x{}",
+            cells.len(),
+            width,
+            height
+        ));
+    }
+    Ok(Grid {
+        width,
+        height,
+        resolution,
+        origin: (origin("x"), origin("y")),
+        cells,
+    })
+}
+
+/// Where a robot is on the map and where it is heading, counter-clockwise from `x`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pose {
+    pub x: f64,
+    pub y: f64,
+    pub yaw: f64,
+}
+
+/// Whether messages of `msg_type` have a pose to overlay on maps.
+pub fn has_pose(msg_type: &str) -> bool {
+    POSE_TYPES.contains(&msg_type)
+}
+
+/// The pose of a `PoseStamped`, `PoseWithCovarianceStamped` or `Odometry`.
+pub fn pose(message: &Value) -> Option<Pose> {
+    let pose = ["/pose/pose", "/pose"]
+        .into_iter()
+        .find_map(|pointer| message.pointer(pointer)?.get("position").map(|_| pointer))?;
+    let number = |field: &str| message.pointer(&format!("{}/{}", pose, field))?.as_f64();
+    let (qx, qy, qz, qw) = (
+        number("orientation/x")?,
+        number("orientation/y")?,
+        number("orientation/z")?,
+        number("orientation/w")?,
+    );
+    Some(Pose {
+        x: number("position/x")?,
+        y: number("position/y")?,
+        yaw: f64::atan2(2.0 * (qw * qz + qx * qy), 1.0 - 2.0 * (qy * qy + qz * qz)),
+    })
+}
+
+/// Draw the part of `grid` in `view` with half-blocks, north up, and `pose`
+/// as an arrow on top.
+pub fn render(
+    grid: &Grid,
+    view: &PlotView,
+    pose: Option<Pose>,
+    area: Rect,
+    buffer: &mut Buffer,
+    theme: &Theme,
+) {
+    if area.is_empty() {
+        return;
+    }
+    // Pixels are about square, two to a cell
+    let (columns, rows) = (f64::from(area.width), f64::from(area.height) * 2.0);
+    let (map_width, map_height) = (
+        grid.width as f64 * grid.resolution,
+        grid.height as f64 * grid.resolution,
+    );
+    let metres = f64::max(map_width / columns, map_height / rows) / view.zoom;
+    // The view pans up and left of the middle of the map
+    let center = (
+        grid.origin.0 + map_width / 2.0 - view.center.1,
+        grid.origin.1 + map_height / 2.0 + view.center.0,
+    );
+
+    let samples = ((metres / grid.resolution).ceil() as usize).clamp(1, SAMPLES);
+    let pixel = |column: u16, row: u16| {
+        let x = center.0 + (f64::from(column) + 0.5 - columns / 2.0) * metres;
+        let y = center.1 + (rows / 2.0 - f64::from(row) - 0.5) * metres;
+        let offset = |i: usize| ((i as f64 + 0.5) / samples as f64 - 0.5) * metres;
+        // The most occupied cell under the pixel, unknown only if none is known
+        let cells = (0..samples)
+            .flat_map(|i| (0..samples).map(move |j| (i, j)))
+            .filter_map(|(i, j)| grid.at(x + offset(i), y + offset(j)));
+        match cells.max() {
+            None => Color::Reset,
+            Some(cell @ 0..=100) => {
+                let shade = FREE - (u16::from(FREE) * cell as u16 / 100) as u8;
+                Color::Rgb(shade, shade, shade)
+            }
+            Some(_) => UNKNOWN,
+        }
+    };
+    for y in 0..area.height {
+        for x in 0..area.width {
+            if let Some(cell) = buffer.cell_mut((area.x + x, area.y + y)) {
+                cell.set_symbol("▀")
+                    .set_fg(pixel(x, 2 * y))
+                    .set_bg(pixel(x, 2 * y + 1));
+            }
+        }
+    }
+
+    let Some(pose) = pose else {
+        return;
+    };
+    let column = ((pose.x - center.0) / metres + columns / 2.0).floor();
+    let row = ((rows / 2.0 - (pose.y - center.1) / metres) / 2.0).floor();
+    if !(0.0..columns).contains(&column) || !(0.0..rows / 2.0).contains(&row) {
+        return;
+    }
+    let heading = (pose.yaw / FRAC_PI_4).round().rem_euclid(8.0) as usize;
+    if let Some(cell) = buffer.cell_mut((area.x + column as u16, area.y + row as u16)) {
+        let style = Style::default()
+            .fg(theme.error)
+            .add_modifier(Modifier::BOLD);
+        cell.set_symbol(ARROWS[heading]).set_style(style);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::preview::tests::header;
+
+    /// A `nav_msgs/OccupancyGrid` of one metre cells from the origin.
+    pub fn grid(width: usize, height: usize, data: &[i8]) -> Value {
+        serde_json::json!({
+            "header": header("map"),
+            "info": {
+                "resolution": 1.0,
+                "width": width,
+                "height": height,
+                "origin": { "position": { "x": 0.0, "y": 0.0, "z": 0.0 } },
+            },
+            "data": data,
+        })
+    }
+
+    /// A `geometry_msgs/PoseWithCovarianceStamped` heading `yaw` from `x`.
+    pub fn pose_with_covariance(x: f64, y: f64, yaw: f64) -> Value {
+        serde_json::json!({
+            "header": header("map"),
+            "pose": {
+                "pose": {
+                    "position": { "x": x, "y": y, "z": 0.0 },
+                    "orientation": { "x": 0.0, "y": 0.0, "z": (yaw / 2.0).sin(), "w": (yaw / 2.0).cos() },
+                },
+                "covariance": vec![0.0; 36],
+            },
+        })
+    }
+
+    #[test]
+    fn grids_are_decoded_from_the_origin_up() {
+        let decoded = decode_grid(&grid(2, 2, &[0, 100, -1, 50])).unwrap();
+        assert_eq!((decoded.width, decoded.height), (2, 2));
+        assert_eq!(decoded.extent(), 1.0);
+        assert_eq!(decoded.at(0.5, 0.5), Some(0));
+        assert_eq!(decoded.at(1.5, 0.5), Some(100));
+        assert_eq!(decoded.at(0.5, 1.5), Some(-1));
+        assert_eq!(decoded.at(2.5, 0.5), None);
+
+        let error = |message: Value| decode_grid(&message).unwrap_err();
+        assert_eq!(error(grid(0, 2, &[])), "empty map");
+        assert_eq!(error(grid(2, 2, &[0; 3])), "3 cells are too few for 2x2");
+        assert_eq!(
+            error(grid(usize::MAX, 2, &[0])),
+            format!("1 cells are too few for {}x2", usize::MAX)
+        );
+        let mut not_cells = grid(1, 1, &[0]);
+        not_cells["data"] = serde_json::json!([200]);
+        assert_eq!(error(not_cells), "data is not a list of cells");
+    }
+
+    #[test]
+    fn poses_are_found_in_odometry_and_pose_messages() {
+        let with_covariance = pose(&pose_with_covariance(0.5, 1.5, 0.0)).unwrap();
+        assert_eq!(
+            with_covariance,
+            Pose {
+                x: 0.5,
+                y: 1.5,
+                yaw: 0.0
+            }
+        );
+
+        let odometry = serde_json::json!({
+            "header": header("odom"),
+            "child_frame_id": "base_link",
+            "pose": { "pose": { "position": { "x": 1.0, "y": 2.0, "z": 0.0 },
+                                "orientation": { "x": 0.0, "y": 0.0, "z": 0.0, "w": 1.0 } } },
+        });
+        assert_eq!(
+            pose(&odometry),
+            Some(Pose {
+                x: 1.0,
+                y: 2.0,
+                yaw: 0.0
+            })
+        );
+
+        // Turned half way round
+        let pose_stamped = serde_json::json!({
+            "header": header("map"),
+            "pose": { "position": { "x": 3.0, "y": 4.0, "z": 0.0 },
+                      "orientation": { "x": 0.0, "y": 0.0, "z": 1.0, "w": 0.0 } },
+        });
+        let turned = pose(&pose_stamped).unwrap();
+        assert_eq!((turned.x, turned.y), (3.0, 4.0));
+        assert!((turned.yaw - std::f64::consts::PI).abs() < 1e-9);
+
+        assert_eq!(pose(&serde_json::json!({ "data": 1 })), None);
+    }
+}
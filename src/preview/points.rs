@@ -0,0 +1,493 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    text::Line,
+    widgets::canvas::{Canvas, Circle, Points as CanvasPoints},
+    Frame,
+};
+use serde_json::Value;
+
+use crate::config::{Action, KeyBindings, Theme};
+
+/// Clouds are thinned out to about this many points, more do not show in braille anyway.
+const MAX_POINTS: usize = 20_000;
+
+/// Factor of one zoom step.
+const ZOOM_STEP: f64 = 1.5;
+
+/// Share of the visible height one pan step moves.
+const PAN_STEP: f64 = 0.2;
+
+/// Colour buckets between the lowest and the highest value.
+const SHADES: usize = 8;
+
+/// A point in metres in the frame of the message, `x` forward and `y` left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub intensity: Option<f64>,
+}
+
+/// The points of a scan or a cloud.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Points {
+    pub points: Vec<Point>,
+    /// Distance from the sensor to the edge of the plot at zoom 1.
+    pub extent: f64,
+}
+
+/// The points hit by a `sensor_msgs/LaserScan`, leaving out the ranges outside
+/// `range_min..=range_max`.
+pub fn decode_scan(message: &Value) -> Result<Points, String> {
+    let number = |name: &str| {
+        message
+            .get(name)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| format!("no {} in the message", name))
+    };
+    let (angle_min, increment) = (number("angle_min")?, number("angle_increment")?);
+    let (range_min, range_max) = (number("range_min")?, number("range_max")?);
+    let ranges = message
+        .get("ranges")
+        .and_then(Value::as_array)
+        .ok_or("no ranges in the message")?;
+    let intensities = message.get("intensities").and_then(Value::as_array);
+
+    // Ranges that are not a number come as null
+    let points: Vec<Point> = ranges
+        .iter()
+        .enumerate()
+        .filter_map(|(i, range)| {
+            let range = range
+                .as_f64()
+                .filter(|r| (range_min..=range_max).contains(r))?;
+            let angle = angle_min + increment * i as f64;
+            Some(Point {
+                x: range * angle.cos(),
+                y: range * angle.sin(),
+                z: 0.0,
+                intensity: intensities.and_then(|values| values.get(i)?.as_f64()),
+            })
+        })
+        .collect();
+    let extent = match range_max.is_finite() && range_max > 0.0 {
+        true => range_max,
+        false => farthest(&points),
+    };
+    Ok(Points { points, extent })
+}
+
+/// The points of a `sensor_msgs/PointCloud2` with `x`, `y` and optionally `z`
+/// and `intensity` fields, leaving out the ones that are not a number.
+pub fn decode_cloud(message: &Value) -> Result<Points, String> {
+    let number = |name: &str| {
+        message
+            .get(name)
+            .and_then(Value::as_u64)
+            .ok_or_else(|| format!("no {} in the message", name))
+    };
+    let (width, height) = (number("width")?, number("height")?);
+    let point_step = number("point_step")?;
+    let big_endian = message
+        .get("is_bigendian")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let fields = message
+        .get("fields")
+        .and_then(Value::as_array)
+        .ok_or("no fields in the message")?;
+    let field = |name: &str| {
+        let field = fields.iter().find(|field| field["name"] == name)?;
+        Some((
+            field["offset"].as_u64()? as usize,
+            field["datatype"].as_u64()?,
+        ))
+    };
+    let (Some(x), Some(y)) = (field("x"), field("y")) else {
+        return Err("no x and y fields in the cloud".to_string());
+    };
+    let (z, intensity) = (field("z"), field("intensity"));
+
+    let data = super::bytes(message)?;
+    // Sizes of malformed messages may not even fit into a number
+    let count = width.checked_mul(height).filter(|count| {
+        count
+            .checked_mul(point_step)
+            .is_some_and(|size| size <= data.len() as u64)
+    });
+    let Some(count) = count else {
+        return Err(format!(
+            "{} bytes are too few for {} points",
+            data.len(),
+            u128::from(width) * u128::from(height)
+        ));
+    };
+    let (count, point_step) = (count as usize, point_step as usize);
+    let read = |point: usize, (offset, datatype): (usize, u64)| {
+        read_number(
+            data.get((point * point_step).checked_add(offset)?..)?,
+            datatype,
+            big_endian,
+        )
+    };
+
+    let stride = count.div_ceil(MAX_POINTS).max(1);
+    let points: Vec<Point> = (0..count)
+        .step_by(stride)
+        .filter_map(|i| {
+            let point = Point {
+                x: read(i, x)?,
+                y: read(i, y)?,
+                z: z.and_then(|z| read(i, z)).unwrap_or(0.0),
+                intensity: intensity.and_then(|intensity| read(i, intensity)),
+            };
+            [point.x, point.y, point.z]
+                .iter()
+                .all(|value| value.is_finite())
+                .then_some(point)
+        })
+        .collect();
+    Ok(Points {
+        extent: farthest(&points),
+        points,
+    })
+}
+
+/// A number of one of the `sensor_msgs/PointField` datatypes at the start of `bytes`.
+fn read_number(bytes: &[u8], datatype: u64, big_endian: bool) -> Option<f64> {
+    macro_rules! read {
+        ($type:ty) => {{
+            let bytes = bytes.get(..size_of::<$type>())?.try_into().ok()?;
+            f64::from(match big_endian {
+                true => <$type>::from_be_bytes(bytes),
+                false => <$type>::from_le_bytes(bytes),
+            })
+        }};
+    }
+    Some(match datatype {
+        1 => read!(i8),
+        2 => read!(u8),
+        3 => read!(i16),
+        4 => read!(u16),
+        5 => read!(i32),
+        6 => read!(u32),
+        7 => read!(f32),
+        8 => read!(f64),
+        _ => return None,
+    })
+}
+
+/// Distance to the farthest point on the ground, at least a metre.
+fn farthest(points: &[Point]) -> f64 {
+    points
+        .iter()
+        .map(|point| point.x.hypot(point.y))
+        .fold(1.0, f64::max)
+}
+
+/// What the colour of the points stands for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Colouring {
+    #[default]
+    Intensity,
+    Height,
+    None,
+}
+
+/// Zoom, pan and colouring of a plot or a map.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotView {
+    pub zoom: f64,
+    /// How far the middle of the plot lies up and left of the sensor, or of
+    /// the middle of a map.
+    pub center: (f64, f64),
+    pub colouring: Colouring,
+}
+
+impl Default for PlotView {
+    fn default() -> Self {
+        PlotView {
+            zoom: 1.0,
+            center: (0.0, 0.0),
+            colouring: Colouring::default(),
+        }
+    }
+}
+
+impl PlotView {
+    /// Zoom, pan or colour the plot. Returns whether the key did one of those.
+    pub fn handle_key_event(
+        &mut self,
+        key_event: crossterm::event::KeyEvent,
+        keys: &KeyBindings,
+        extent: f64,
+    ) -> bool {
+        // Up is forward, `x`, and left is `y`
+        let step = PAN_STEP * extent / self.zoom;
+        let pressed = |actions: [Action; 2]| {
+            actions
+                .into_iter()
+                .any(|action| keys.matches(action, &key_event))
+        };
+        if keys.matches(Action::ZoomIn, &key_event) {
+            self.zoom_by(1);
+        } else if keys.matches(Action::ZoomOut, &key_event) {
+            self.zoom_by(-1);
+        } else if keys.matches(Action::ResetZoom, &key_event) {
+            (self.zoom, self.center) = (1.0, (0.0, 0.0));
+        } else if keys.matches(Action::CycleColouring, &key_event) {
+            self.colouring = match self.colouring {
+                Colouring::Intensity => Colouring::Height,
+                Colouring::Height => Colouring::None,
+                Colouring::None => Colouring::Intensity,
+            };
+        } else if pressed([Action::PanUp, Action::Up]) {
+            self.center.0 += step;
+        } else if pressed([Action::PanDown, Action::Down]) {
+            self.center.0 -= step;
+        } else if pressed([Action::PanLeft, Action::Left]) {
+            self.center.1 += step;
+        } else if pressed([Action::PanRight, Action::Right]) {
+            self.center.1 -= step;
+        } else {
+            return false;
+        }
+        true
+    }
+
+    /// Zoom in by `steps`, or out for negative ones.
+    pub fn zoom_by(&mut self, steps: i32) {
+        self.zoom = (self.zoom * ZOOM_STEP.powi(steps)).clamp(0.01, 1000.0);
+    }
+
+    /// E.g. `rings 2 m, by intensity`.
+    pub fn status(&self, extent: f64) -> String {
+        let colouring = match self.colouring {
+            Colouring::Intensity => "by intensity",
+            Colouring::Height => "by height",
+            Colouring::None => "plain",
+        };
+        format!(
+            "rings {} m, {}",
+            ring_spacing(extent / self.zoom),
+            colouring
+        )
+    }
+
+    /// Draw `points` from above with braille dots, the sensor at the origin
+    /// with range rings around it.
+    pub fn render(&self, points: &Points, area: Rect, frame: &mut Frame, theme: &Theme) {
+        if area.is_empty() {
+            return;
+        }
+        // Braille dots are about square, two across and four down a cell
+        let half_height = points.extent / self.zoom;
+        let half_width = half_height * f64::from(area.width) / (f64::from(area.height) * 2.0);
+        // The canvas has `x` to the right and `y` up, that is `-y` and `x` of the sensor
+        let (right, up) = (-self.center.1, self.center.0);
+        let spacing = ring_spacing(half_height);
+        let farthest_corner = right.abs().hypot(up.abs()) + half_width.hypot(half_height);
+        let shades = self.shades(&points.points, theme);
+
+        let canvas = Canvas::default()
+            .marker(Marker::Braille)
+            .x_bounds([right - half_width, right + half_width])
+            .y_bounds([up - half_height, up + half_height])
+            .paint(|ctx| {
+                let rings = (farthest_corner / spacing).ceil() as usize;
+                for ring in 1..=rings.min(100) {
+                    ctx.draw(&Circle {
+                        x: 0.0,
+                        y: 0.0,
+                        radius: spacing * ring as f64,
+                        color: theme.border,
+                    });
+                }
+                ctx.layer();
+                for (coords, color) in &shades {
+                    ctx.draw(&CanvasPoints {
+                        coords,
+                        color: *color,
+                    });
+                }
+                ctx.print(
+                    0.0,
+                    0.0,
+                    Line::styled("+", Style::default().fg(theme.header)),
+                );
+                for ring in 1..=rings.min(100) {
+                    let radius = spacing * ring as f64;
+                    ctx.print(
+                        0.0,
+                        radius,
+                        Line::styled(format!("{} m", radius), Style::default().fg(theme.border)),
+                    );
+                }
+            });
+        frame.render_widget(canvas, area);
+    }
+
+    /// The points on the canvas, grouped by colour.
+    fn shades(&self, points: &[Point], theme: &Theme) -> Vec<(Vec<(f64, f64)>, Color)> {
+        let value = |point: &Point| match self.colouring {
+            Colouring::Intensity => point.intensity,
+            Colouring::Height => Some(point.z),
+            Colouring::None => None,
+        };
+        let (low, high) = points
+            .iter()
+            .filter_map(value)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), v| {
+                (low.min(v), high.max(v))
+            });
+
+        let mut shades = vec![vec![]; SHADES + 1];
+        for point in points {
+            // Points without a value go last, in the text colour
+            let shade = match value(point) {
+                Some(v) if high > low => ((v - low) / (high - low) * (SHADES - 1) as f64) as usize,
+                Some(_) => 0,
+                None => SHADES,
+            };
+            shades[shade.min(SHADES)].push((-point.y, point.x));
+        }
+        shades
+            .into_iter()
+            .enumerate()
+            .map(|(shade, coords)| {
+                let color = match shade {
+                    SHADES => theme.text,
+                    _ => {
+                        let [r, g, b] = super::colormap(shade as f64 / (SHADES - 1) as f64).0;
+                        Color::Rgb(r, g, b)
+                    }
+                };
+                (coords, color)
+            })
+            .filter(|(coords, _)| !coords.is_empty())
+            .collect()
+    }
+}
+
+/// A round distance between range rings, about a third of `half_height`.
+fn ring_spacing(half_height: f64) -> f64 {
+    let rough = half_height / 3.0;
+    let magnitude = 10f64.powf(rough.log10().floor());
+    let spacing = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|spacing| *spacing >= rough)
+        .unwrap_or(10.0 * magnitude);
+    // Rounded to the digits shown, `0.30000000000000004` is `0.3`
+    (spacing * 1e6).round() / 1e6
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::preview::tests::header;
+
+    /// A `sensor_msgs/LaserScan` of three beams at right angles, from ahead to behind.
+    pub fn scan(ranges: Value) -> Value {
+        serde_json::json!({
+            "header": header("laser"),
+            "angle_min": 0.0,
+            "angle_max": std::f64::consts::PI,
+            "angle_increment": std::f64::consts::FRAC_PI_2,
+            "range_min": 0.1,
+            "range_max": 10.0,
+            "ranges": ranges,
+            "intensities": [5.0, 6.0, 7.0],
+        })
+    }
+
+    /// A cloud of points with `x`, `y`, `z` as `f32` and `intensity` as `u16`.
+    pub fn cloud(points: &[[f32; 3]], big_endian: bool) -> Value {
+        let field = |name: &str, offset: u32, datatype: u8| serde_json::json!({ "name": name, "offset": offset, "datatype": datatype, "count": 1 });
+        let mut data = vec![];
+        for (i, point) in points.iter().enumerate() {
+            for value in point {
+                data.extend(match big_endian {
+                    true => value.to_be_bytes(),
+                    false => value.to_le_bytes(),
+                });
+            }
+            data.extend(match big_endian {
+                true => (i as u16 * 100).to_be_bytes(),
+                false => (i as u16 * 100).to_le_bytes(),
+            });
+        }
+        serde_json::json!({
+            "header": header("lidar"),
+            "height": 1,
+            "width": points.len(),
+            "fields": [field("x", 0, 7), field("y", 4, 7), field("z", 8, 7), field("intensity", 12, 4)],
+            "is_bigendian": big_endian,
+            "point_step": 14,
+            "row_step": 14 * points.len(),
+            "data": data,
+            "is_dense": false,
+        })
+    }
+
+    #[test]
+    fn scans_and_clouds_are_decoded_into_points() {
+        // Null is not a number, 20 m lies beyond the range of the sensor
+        let scan = decode_scan(&scan(serde_json::json!([1.0, null, 20.0])));
+        let point = Point {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+            intensity: Some(5.0),
+        };
+        assert_eq!(
+            scan,
+            Ok(Points {
+                points: vec![point],
+                extent: 10.0,
+            })
+        );
+
+        for big_endian in [false, true] {
+            let points = [[1.0, 2.0, 3.0], [f32::NAN, 0.0, 0.0], [-4.0, 0.5, -1.0]];
+            let decoded = decode_cloud(&cloud(&points, big_endian)).unwrap();
+            assert_eq!(
+                decoded.points,
+                [
+                    Point {
+                        x: 1.0,
+                        y: 2.0,
+                        z: 3.0,
+                        intensity: Some(0.0),
+                    },
+                    Point {
+                        x: -4.0,
+                        y: 0.5,
+                        z: -1.0,
+                        intensity: Some(200.0),
+                    },
+                ]
+            );
+        }
+
+        let mut short = cloud(&[[1.0, 2.0, 3.0]], false);
+        short["width"] = 2.into();
+        assert!(decode_cloud(&short)
+            .unwrap_err()
+            .contains("14 bytes are too few for 2 points"));
+        // Sizes too large to compute are too large for the data as well
+        short["width"] = u64::MAX.into();
+        short["height"] = 2.into();
+        assert!(decode_cloud(&short)
+            .unwrap_err()
+            .contains("14 bytes are too few"));
+        short["width"] = (u64::MAX / 2).into();
+        short["height"] = 1.into();
+        assert!(decode_cloud(&short)
+            .unwrap_err()
+            .contains("14 bytes are too few"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    ptr,
+};
+
+use r2r::QosProfile;
+use r2r_rcl as rcl;
+
+/// Signature shared by `rcl_get_publishers_info_by_topic` and
+/// `rcl_get_subscriptions_info_by_topic`.
+type EndpointQuery = unsafe extern "C" fn(
+    *const rcl::rcl_node_t,
+    *mut rcl::rcutils_allocator_t,
+    *const c_char,
+    bool,
+    *mut rcl::rcl_topic_endpoint_info_array_t,
+) -> rcl::rcl_ret_t;
+
+/// A publisher or subscription of a topic.
+pub struct Endpoint {
+    /// Fully qualified name of the node owning the endpoint.
+    pub node_name: String,
+    pub qos: QosProfile,
+}
+
+/// An rcl node for the graph queries r2r does not wrap: the subscriptions of a
+/// topic and the services.
+///
+/// r2r keeps the handles of its nodes private, so this node lives in an rcl
+/// context of its own. Other tools discover it like any node, so give it a
+/// name starting with `_`, which `ros2 node list` and lazyros hide by default.
+/// It has no endpoints and does not log to `/rosout`.
+pub struct GraphNode {
+    context: Box<rcl::rcl_context_t>,
+    node: Box<rcl::rcl_node_t>,
+}
+
+impl GraphNode {
+    pub fn create(name: &str) -> Result<GraphNode, r2r::Error> {
+        let name = CString::new(name).map_err(|_| r2r::Error::RCL_RET_INVALID_ARGUMENT)?;
+        let namespace = CString::default();
+
+        // Both are finalized on drop, which does nothing for the parts never initialized
+        let mut graph_node = GraphNode {
+            context: Box::new(unsafe { rcl::rcl_get_zero_initialized_context() }),
+            node: Box::new(unsafe { rcl::rcl_get_zero_initialized_node() }),
+        };
+        unsafe {
+            let mut init_options = rcl::rcl_get_zero_initialized_init_options();
+            check(rcl::rcl_init_options_init(
+                &mut init_options,
+                rcl::rcutils_get_default_allocator(),
+            ))?;
+            let ret = rcl::rcl_init(0, ptr::null(), &init_options, graph_node.context.as_mut());
+            rcl::rcl_init_options_fini(&mut init_options);
+            check(ret)?;
+
+            let mut node_options = rcl::rcl_node_get_default_options();
+            node_options.enable_rosout = false;
+            check(rcl::rcl_node_init(
+                graph_node.node.as_mut(),
+                name.as_ptr(),
+                namespace.as_ptr(),
+                graph_node.context.as_mut(),
+                &node_options,
+            ))?;
+        }
+        Ok(graph_node)
+    }
+
+    pub fn topic_names_and_types(&self) -> Result<Vec<(String, Vec<String>)>, r2r::Error> {
+        let mut names_and_types = unsafe { rcl::rmw_get_zero_initialized_names_and_types() };
+        let mut allocator = unsafe { rcl::rcutils_get_default_allocator() };
+        let ret = unsafe {
+            rcl::rcl_get_topic_names_and_types(
+                self.node.as_ref(),
+                &mut allocator,
+                false,
+                &mut names_and_types,
+            )
+        };
+        let result = unsafe { take_names_and_types(&mut names_and_types) };
+        check(ret).map(|_| result)
+    }
+
+    pub fn service_names_and_types(&self) -> Result<Vec<(String, Vec<String>)>, r2r::Error> {
+        let mut names_and_types = unsafe { rcl::rmw_get_zero_initialized_names_and_types() };
+        let mut allocator = unsafe { rcl::rcutils_get_default_allocator() };
+        let ret = unsafe {
+            rcl::rcl_get_service_names_and_types(
+                self.node.as_ref(),
+                &mut allocator,
+                &mut names_and_types,
+            )
+        };
+        let result = unsafe { take_names_and_types(&mut names_and_types) };
+        check(ret).map(|_| result)
+    }
+
+    pub fn publishers_info_by_topic(&self, topic: &str) -> Result<Vec<Endpoint>, r2r::Error> {
+        self.endpoints(topic, rcl::rcl_get_publishers_info_by_topic)
+    }
+
+    pub fn subscriptions_info_by_topic(&self, topic: &str) -> Result<Vec<Endpoint>, r2r::Error> {
+        self.endpoints(topic, rcl::rcl_get_subscriptions_info_by_topic)
+    }
+
+    fn endpoints(&self, topic: &str, query: EndpointQuery) -> Result<Vec<Endpoint>, r2r::Error> {
+        let topic = CString::new(topic).map_err(|_| r2r::Error::RCL_RET_INVALID_ARGUMENT)?;
+        let mut info_array = unsafe { rcl::rmw_get_zero_initialized_topic_endpoint_info_array() };
+        let mut allocator = unsafe { rcl::rcutils_get_default_allocator() };
+        let ret = unsafe {
+            query(
+                self.node.as_ref(),
+                &mut allocator,
+                topic.as_ptr(),
+                false,
+                &mut info_array,
+            )
+        };
+
+        let mut endpoints = vec![];
+        if !info_array.info_array.is_null() {
+            let infos =
+                unsafe { std::slice::from_raw_parts(info_array.info_array, info_array.size) };
+            for info in infos {
+                let (namespace, name) = unsafe {
+                    (
+                        string_lossy(info.node_namespace),
+                        string_lossy(info.node_name),
+                    )
+                };
+                endpoints.push(Endpoint {
+                    node_name: crate::graph::fully_qualified_name(&namespace, &name),
+                    qos: QosProfile::from(info.qos_profile),
+                });
+            }
+        }
+        unsafe { rcl::rmw_topic_endpoint_info_array_fini(&mut info_array, &mut allocator) };
+        check(ret).map(|_| endpoints)
+    }
+}
+
+impl Drop for GraphNode {
+    fn drop(&mut self) {
+        unsafe {
+            rcl::rcl_node_fini(self.node.as_mut());
+            rcl::rcl_shutdown(self.context.as_mut());
+            rcl::rcl_context_fini(self.context.as_mut());
+        }
+    }
+}
+
+fn check(ret: rcl::rcl_ret_t) -> Result<(), r2r::Error> {
+    match ret == rcl::RCL_RET_OK as rcl::rcl_ret_t {
+        true => Ok(()),
+        false => Err(r2r::Error::from_rcl_error(ret)),
+    }
+}
+
+/// Copy the names and their types out of `names_and_types` and free it.
+unsafe fn take_names_and_types(
+    names_and_types: &mut rcl::rcl_names_and_types_t,
+) -> Vec<(String, Vec<String>)> {
+    let names = string_array(&names_and_types.names);
+    let result = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| (name, string_array(&*names_and_types.types.add(i))))
+        .collect();
+    rcl::rmw_names_and_types_fini(names_and_types);
+    result
+}
+
+unsafe fn string_array(array: &rcl::rcutils_string_array_t) -> Vec<String> {
+    if array.data.is_null() {
+        return vec![];
+    }
+    std::slice::from_raw_parts(array.data, array.size)
+        .iter()
+        .map(|&s| string_lossy(s))
+        .collect()
+}
+
+unsafe fn string_lossy(s: *const c_char) -> String {
+    match s.is_null() {
+        true => String::new(),
+        false => CStr::from_ptr(s).to_string_lossy().into_owned(),
+    }
+}
@@ -0,0 +1,654 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    error::Error,
+    future::Future,
+    io,
+    panic::{self, AssertUnwindSafe},
+    rc::Rc,
+    sync::mpsc::{self, TryRecvError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use futures::{
+    executor::{LocalPool, LocalSpawner},
+    future::{self, AbortHandle, Abortable, FutureExt},
+    stream::{Stream, StreamExt},
+    task::LocalSpawnExt,
+};
+use r2r::QosProfile;
+
+use crate::{
+    diagnostics,
+    event::{Event, ROSEvent, TopicRate},
+    graph, latency,
+    rcl_graph::{self, GraphNode},
+    terminal,
+};
+
+/// Longest wait for ROS work in one spin, which bounds the latency of commands.
+const SPIN_TIMEOUT: Duration = Duration::from_millis(10);
+
+/// How often the graph and the topic rates are sent to the UI.
+const GRAPH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Topics whose rates are measured at the same time, besides the echoed ones.
+/// The others wait for their turn.
+const RATE_SLOTS: usize = 8;
+
+/// Name of the node querying the graph, hidden like every name starting with `_`.
+pub const GRAPH_NODE: &str = "_lazyros_graph";
+
+/// Requests of the UI to the ROS backend. Failures come back as `ROSEvent::BackendError`.
+// The UI only subscribes so far, publishers and clients are there for the
+// views that will drive them
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RosCommand {
+    /// Forward the messages of `topic` as `ROSEvent::TopicMessage`.
+    Subscribe {
+        topic: String,
+        msg_type: String,
+    },
+    Unsubscribe {
+        topic: String,
+    },
+    /// Measure the message rates of these topics, a few at a time when there
+    /// are many, and send them back as `ROSEvent::TopicRates`.
+    MeasureRates {
+        topics: Vec<String>,
+    },
+    CreatePublisher {
+        topic: String,
+        msg_type: String,
+    },
+    /// Publish a message, written as JSON, with a publisher created before.
+    Publish {
+        topic: String,
+        message: serde_json::Value,
+    },
+    DestroyPublisher {
+        topic: String,
+    },
+    CreateClient {
+        service: String,
+        srv_type: String,
+    },
+    /// Call a service with a client created before. The response comes back as
+    /// `ROSEvent::ServiceResponse`.
+    CallService {
+        service: String,
+        request: serde_json::Value,
+    },
+    DestroyClient {
+        service: String,
+    },
+}
+
+/// Where the UI gets its ROS events from: the real ROS graph, or the simulation
+/// of the `demo` module.
+pub trait Backend {
+    /// Start the backend on its own thread and return the sender of its commands.
+    ///
+    /// It sends its events to `events` and stops once the command sender is
+    /// dropped. When it fails it sends a `ROSEvent::BackendStopped`, the UI can
+    /// then start a new one.
+    fn spawn(&self, events: mpsc::Sender<Event>) -> io::Result<mpsc::Sender<RosCommand>>;
+}
+
+/// The backend talking to ROS through r2r.
+pub struct R2rBackend;
+
+impl Backend for R2rBackend {
+    fn spawn(&self, events: mpsc::Sender<Event>) -> io::Result<mpsc::Sender<RosCommand>> {
+        let (command_tx, command_rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("ros".to_string())
+            .spawn(move || {
+                let result =
+                    panic::catch_unwind(AssertUnwindSafe(|| run(events.clone(), command_rx)));
+                let reason = match result {
+                    Ok(Ok(())) => return,
+                    Ok(Err(e)) => e.to_string(),
+                    Err(payload) => format!("panicked: {}", terminal::panic_message(&*payload)),
+                };
+                let _ = events.send(Event::ROSEvent {
+                    event: ROSEvent::BackendStopped(reason),
+                });
+            })?;
+        Ok(command_tx)
+    }
+}
+
+/// Everything the backend created on behalf of the UI, by topic or service name.
+struct RosNode {
+    node: r2r::Node,
+    events: mpsc::Sender<Event>,
+    spawner: LocalSpawner,
+    subscriptions: HashMap<String, Subscription>,
+    publishers: HashMap<String, r2r::PublisherUntyped>,
+    clients: HashMap<String, r2r::ClientUntyped>,
+    /// Topics whose rates the UI asked for.
+    rate_topics: Vec<String>,
+    /// Index into `rate_topics` of the next topic to get a turn.
+    next_rate_topic: usize,
+    /// Counting subscriptions of the asked topics that are not subscribed
+    /// already, at most `RATE_SLOTS` of them.
+    rate_subscriptions: HashMap<String, RateSubscription>,
+    last_rates: Instant,
+}
+
+/// A turn of a topic in the rate monitor.
+struct RateSubscription {
+    /// `None` for topics whose type r2r was not built with.
+    subscription: Option<Subscription>,
+    msg_type: String,
+    /// The newest message, still serialized.
+    last_message: Rc<RefCell<Vec<u8>>>,
+    /// Rate intervals since subscribing. The first one only waits for
+    /// discovery, the second one is measured.
+    intervals: u32,
+}
+
+/// A subscription running on the local pool, with the number of messages it
+/// received since the rates were last sent. Dropping it ends the subscription.
+struct Subscription {
+    abort: AbortHandle,
+    count: Rc<Cell<usize>>,
+    /// See `TopicRate::newest`.
+    newest: Rc<Cell<Option<SystemTime>>>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        // Dropping the stream makes r2r remove the subscription
+        self.abort.abort();
+    }
+}
+
+/// Run the ROS backend until the UI hangs up.
+///
+/// This thread is the only owner of the node: commands are executed between two
+/// spins and the subscription streams run on a local executor polled right after
+/// each spin, so nothing ever waits on a lock.
+fn run(
+    events: mpsc::Sender<Event>,
+    commands: mpsc::Receiver<RosCommand>,
+) -> Result<(), r2r::Error> {
+    let ctx = r2r::Context::create()?;
+    let node = r2r::Node::create(ctx, "lazyros", "")?;
+    let graph_node = GraphNode::create(GRAPH_NODE)?;
+    let mut pool = LocalPool::new();
+    let mut backend = RosNode::new(node, events, pool.spawner());
+
+    for topic in diagnostics::DIAGNOSTICS_TOPICS {
+        backend.subscribe_diagnostics(topic)?;
+    }
+
+    let mut last_graph: Option<Instant> = None;
+    loop {
+        loop {
+            match commands.try_recv() {
+                Ok(command) => backend.execute(command),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+
+        backend.node.spin_once(SPIN_TIMEOUT);
+        pool.run_until_stalled();
+
+        if last_graph.is_none_or(|sent| sent.elapsed() >= GRAPH_INTERVAL) {
+            // A failed query is tried again at the next interval
+            match collect_graph(&graph_node) {
+                Ok(snapshot) => {
+                    backend.monitor_rates(&snapshot);
+                    backend.send(ROSEvent::GraphUpdate(snapshot));
+                }
+                Err(e) => backend.send(ROSEvent::BackendError(format!(
+                    "querying the ROS graph failed: {}",
+                    e
+                ))),
+            }
+            last_graph = Some(Instant::now());
+        }
+    }
+}
+
+impl RosNode {
+    fn new(node: r2r::Node, events: mpsc::Sender<Event>, spawner: LocalSpawner) -> RosNode {
+        RosNode {
+            node,
+            events,
+            spawner,
+            subscriptions: HashMap::new(),
+            publishers: HashMap::new(),
+            clients: HashMap::new(),
+            rate_topics: vec![],
+            next_rate_topic: 0,
+            rate_subscriptions: HashMap::new(),
+            last_rates: Instant::now(),
+        }
+    }
+
+    fn send(&self, event: ROSEvent) {
+        // Nobody is listening anymore once the UI quits, `run` returns right after
+        let _ = self.events.send(Event::ROSEvent { event });
+    }
+
+    fn spawn(&self, task: impl Future<Output = ()> + 'static) {
+        self.spawner
+            .spawn_local(task)
+            .expect("the local pool outlives the backend");
+    }
+
+    /// Run `messages` on the local pool, counting them and passing each to
+    /// `forward`, which returns the header stamp of the ones that have one.
+    fn spawn_subscription<T: 'static>(
+        &self,
+        messages: impl Stream<Item = T> + 'static,
+        mut forward: impl FnMut(T) -> Option<SystemTime> + 'static,
+    ) -> Subscription {
+        let count = Rc::new(Cell::new(0));
+        let newest = Rc::new(Cell::new(None));
+        let (task_count, task_newest) = (count.clone(), newest.clone());
+        let task = messages.for_each(move |message| {
+            task_count.set(task_count.get() + 1);
+            let stamp = forward(message);
+            task_newest.set(Some(stamp.unwrap_or_else(SystemTime::now)));
+            future::ready(())
+        });
+        let (abort, registration) = AbortHandle::new_pair();
+        self.spawn(Abortable::new(task, registration).map(|_| ()));
+        Subscription {
+            abort,
+            count,
+            newest,
+        }
+    }
+
+    fn execute(&mut self, command: RosCommand) {
+        if let Err(e) = self.try_execute(command) {
+            self.send(ROSEvent::BackendError(e.to_string()));
+        }
+    }
+
+    fn try_execute(&mut self, command: RosCommand) -> Result<(), Box<dyn Error>> {
+        match command {
+            RosCommand::Subscribe { topic, msg_type } => {
+                if self.subscriptions.contains_key(&topic) {
+                    return Ok(());
+                }
+                let qos = self.subscription_qos(&topic)?;
+                let messages = self.node.subscribe_untyped(&topic, &msg_type, qos)?;
+
+                let events = self.events.clone();
+                let name = topic.clone();
+                let subscription = self.spawn_subscription(messages, move |message| {
+                    let (event, stamp) = match message {
+                        Ok(message) => {
+                            let stamp =
+                                latency::header_stamp(&message).map(|stamp| UNIX_EPOCH + stamp);
+                            (ROSEvent::TopicMessage(name.clone(), message), stamp)
+                        }
+                        Err(e) => (ROSEvent::BackendError(format!("{}: {}", name, e)), None),
+                    };
+                    let _ = events.send(Event::ROSEvent { event });
+                    stamp
+                });
+                // The subscription counts the messages for the rates as well
+                self.rate_subscriptions.remove(&topic);
+                self.subscriptions.insert(topic, subscription);
+            }
+            RosCommand::Unsubscribe { topic } => {
+                self.subscriptions.remove(&topic);
+            }
+            RosCommand::MeasureRates { topics } => {
+                self.rate_topics = topics;
+            }
+            RosCommand::CreatePublisher { topic, msg_type } => {
+                let publisher =
+                    self.node
+                        .create_publisher_untyped(&topic, &msg_type, QosProfile::default())?;
+                self.publishers.insert(topic, publisher);
+            }
+            RosCommand::Publish { topic, message } => match self.publishers.get(&topic) {
+                Some(publisher) => publisher.publish(message)?,
+                None => return Err(format!("no publisher for {}", topic).into()),
+            },
+            RosCommand::DestroyPublisher { topic } => {
+                if let Some(publisher) = self.publishers.remove(&topic) {
+                    self.node.destroy_publisher_untyped(publisher);
+                }
+            }
+            RosCommand::CreateClient { service, srv_type } => {
+                let client =
+                    self.node
+                        .create_client_untyped(&service, &srv_type, QosProfile::default())?;
+                self.clients.insert(service, client);
+            }
+            RosCommand::CallService { service, request } => {
+                let Some(client) = self.clients.get(&service) else {
+                    return Err(format!("no client for {}", service).into());
+                };
+                let response = client.request(request)?;
+
+                let events = self.events.clone();
+                self.spawn(async move {
+                    let event = match response.await {
+                        Ok(Ok(response)) => ROSEvent::ServiceResponse(service, response),
+                        Ok(Err(e)) | Err(e) => {
+                            ROSEvent::BackendError(format!("{}: {}", service, e))
+                        }
+                    };
+                    let _ = events.send(Event::ROSEvent { event });
+                });
+            }
+            RosCommand::DestroyClient { service } => {
+                // r2r cannot destroy clients, without the handle it is never used again
+                self.clients.remove(&service);
+            }
+        }
+        Ok(())
+    }
+
+    /// A reliable subscription does not match best effort publishers, so fall
+    /// back to best effort when there is one.
+    fn subscription_qos(&self, topic: &str) -> Result<QosProfile, r2r::Error> {
+        let best_effort = self
+            .node
+            .get_publishers_info_by_topic(topic, false)?
+            .iter()
+            .any(|info| {
+                matches!(
+                    info.qos_profile.reliability,
+                    r2r::qos::ReliabilityPolicy::BestEffort
+                )
+            });
+        Ok(match best_effort {
+            true => QosProfile::default().best_effort(),
+            false => QosProfile::default(),
+        })
+    }
+
+    fn subscribe_diagnostics(&mut self, topic: &'static str) -> Result<(), r2r::Error> {
+        let messages = self
+            .node
+            .subscribe::<r2r::diagnostic_msgs::msg::DiagnosticArray>(
+                topic,
+                QosProfile::default(),
+            )?;
+
+        let events = self.events.clone();
+        self.spawn(messages.for_each(move |msg| {
+            let statuses = msg
+                .status
+                .into_iter()
+                .map(|status| diagnostics::DiagnosticStatus {
+                    level: diagnostics::DiagnosticLevel::from_byte(status.level),
+                    name: status.name,
+                    message: status.message,
+                    hardware_id: status.hardware_id,
+                    values: status
+                        .values
+                        .into_iter()
+                        .map(|kv| (kv.key, kv.value))
+                        .collect(),
+                })
+                .collect();
+            let _ = events.send(Event::ROSEvent {
+                event: ROSEvent::Diagnostics(topic.to_string(), statuses),
+            });
+            future::ready(())
+        }));
+        Ok(())
+    }
+
+    /// Send the rates of the echoed topics and the ones whose turn is over,
+    /// then let the next topics take the free slots. Topics that left the graph
+    /// lose their subscription. When every asked topic fits into the slots
+    /// they keep their subscription and are measured every time.
+    fn monitor_rates(&mut self, graph: &graph::GraphSnapshot) {
+        let msg_types: HashMap<&str, &String> = graph
+            .topics
+            .iter()
+            .filter_map(|topic| Some((topic.name.as_str(), topic.msg_types.first()?)))
+            .collect();
+        self.rate_subscriptions.retain(|topic, _| {
+            self.rate_topics.contains(topic)
+                && msg_types.contains_key(topic.as_str())
+                && !self.subscriptions.contains_key(topic)
+        });
+
+        let elapsed = self.last_rates.elapsed().as_secs_f64();
+        self.last_rates = Instant::now();
+        let mut rates = HashMap::new();
+        // Echoes count their messages too, whether their rate is asked for or not
+        for (topic, subscription) in &self.subscriptions {
+            let count = subscription.count.take();
+            if self.rate_topics.contains(topic) {
+                let rate = TopicRate {
+                    hz: count as f64 / elapsed,
+                    newest: subscription.newest.get(),
+                };
+                rates.insert(topic.clone(), rate);
+            }
+        }
+        for (topic, rate) in &mut self.rate_subscriptions {
+            if let Some(subscription) = &rate.subscription {
+                let count = subscription.count.take();
+                if rate.intervals > 0 {
+                    // Only the newest message is deserialized, for its stamp
+                    let newest = subscription.newest.get().map(|received| {
+                        raw_stamp(&rate.msg_type, &rate.last_message.borrow()).unwrap_or(received)
+                    });
+                    let rate = TopicRate {
+                        hz: count as f64 / elapsed,
+                        newest,
+                    };
+                    rates.insert(topic.clone(), rate);
+                }
+            }
+            rate.intervals += 1;
+        }
+        self.send(ROSEvent::TopicRates(rates));
+
+        // Topics that were measured make room for the ones waiting
+        let waiting: Vec<String> = self
+            .rate_topics
+            .iter()
+            .filter(|topic| {
+                msg_types.contains_key(topic.as_str())
+                    && !self.subscriptions.contains_key(*topic)
+                    && !self.rate_subscriptions.contains_key(*topic)
+            })
+            .cloned()
+            .collect();
+        if !waiting.is_empty() {
+            self.rate_subscriptions
+                .retain(|_, rate| rate.intervals < 2 && rate.subscription.is_some());
+        }
+
+        let count = self.rate_topics.len();
+        for _ in 0..count {
+            if self.rate_subscriptions.len() >= RATE_SLOTS {
+                break;
+            }
+            self.next_rate_topic %= count;
+            let topic = self.rate_topics[self.next_rate_topic].clone();
+            self.next_rate_topic += 1;
+            if !waiting.contains(&topic) {
+                continue;
+            }
+            // Raw messages are only counted and kept, never deserialized. Best
+            // effort matches both reliable and best effort publishers.
+            let msg_type = msg_types[topic.as_str()].clone();
+            let last_message = Rc::new(RefCell::new(vec![]));
+            let task_last_message = last_message.clone();
+            let subscription = self
+                .node
+                .subscribe_raw(&topic, &msg_type, QosProfile::sensor_data())
+                .ok()
+                .map(|messages| {
+                    self.spawn_subscription(messages, move |message| {
+                        *task_last_message.borrow_mut() = message;
+                        None
+                    })
+                });
+            self.rate_subscriptions.insert(
+                topic,
+                RateSubscription {
+                    subscription,
+                    msg_type,
+                    last_message,
+                    intervals: 0,
+                },
+            );
+        }
+    }
+}
+
+/// The `header.stamp` of a serialized message of type `msg_type`, if it has one.
+fn raw_stamp(msg_type: &str, bytes: &[u8]) -> Option<SystemTime> {
+    let mut message = r2r::WrappedNativeMsgUntyped::new_from(msg_type).ok()?;
+    message.from_serialized_bytes(bytes).ok()?;
+    let stamp = latency::header_stamp(&message.to_json().ok()?)?;
+    Some(UNIX_EPOCH + stamp)
+}
+
+/// Query the topics, their endpoints and the services visible to `node`.
+pub fn collect_graph(node: &GraphNode) -> Result<graph::GraphSnapshot, r2r::Error> {
+    let endpoints = |endpoints: Vec<rcl_graph::Endpoint>| -> Vec<graph::EndpointInfo> {
+        endpoints
+            .into_iter()
+            .map(|endpoint| graph::EndpointInfo {
+                node_name: endpoint.node_name,
+                qos: qos_info(&endpoint.qos),
+            })
+            .collect()
+    };
+
+    let mut topics = vec![];
+    for (name, msg_types) in node.topic_names_and_types()? {
+        topics.push(graph::TopicInfo {
+            publishers: endpoints(node.publishers_info_by_topic(&name)?),
+            subscribers: endpoints(node.subscriptions_info_by_topic(&name)?),
+            name,
+            msg_types,
+        });
+    }
+
+    let mut snapshot = graph::GraphSnapshot::from_topics(topics);
+    snapshot.services = node
+        .service_names_and_types()?
+        .into_iter()
+        .map(|(name, srv_types)| graph::ServiceInfo { name, srv_types })
+        .collect();
+    Ok(snapshot)
+}
+
+fn qos_info(qos: &QosProfile) -> graph::QosInfo {
+    graph::QosInfo {
+        reliability: format!("{:?}", qos.reliability),
+        durability: format!("{:?}", qos.durability),
+        history: format!("{:?}", qos.history),
+        depth: qos.depth,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A backend on a node of its own, with its pool and the events it sends.
+    fn backend(name: &str) -> (RosNode, LocalPool, mpsc::Receiver<Event>) {
+        let ctx = r2r::Context::create().unwrap();
+        let node = r2r::Node::create(ctx, name, "").unwrap();
+        let pool = LocalPool::new();
+        let (events, received) = mpsc::channel();
+        (RosNode::new(node, events, pool.spawner()), pool, received)
+    }
+
+    fn errors(events: &mpsc::Receiver<Event>) -> Vec<String> {
+        events
+            .try_iter()
+            .filter_map(|event| match event {
+                Event::ROSEvent {
+                    event: ROSEvent::BackendError(error),
+                } => Some(error),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn published_messages_reach_the_subscriptions() {
+        let (mut backend, mut pool, events) = backend("lazyros_test_publish");
+        let topic = "/lazyros_test/chatter".to_string();
+        let msg_type = "std_msgs/msg/String".to_string();
+        backend.execute(RosCommand::CreatePublisher {
+            topic: topic.clone(),
+            msg_type: msg_type.clone(),
+        });
+        backend.execute(RosCommand::Subscribe {
+            topic: topic.clone(),
+            msg_type,
+        });
+
+        // Publish until discovery has matched the two
+        let message = serde_json::json!({ "data": "hello" });
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut received = None;
+        while received.is_none() && Instant::now() < deadline {
+            backend.execute(RosCommand::Publish {
+                topic: topic.clone(),
+                message: message.clone(),
+            });
+            backend.node.spin_once(SPIN_TIMEOUT);
+            pool.run_until_stalled();
+            received = events.try_iter().find_map(|event| match event {
+                Event::ROSEvent {
+                    event: ROSEvent::TopicMessage(name, message),
+                } if name == topic => Some(message),
+                _ => None,
+            });
+        }
+        assert_eq!(received, Some(message.clone()));
+
+        backend.execute(RosCommand::DestroyPublisher {
+            topic: topic.clone(),
+        });
+        backend.execute(RosCommand::Publish { topic, message });
+        assert_eq!(errors(&events), ["no publisher for /lazyros_test/chatter"]);
+    }
+
+    #[test]
+    fn services_are_called_through_a_client_created_before() {
+        let (mut backend, _pool, events) = backend("lazyros_test_client");
+        let service = "/lazyros_test/get_parameters".to_string();
+        let call = RosCommand::CallService {
+            service: service.clone(),
+            request: serde_json::json!({ "names": [] }),
+        };
+        backend.execute(call.clone());
+        assert_eq!(
+            errors(&events),
+            ["no client for /lazyros_test/get_parameters"]
+        );
+
+        backend.execute(RosCommand::CreateClient {
+            service: service.clone(),
+            srv_type: "rcl_interfaces/srv/GetParameters".to_string(),
+        });
+        assert!(backend.clients.contains_key(&service));
+        assert_eq!(errors(&events), Vec::<String>::new());
+
+        backend.execute(RosCommand::DestroyClient { service });
+        backend.execute(call);
+        assert_eq!(
+            errors(&events),
+            ["no client for /lazyros_test/get_parameters"]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use std::cmp::Ordering;
+
+/// Sort key of a table pane: the selected column and its direction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TableSort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+impl TableSort {
+    pub fn next_column(&mut self, num_columns: usize) {
+        self.column = (self.column + 1).min(num_columns.saturating_sub(1));
+    }
+
+    pub fn previous_column(&mut self) {
+        self.column = self.column.saturating_sub(1);
+    }
+
+    pub fn toggle_order(&mut self) {
+        self.descending = !self.descending;
+    }
+
+    /// Sort `items` by the selected column, `compare` orders two items by a given column.
+    pub fn sort<T>(&self, items: &mut [T], compare: impl Fn(usize, &T, &T) -> Ordering) {
+        items.sort_by(|a, b| {
+            let ordering = compare(self.column, a, b);
+            match self.descending {
+                true => ordering.reverse(),
+                false => ordering,
+            }
+        });
+    }
+
+    /// Column titles with an arrow on the sort column.
+    pub fn header(&self, titles: &[&str]) -> Vec<String> {
+        titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| match (i == self.column, self.descending) {
+                (true, false) => format!("{} ▲", title),
+                (true, true) => format!("{} ▼", title),
+                (false, _) => title.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rows ordered like the panes order theirs: by a column, then by name.
+    fn compare(column: usize, a: &(&str, u32, f64), b: &(&str, u32, f64)) -> Ordering {
+        let ordering = match column {
+            1 => a.1.cmp(&b.1),
+            2 => a.2.partial_cmp(&b.2).unwrap_or(Ordering::Equal),
+            _ => Ordering::Equal,
+        };
+        ordering.then_with(|| a.0.cmp(b.0))
+    }
+
+    fn sorted(column: usize, descending: bool) -> Vec<&'static str> {
+        let mut rows = vec![
+            ("/scan", 1, 10.0),
+            ("/cmd_vel", 2, 20.0),
+            ("/odom", 1, 50.0),
+            ("/tf", 2, 10.0),
+        ];
+        TableSort { column, descending }.sort(&mut rows, compare);
+        rows.into_iter().map(|row| row.0).collect()
+    }
+
+    #[test]
+    fn every_column_sorts_both_ways_with_ties_by_name() {
+        assert_eq!(sorted(0, false), ["/cmd_vel", "/odom", "/scan", "/tf"]);
+        assert_eq!(sorted(0, true), ["/tf", "/scan", "/odom", "/cmd_vel"]);
+        assert_eq!(sorted(1, false), ["/odom", "/scan", "/cmd_vel", "/tf"]);
+        assert_eq!(sorted(1, true), ["/tf", "/cmd_vel", "/scan", "/odom"]);
+        assert_eq!(sorted(2, false), ["/scan", "/tf", "/cmd_vel", "/odom"]);
+        assert_eq!(sorted(2, true), ["/odom", "/cmd_vel", "/tf", "/scan"]);
+    }
+
+    #[test]
+    fn column_moves_stay_in_range() {
+        let mut sort = TableSort::default();
+        sort.previous_column();
+        assert_eq!(sort.column, 0);
+        for _ in 0..5 {
+            sort.next_column(3);
+        }
+        assert_eq!(sort.column, 2);
+        sort.next_column(0);
+        assert_eq!(sort.column, 0);
+    }
+
+    #[test]
+    fn header_marks_the_sort_column_and_order() {
+        let mut sort = TableSort {
+            column: 1,
+            descending: false,
+        };
+        assert_eq!(sort.header(&["Name", "Type"]), ["Name", "Type ▲"]);
+        sort.toggle_order();
+        assert_eq!(sort.header(&["Name", "Type"]), ["Name", "Type ▼"]);
+    }
+}
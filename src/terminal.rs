@@ -0,0 +1,149 @@
+use std::{
+    any::Any,
+    env,
+    io::{self, Write},
+    panic, thread,
+};
+
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use image::{imageops::FilterType, RgbImage};
+use ratatui::{layout::Rect, DefaultTerminal};
+
+use crate::{
+    config::Graphics,
+    preview::{
+        self,
+        image::{base64, kitty, sixel},
+    },
+};
+
+/// Enter raw mode and the alternate screen like `ratatui::init`, but restore
+/// the terminal only when the calling thread, the UI one, panics.
+///
+/// ratatui's hook restores the terminal on a panic in any thread. Background
+/// threads catch their panics and report them to the UI instead, and restoring
+/// the terminal under a running UI would leave it with a half-drawn screen.
+/// Every panic still reaches the hook installed before, which prints it.
+pub fn init() -> DefaultTerminal {
+    let print = panic::take_hook();
+    let terminal = ratatui::init();
+    drop(panic::take_hook());
+
+    let ui_thread = thread::current().id();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().id() == ui_thread {
+            // ratatui only knows about the raw mode and the alternate screen
+            let _ = set_mouse_capture(false);
+            ratatui::restore();
+        }
+        print(info);
+    }));
+    terminal
+}
+
+/// Report clicks, drags and the wheel as events, or stop doing so. While
+/// captured the terminal cannot select text without a modifier, usually shift.
+pub fn set_mouse_capture(enabled: bool) -> io::Result<()> {
+    match enabled {
+        true => crossterm::execute!(io::stdout(), EnableMouseCapture),
+        false => crossterm::execute!(io::stdout(), DisableMouseCapture),
+    }
+}
+
+/// Ask the terminal to put `text` on the system clipboard with an OSC 52
+/// escape sequence. It travels with the output, so it also works over SSH.
+/// Terminals without support ignore it.
+pub fn copy_to_clipboard(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", base64(text.as_bytes()))?;
+    out.flush()
+}
+
+/// Kitty or sixel when the terminal is known to draw them, judged by the
+/// variables it sets. tmux passes neither through by default.
+pub fn detect_graphics() -> Graphics {
+    let var = |name| env::var(name).unwrap_or_default();
+    let (term, program) = (var("TERM"), var("TERM_PROGRAM"));
+    if env::var_os("TMUX").is_some() {
+        Graphics::Halfblocks
+    } else if env::var_os("KITTY_WINDOW_ID").is_some()
+        || term == "xterm-kitty"
+        || matches!(program.as_str(), "ghostty" | "WezTerm")
+    {
+        Graphics::Kitty
+    } else if term.contains("sixel") || term.starts_with("foot") || term == "mlterm" {
+        Graphics::Sixel
+    } else {
+        Graphics::Halfblocks
+    }
+}
+
+/// Draw `picture` as large as it fits into the cells of `area`, centred
+/// horizontally. The cells should be left alone by ratatui meanwhile.
+pub fn draw_picture(
+    out: &mut impl Write,
+    graphics: Graphics,
+    area: Rect,
+    picture: &RgbImage,
+) -> io::Result<()> {
+    // Terminals that do not tell their cell size mostly have about this one
+    let (cell_width, cell_height) = match crossterm::terminal::window_size() {
+        Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => (
+            u32::from(size.width / size.columns),
+            u32::from(size.height / size.rows),
+        ),
+        _ => (8, 16),
+    };
+    let (width, height) = preview::fit(
+        picture.dimensions(),
+        (
+            u32::from(area.width) * cell_width,
+            u32::from(area.height) * cell_height,
+        ),
+    );
+    let columns = width.div_ceil(cell_width) as u16;
+    let x = area.x + area.width.saturating_sub(columns) / 2;
+    crossterm::queue!(out, crossterm::cursor::MoveTo(x, area.y))?;
+
+    let sequence = match graphics {
+        Graphics::Kitty => {
+            // The terminal scales, smaller pictures only save bandwidth
+            let smaller;
+            let picture = match width < picture.width() {
+                true => {
+                    smaller = image::imageops::resize(picture, width, height, FilterType::Triangle);
+                    &smaller
+                }
+                false => picture,
+            };
+            kitty(picture, columns, height.div_ceil(cell_height) as u16)
+        }
+        Graphics::Sixel => sixel(&image::imageops::resize(
+            picture,
+            width,
+            height,
+            FilterType::Triangle,
+        )),
+        Graphics::Auto | Graphics::Halfblocks => return Ok(()),
+    };
+    out.write_all(sequence.as_bytes())?;
+    out.flush()
+}
+
+/// Remove what `draw_picture` drew. Sixel pixels are only removed by drawing
+/// the cells again.
+pub fn clear_pictures(out: &mut impl Write, graphics: Graphics) -> io::Result<()> {
+    if graphics == Graphics::Kitty {
+        out.write_all(b"\x1b_Ga=d,d=A,q=2\x1b\\")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// The message of a panic caught with `catch_unwind`.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, HashSet};
+
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+};
+
+use crate::filter::highlight;
+
+/// A line of a table pane: one of its items, or a namespace in the tree view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaneRow {
+    Item {
+        /// Index into the items passing the filter.
+        index: usize,
+        depth: usize,
+        /// Character position where the label starts, the namespace is not repeated.
+        label_start: usize,
+    },
+    Namespace {
+        path: String,
+        depth: usize,
+        /// Number of items anywhere below the namespace.
+        count: usize,
+        collapsed: bool,
+    },
+}
+
+impl PaneRow {
+    /// Identifies the row across updates. Namespaces end with a `/`, which
+    /// keeps `/robot1/` apart from a node called `/robot1`.
+    pub fn key<'a>(&self, item_name: impl Fn(usize) -> &'a str) -> String {
+        match self {
+            PaneRow::Item { index, .. } => item_name(*index).to_string(),
+            PaneRow::Namespace { path, .. } => format!("{}/", path),
+        }
+    }
+
+    /// The name cell of an item row, with the filter matches highlighted.
+    pub fn item_label(
+        depth: usize,
+        label_start: usize,
+        name: &str,
+        indices: &[usize],
+        style: Style,
+        matched: Style,
+    ) -> Line<'static> {
+        let label: String = name.chars().skip(label_start).collect();
+        let indices: Vec<usize> = indices
+            .iter()
+            .filter_map(|i| i.checked_sub(label_start))
+            .collect();
+        let mut line = highlight(&label, &indices, style, matched);
+        if depth > 0 {
+            line.spans
+                .insert(0, Span::styled("  ".repeat(depth), style));
+        }
+        line
+    }
+
+    /// The name cell of a namespace row, e.g. `▾ arm/ (3)`.
+    pub fn namespace_label(
+        path: &str,
+        depth: usize,
+        count: usize,
+        collapsed: bool,
+        style: Style,
+    ) -> Line<'static> {
+        let marker = if collapsed { "▸" } else { "▾" };
+        let segment = path.rsplit('/').next().unwrap_or_default();
+        Line::styled(
+            format!("{}{} {}/ ({})", "  ".repeat(depth), marker, segment, count),
+            style,
+        )
+    }
+}
+
+/// Whether a table pane shows its items flat or grouped by namespace, and
+/// which namespaces are folded.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct NamespaceTree {
+    enabled: bool,
+    collapsed: HashSet<String>,
+}
+
+#[derive(Default)]
+struct Group<'a> {
+    groups: BTreeMap<&'a str, Group<'a>>,
+    items: Vec<(usize, usize)>,
+    count: usize,
+}
+
+impl NamespaceTree {
+    pub fn toggle_view(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn toggle_fold(&mut self, path: &str) {
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.to_string());
+        }
+    }
+
+    /// The rows for items with the given names, which keep their order inside
+    /// each namespace. Namespaces come before the items next to them.
+    pub fn rows<'a>(&self, names: impl Iterator<Item = &'a str>) -> Vec<PaneRow> {
+        if !self.enabled {
+            return names
+                .enumerate()
+                .map(|(index, _)| PaneRow::Item {
+                    index,
+                    depth: 0,
+                    label_start: 0,
+                })
+                .collect();
+        }
+
+        let mut root = Group::default();
+        for (index, name) in names.enumerate() {
+            let segments: Vec<&str> = name.split('/').filter(|s| !s.is_empty()).collect();
+            let label_start = name[..name.rfind('/').map_or(0, |i| i + 1)].chars().count();
+
+            let mut group = &mut root;
+            for segment in segments.iter().take(segments.len().saturating_sub(1)) {
+                group = group.groups.entry(segment).or_default();
+                group.count += 1;
+            }
+            group.items.push((index, label_start));
+        }
+
+        let mut rows = vec![];
+        self.flatten(&root, "", 0, &mut rows);
+        rows
+    }
+
+    fn flatten(&self, group: &Group, path: &str, depth: usize, rows: &mut Vec<PaneRow>) {
+        for (segment, child) in &group.groups {
+            let path = format!("{}/{}", path, segment);
+            let collapsed = self.collapsed.contains(&path);
+            rows.push(PaneRow::Namespace {
+                path: path.clone(),
+                depth,
+                count: child.count,
+                collapsed,
+            });
+            if !collapsed {
+                self.flatten(child, &path, depth + 1, rows);
+            }
+        }
+        rows.extend(
+            group
+                .items
+                .iter()
+                .map(|&(index, label_start)| PaneRow::Item {
+                    index,
+                    depth,
+                    label_start,
+                }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 4] = [
+        "/robot/arm/joint_states",
+        "/robot/lidar/scan",
+        "/robot/odom",
+        "/tf",
+    ];
+
+    fn namespace(path: &str, depth: usize, count: usize, collapsed: bool) -> PaneRow {
+        PaneRow::Namespace {
+            path: path.to_string(),
+            depth,
+            count,
+            collapsed,
+        }
+    }
+
+    fn item(index: usize, depth: usize, label_start: usize) -> PaneRow {
+        PaneRow::Item {
+            index,
+            depth,
+            label_start,
+        }
+    }
+
+    fn tree_view() -> NamespaceTree {
+        let mut tree = NamespaceTree::default();
+        tree.toggle_view();
+        tree
+    }
+
+    #[test]
+    fn flat_view_lists_the_items_in_order() {
+        let rows = NamespaceTree::default().rows(NAMES.into_iter());
+        assert_eq!(rows, (0..4).map(|i| item(i, 0, 0)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tree_view_nests_namespaces_before_their_items() {
+        assert_eq!(
+            tree_view().rows(NAMES.into_iter()),
+            [
+                namespace("/robot", 0, 3, false),
+                namespace("/robot/arm", 1, 1, false),
+                item(0, 2, 11),
+                namespace("/robot/lidar", 1, 1, false),
+                item(1, 2, 13),
+                item(2, 1, 7),
+                item(3, 0, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn folding_hides_everything_below_a_namespace() {
+        let mut tree = tree_view();
+        tree.toggle_fold("/robot/arm");
+        assert_eq!(
+            tree.rows(NAMES.into_iter())[..3],
+            [
+                namespace("/robot", 0, 3, false),
+                namespace("/robot/arm", 1, 1, true),
+                namespace("/robot/lidar", 1, 1, false),
+            ]
+        );
+
+        // Folding a parent keeps the fold of its children for later
+        tree.toggle_fold("/robot");
+        assert_eq!(
+            tree.rows(NAMES.into_iter()),
+            [namespace("/robot", 0, 3, true), item(3, 0, 1)]
+        );
+        tree.toggle_fold("/robot");
+        assert_eq!(
+            tree.rows(NAMES.into_iter())[1],
+            namespace("/robot/arm", 1, 1, true)
+        );
+
+        tree.toggle_fold("/robot/arm");
+        assert_eq!(
+            tree.rows(NAMES.into_iter()),
+            tree_view().rows(NAMES.into_iter())
+        );
+    }
+
+    #[test]
+    fn namespace_keys_differ_from_items_of_the_same_name() {
+        let name = |_| "/robot";
+        assert_eq!(item(0, 0, 0).key(name), "/robot");
+        assert_eq!(namespace("/robot", 0, 1, false).key(name), "/robot/");
+    }
+}